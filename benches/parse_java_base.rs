@@ -0,0 +1,81 @@
+// Compares parsing every class of java.base into the owned ClassFile with the borrowed
+// ClassFileRef. Run with `cargo bench`, JAVA_HOME has to point at a JDK with jmods.
+
+use std::{
+    env,
+    fs::File,
+    hint::black_box,
+    io::Read,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use jvm::parser::{class::ClassFile, class_ref::ClassFileRef};
+use zip::ZipArchive;
+
+const RUNS: usize = 5;
+
+fn java_base_classes() -> Vec<Vec<u8>> {
+    let jmod = PathBuf::from(env::var("JAVA_HOME").expect("JAVA_HOME is not set"))
+        .join("jmods/java.base.jmod");
+    let mut archive = ZipArchive::new(File::open(jmod).unwrap()).unwrap();
+
+    let mut classes = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).unwrap();
+        if entry.name().starts_with("classes/") && entry.name().ends_with(".class") {
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data).unwrap();
+            classes.push(data);
+        }
+    }
+
+    classes
+}
+
+/// The fastest of several runs of `f` over all classes.
+fn bench(name: &str, classes: &[Vec<u8>], f: impl Fn(&[u8]) -> usize) {
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        for data in classes {
+            black_box(f(data));
+        }
+        best = best.min(start.elapsed());
+    }
+
+    println!(
+        "{name:<40} {:>8.1} ms, {:>6.2} µs per class",
+        best.as_secs_f64() * 1000.0,
+        best.as_secs_f64() * 1_000_000.0 / classes.len() as f64
+    );
+}
+
+fn main() {
+    let classes = java_base_classes();
+    println!("{} classes from java.base", classes.len());
+
+    bench("ClassFile::from_bytes", &classes, |data| {
+        ClassFile::from_bytes(data).unwrap().methods.len()
+    });
+    bench("ClassFileRef::new", &classes, |data| {
+        ClassFileRef::new(data).unwrap().methods().unwrap().count()
+    });
+
+    bench("ClassFile method names", &classes, |data| {
+        let class_file = ClassFile::from_bytes(data).unwrap();
+        class_file
+            .methods
+            .iter()
+            .map(|method| method.name(&class_file.constant_pool).unwrap().len())
+            .sum()
+    });
+    bench("ClassFileRef method names", &classes, |data| {
+        let class_ref = ClassFileRef::new(data).unwrap();
+        class_ref
+            .methods()
+            .unwrap()
+            .map(|method| class_ref.utf8(method.name_index()).unwrap().len())
+            .sum()
+    });
+}
@@ -0,0 +1,805 @@
+// Prints a class file in the style of `javap -v`.
+// https://docs.oracle.com/en/java/javase/17/docs/specs/man/javap.html
+
+use std::{fmt::Debug, path::PathBuf, process::ExitCode};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use jvm::{
+    parser::{
+        attribute::{
+            stack_map_frame::{StackMapFrame, VerificationType},
+            Attribute,
+        },
+        class::{AccessFlag, ClassFile},
+        constant_pool::{ConstantPool, ConstantPoolInfo, Index, MethodHandleKind},
+        field::{Field, FieldFlag},
+        instruction::Instruction,
+        method::{Method, MethodFlag},
+    },
+    ClassIdentifier, ClassPath,
+};
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Cli {
+    /// Directories, jars or jmods to search in addition to $JAVA_HOME/jmods
+    #[arg(short, long)]
+    classpath: Vec<PathBuf>,
+    /// Path to a .class file, or a class name such as java.lang.String
+    class: String,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match disassemble(&cli) {
+        Ok(output) => {
+            print!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn disassemble(cli: &Cli) -> Result<String> {
+    let path = PathBuf::from(&cli.class);
+    let class_file = if path.extension().is_some_and(|e| e == "class") && path.is_file() {
+        ClassFile::from_bytes(&std::fs::read(&path)?)?
+    } else {
+        let identifier = ClassIdentifier::from_utf8(cli.class.replace('.', "/"))
+            .context(format!("invalid class name {}", cli.class))?;
+        let data = ClassPath::load(cli.classpath.clone())?
+            .find(&identifier)?
+            .with_context(|| format!("class {identifier} not found"))?;
+        ClassFile::new(&data, identifier)?
+    };
+
+    let mut out = String::new();
+    class(&mut out, &class_file);
+    Ok(out)
+}
+
+fn class(out: &mut String, class_file: &ClassFile) {
+    let cp = &class_file.constant_pool;
+
+    line(
+        out,
+        0,
+        format!("Classfile {}", class_name(cp, &class_file.this_class_index)),
+    );
+    line(
+        out,
+        2,
+        format!("minor version: {}", class_file.version.minor),
+    );
+    line(
+        out,
+        2,
+        format!("major version: {}", class_file.version.major),
+    );
+    line(
+        out,
+        2,
+        format!(
+            "flags: {}",
+            flags(
+                &class_file.access_flags,
+                AccessFlag::bits(&class_file.access_flags)
+            )
+        ),
+    );
+    line(
+        out,
+        2,
+        with_comment(
+            format!("this_class: #{}", class_file.this_class_index.value()),
+            class_name(cp, &class_file.this_class_index),
+        ),
+    );
+    if class_file.super_class_index.is_zero() {
+        line(out, 2, "super_class: #0".to_string());
+    } else {
+        line(
+            out,
+            2,
+            with_comment(
+                format!("super_class: #{}", class_file.super_class_index.value()),
+                class_name(cp, &class_file.super_class_index),
+            ),
+        );
+    }
+    line(
+        out,
+        2,
+        format!(
+            "interfaces: {}, fields: {}, methods: {}, attributes: {}",
+            class_file.interface_indices.len(),
+            class_file.fields.len(),
+            class_file.methods.len(),
+            class_file.attributes.len()
+        ),
+    );
+
+    line(out, 0, "Constant pool:".to_string());
+    for (index, info) in cp.iter() {
+        let entry = format!(
+            "{:>5} = {:<18} {}",
+            format!("#{}", index.value()),
+            info.tag_name(),
+            operands(info)
+        );
+        match describe(cp, &index) {
+            Some(comment) if !matches!(info, ConstantPoolInfo::Utf { .. }) => {
+                line(out, 1, with_comment(entry, comment))
+            }
+            _ => line(out, 1, entry.trim_end().to_string()),
+        }
+    }
+
+    line(out, 0, "{".to_string());
+    for field in &class_file.fields {
+        self::field(out, cp, field);
+    }
+    for method in &class_file.methods {
+        self::method(out, cp, method);
+    }
+    line(out, 0, "}".to_string());
+
+    attributes(out, 0, cp, &class_file.attributes);
+}
+
+fn field(out: &mut String, cp: &ConstantPool, field: &Field) {
+    line(out, 2, format!("{};", utf8(cp, &field.name_index)));
+    line(
+        out,
+        4,
+        format!("descriptor: {}", utf8(cp, &field.descriptor_index)),
+    );
+    let bits = FieldFlag::bits(&field.access_flags);
+    line(
+        out,
+        4,
+        format!("flags: {}", flags(&field.access_flags, bits)),
+    );
+    attributes(out, 4, cp, &field.attributes);
+    line(out, 0, String::new());
+}
+
+fn method(out: &mut String, cp: &ConstantPool, method: &Method) {
+    line(out, 2, format!("{};", utf8(cp, &method.name_index)));
+    line(
+        out,
+        4,
+        format!("descriptor: {}", utf8(cp, &method.descriptor_index)),
+    );
+    let bits = MethodFlag::bits(&method.access_flags);
+    line(
+        out,
+        4,
+        format!("flags: {}", flags(&method.access_flags, bits)),
+    );
+    attributes(out, 4, cp, &method.attributes);
+    line(out, 0, String::new());
+}
+
+fn attributes(out: &mut String, indent: usize, cp: &ConstantPool, attributes: &[Attribute]) {
+    for attribute in attributes {
+        self::attribute(out, indent, cp, attribute);
+    }
+}
+
+fn attribute(out: &mut String, indent: usize, cp: &ConstantPool, attribute: &Attribute) {
+    match attribute {
+        Attribute::Code {
+            max_stacks,
+            max_locals,
+            code,
+            exceptions,
+            attributes,
+        } => {
+            line(out, indent, "Code:".to_string());
+            line(
+                out,
+                indent + 2,
+                format!("stack={max_stacks}, locals={max_locals}"),
+            );
+            match Instruction::decode(code) {
+                Ok(instructions) => {
+                    for (offset, instruction) in &instructions {
+                        self::instruction(out, indent + 2, cp, *offset, instruction);
+                    }
+                }
+                Err(err) => line(out, indent + 2, format!("<invalid bytecode: {err}>")),
+            }
+
+            if !exceptions.is_empty() {
+                line(out, indent + 2, "Exception table:".to_string());
+                line(out, indent + 5, "from    to  target type".to_string());
+                for exception in exceptions {
+                    let catch_type = if exception.catch_type == 0 {
+                        "any".to_string()
+                    } else {
+                        format!(
+                            "Class {}",
+                            class_name(cp, &Index::new(exception.catch_type))
+                        )
+                    };
+                    line(
+                        out,
+                        indent + 4,
+                        format!(
+                            "{:>5} {:>5} {:>5}   {catch_type}",
+                            exception.start_pc, exception.end_pc, exception.handler_pc
+                        ),
+                    );
+                }
+            }
+
+            self::attributes(out, indent + 2, cp, attributes);
+        }
+        Attribute::LineNumberTable { table } => {
+            line(out, indent, "LineNumberTable:".to_string());
+            for entry in table {
+                line(
+                    out,
+                    indent + 2,
+                    format!("line {}: {}", entry.line_number, entry.start_pc),
+                );
+            }
+        }
+        Attribute::LocalVariableTable {
+            local_variable_table,
+        } => {
+            line(out, indent, "LocalVariableTable:".to_string());
+            line(
+                out,
+                indent + 2,
+                "Start  Length  Slot  Name   Signature".to_string(),
+            );
+            for variable in local_variable_table {
+                line(
+                    out,
+                    indent + 2,
+                    format!(
+                        "{:>5}  {:>6}  {:>4}  {:>4}   {}",
+                        variable.start_pc,
+                        variable.length,
+                        variable.index,
+                        utf8(cp, &variable.name_index),
+                        utf8(cp, &variable.descriptor_index)
+                    ),
+                );
+            }
+        }
+        Attribute::LocalVariableTypeTable {
+            local_variable_type_table,
+        } => {
+            line(out, indent, "LocalVariableTypeTable:".to_string());
+            line(
+                out,
+                indent + 2,
+                "Start  Length  Slot  Name   Signature".to_string(),
+            );
+            for variable in local_variable_type_table {
+                line(
+                    out,
+                    indent + 2,
+                    format!(
+                        "{:>5}  {:>6}  {:>4}  {:>4}   {}",
+                        variable.start_pc,
+                        variable.length,
+                        variable.index,
+                        utf8(cp, &variable.name_index),
+                        utf8(cp, &variable.signature_index)
+                    ),
+                );
+            }
+        }
+        Attribute::StackMapTable { entries } => {
+            line(
+                out,
+                indent,
+                format!("StackMapTable: number_of_entries = {}", entries.len()),
+            );
+            for frame in entries {
+                stack_map_frame(out, indent + 2, cp, frame);
+            }
+        }
+        Attribute::SourceFile { source_file_index } => {
+            line(
+                out,
+                indent,
+                format!("SourceFile: \"{}\"", utf8(cp, source_file_index)),
+            );
+        }
+        Attribute::ConstantValue {
+            constant_value_index,
+        } => {
+            line(
+                out,
+                indent,
+                format!(
+                    "ConstantValue: {}",
+                    reference(cp, constant_value_index).unwrap_or_default()
+                ),
+            );
+        }
+        Attribute::Signature { signature_index } => {
+            line(
+                out,
+                indent,
+                with_comment(
+                    format!("Signature: #{}", signature_index.value()),
+                    utf8(cp, signature_index),
+                ),
+            );
+        }
+        Attribute::Exceptions {
+            exception_index_table,
+        } => {
+            line(out, indent, "Exceptions:".to_string());
+            for index in exception_index_table {
+                line(out, indent + 2, format!("throws {}", class_name(cp, index)));
+            }
+        }
+        Attribute::Deprecated => line(out, indent, "Deprecated: true".to_string()),
+        Attribute::NestHost { host_class_index } => {
+            line(
+                out,
+                indent,
+                format!("NestHost: class {}", class_name(cp, host_class_index)),
+            );
+        }
+        Attribute::NestMembers { classes } | Attribute::PermittedSubclasses { classes } => {
+            line(out, indent, format!("{}:", attribute.name()));
+            for index in classes {
+                line(out, indent + 2, class_name(cp, index));
+            }
+        }
+        Attribute::EnclosingMethod {
+            class_index,
+            method_index,
+        } => {
+            let method = if method_index.is_zero() {
+                String::new()
+            } else {
+                format!(".{}", describe(cp, method_index).unwrap_or_default())
+            };
+            line(
+                out,
+                indent,
+                with_comment(
+                    format!(
+                        "EnclosingMethod: #{}.#{}",
+                        class_index.value(),
+                        method_index.value()
+                    ),
+                    format!("{}{method}", class_name(cp, class_index)),
+                ),
+            );
+        }
+        Attribute::InnerClasses { classes } => {
+            line(out, indent, "InnerClasses:".to_string());
+            for class in classes {
+                let names: Vec<String> = class
+                    .inner_class_access_flags
+                    .iter()
+                    .map(|f| format!("{f:?}").to_lowercase())
+                    .collect();
+                let mut comment = class_name(cp, &class.inner_class_info_index);
+                if !class.inner_name_index.is_zero() {
+                    comment = format!("{}={comment}", utf8(cp, &class.inner_name_index));
+                }
+                if !class.outer_class_info_index.is_zero() {
+                    comment = format!(
+                        "{comment} of {}",
+                        class_name(cp, &class.outer_class_info_index)
+                    );
+                }
+                line(
+                    out,
+                    indent + 2,
+                    with_comment(
+                        format!(
+                            "{}#{}= #{} of #{};",
+                            names.iter().map(|n| format!("{n} ")).collect::<String>(),
+                            class.inner_name_index.value(),
+                            class.inner_class_info_index.value(),
+                            class.outer_class_info_index.value()
+                        ),
+                        comment,
+                    ),
+                );
+            }
+        }
+        Attribute::BootstrapMethods { bootstrap_methods } => {
+            line(out, indent, "BootstrapMethods:".to_string());
+            for (i, method) in bootstrap_methods.iter().enumerate() {
+                let index = Index::new(method.bootstrap_method_ref);
+                line(
+                    out,
+                    indent + 2,
+                    format!(
+                        "{i}: #{} {}",
+                        index.value(),
+                        describe(cp, &index).unwrap_or_default()
+                    ),
+                );
+                line(out, indent + 4, "Method arguments:".to_string());
+                for argument in &method.bootstrap_arguments {
+                    let index = Index::new(*argument);
+                    line(
+                        out,
+                        indent + 6,
+                        format!("#{argument} {}", describe(cp, &index).unwrap_or_default()),
+                    );
+                }
+            }
+        }
+        _ => line(out, indent, format!("{}: ...", attribute.name())),
+    }
+}
+
+fn instruction(
+    out: &mut String,
+    indent: usize,
+    cp: &ConstantPool,
+    offset: usize,
+    instruction: &Instruction,
+) {
+    let mnemonic = instruction.mnemonic();
+    let prefix = format!("{offset:>5}: ");
+
+    match instruction {
+        Instruction::TableSwitch {
+            default,
+            low,
+            high,
+            offsets,
+        } => {
+            line(
+                out,
+                indent,
+                format!("{prefix}{mnemonic}   {{ // {low} to {high}"),
+            );
+            for (key, delta) in (*low..=*high).zip(offsets) {
+                line(
+                    out,
+                    indent,
+                    format!("{:>19}: {}", key, target(offset, *delta)),
+                );
+            }
+            line(
+                out,
+                indent,
+                format!("{:>19}: {}", "default", target(offset, *default)),
+            );
+            line(out, indent, format!("{:>7}", "}"));
+        }
+        Instruction::LookupSwitch { default, pairs } => {
+            line(
+                out,
+                indent,
+                format!("{prefix}{mnemonic}   {{ // {}", pairs.len()),
+            );
+            for (key, delta) in pairs {
+                line(
+                    out,
+                    indent,
+                    format!("{:>19}: {}", key, target(offset, *delta)),
+                );
+            }
+            line(
+                out,
+                indent,
+                format!("{:>19}: {}", "default", target(offset, *default)),
+            );
+            line(out, indent, format!("{:>7}", "}"));
+        }
+        _ => {
+            let targets = instruction.branch_targets(offset);
+            let text = instruction.to_string();
+            let operands = if let Some(target) = targets.first() {
+                target.to_string()
+            } else {
+                text[mnemonic.len()..].trim().to_string()
+            };
+
+            let text = format!("{prefix}{mnemonic:<13} {operands}");
+            match instruction
+                .constant_pool_index()
+                .and_then(|index| reference(cp, index))
+            {
+                Some(comment) => line(out, indent, with_comment(text, comment)),
+                None => line(out, indent, text.trim_end().to_string()),
+            }
+        }
+    }
+}
+
+fn target(offset: usize, delta: i32) -> i64 {
+    offset as i64 + delta as i64
+}
+
+fn stack_map_frame(out: &mut String, indent: usize, cp: &ConstantPool, frame: &StackMapFrame) {
+    match frame {
+        StackMapFrame::SameFrame { offset_delta } => {
+            line(
+                out,
+                indent,
+                format!("frame_type = {offset_delta} /* same */"),
+            );
+        }
+        StackMapFrame::SameLocals {
+            offset_delta,
+            verification_type,
+        } => {
+            line(
+                out,
+                indent,
+                format!(
+                    "frame_type = {} /* same_locals_1_stack_item */",
+                    offset_delta + 64
+                ),
+            );
+            line(
+                out,
+                indent + 2,
+                format!("stack = [ {} ]", verification(cp, verification_type)),
+            );
+        }
+        StackMapFrame::SameLocalsExtended {
+            offset_delta,
+            verification_type,
+        } => {
+            line(
+                out,
+                indent,
+                "frame_type = 247 /* same_locals_1_stack_item_frame_extended */".to_string(),
+            );
+            line(out, indent + 2, format!("offset_delta = {offset_delta}"));
+            line(
+                out,
+                indent + 2,
+                format!("stack = [ {} ]", verification(cp, verification_type)),
+            );
+        }
+        StackMapFrame::Chop { k, offset_delta } => {
+            line(out, indent, format!("frame_type = {} /* chop */", 251 - k));
+            line(out, indent + 2, format!("offset_delta = {offset_delta}"));
+        }
+        StackMapFrame::SameExtended { offset_delta } => {
+            line(
+                out,
+                indent,
+                "frame_type = 251 /* same_frame_extended */".to_string(),
+            );
+            line(out, indent + 2, format!("offset_delta = {offset_delta}"));
+        }
+        StackMapFrame::Append {
+            offset_delta,
+            locals,
+        } => {
+            line(
+                out,
+                indent,
+                format!("frame_type = {} /* append */", 251 + locals.len()),
+            );
+            line(out, indent + 2, format!("offset_delta = {offset_delta}"));
+            line(
+                out,
+                indent + 2,
+                format!("locals = [ {} ]", verifications(cp, locals)),
+            );
+        }
+        StackMapFrame::Full {
+            offset_delta,
+            locals,
+            stack_items,
+        } => {
+            line(out, indent, "frame_type = 255 /* full_frame */".to_string());
+            line(out, indent + 2, format!("offset_delta = {offset_delta}"));
+            line(
+                out,
+                indent + 2,
+                format!("locals = [ {} ]", verifications(cp, locals)),
+            );
+            line(
+                out,
+                indent + 2,
+                format!("stack = [ {} ]", verifications(cp, stack_items)),
+            );
+        }
+    }
+}
+
+fn verifications(cp: &ConstantPool, types: &[VerificationType]) -> String {
+    types
+        .iter()
+        .map(|t| verification(cp, t))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn verification(cp: &ConstantPool, verification_type: &VerificationType) -> String {
+    match verification_type {
+        VerificationType::Top => "top".to_string(),
+        VerificationType::Integer => "int".to_string(),
+        VerificationType::Float => "float".to_string(),
+        VerificationType::Double => "double".to_string(),
+        VerificationType::Long => "long".to_string(),
+        VerificationType::Null => "null".to_string(),
+        VerificationType::UninitializedThis => "uninitialized_this".to_string(),
+        VerificationType::Object { cpool_index } => {
+            format!("class {}", class_name(cp, cpool_index))
+        }
+        VerificationType::Uninitialized { offset } => format!("uninitialized {offset}"),
+    }
+}
+
+fn operands(info: &ConstantPoolInfo) -> String {
+    match info {
+        ConstantPoolInfo::Reserved => String::new(),
+        ConstantPoolInfo::FieldRef {
+            class_index,
+            name_and_type_index,
+        }
+        | ConstantPoolInfo::MethodRef {
+            class_index,
+            name_and_type_index,
+        }
+        | ConstantPoolInfo::InterfaceMethodRef {
+            class_index,
+            name_and_type_index,
+        } => format!("#{}.#{}", class_index.value(), name_and_type_index.value()),
+        ConstantPoolInfo::String { string_index } => format!("#{}", string_index.value()),
+        ConstantPoolInfo::ClassInfo { name_index }
+        | ConstantPoolInfo::Module { name_index }
+        | ConstantPoolInfo::Package { name_index } => format!("#{}", name_index.value()),
+        ConstantPoolInfo::NameAndType {
+            name_index,
+            descriptor_index,
+        } => format!("#{}:#{}", name_index.value(), descriptor_index.value()),
+        ConstantPoolInfo::Utf { text } => text.to_string(),
+        ConstantPoolInfo::InvokeDynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        }
+        | ConstantPoolInfo::Dynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } => format!(
+            "#{bootstrap_method_attr_index}:#{}",
+            name_and_type_index.value()
+        ),
+        ConstantPoolInfo::Integer(val) => val.to_string(),
+        ConstantPoolInfo::MethodHandle {
+            reference_kind,
+            reference_index,
+        } => format!("{reference_kind}:#{}", reference_index.value()),
+        ConstantPoolInfo::MethodType { descriptor_index } => {
+            format!("#{}", descriptor_index.value())
+        }
+        ConstantPoolInfo::Long(val) => format!("{val}l"),
+        ConstantPoolInfo::Float(val) => format!("{val:?}f"),
+        ConstantPoolInfo::Double(val) => format!("{val:?}d"),
+    }
+}
+
+/// The resolved form of an entry as javap shows it after `//`.
+fn describe(cp: &ConstantPool, index: &Index) -> Option<String> {
+    Some(match cp.get(index)? {
+        ConstantPoolInfo::Reserved => return None,
+        ConstantPoolInfo::FieldRef {
+            class_index,
+            name_and_type_index,
+        }
+        | ConstantPoolInfo::MethodRef {
+            class_index,
+            name_and_type_index,
+        }
+        | ConstantPoolInfo::InterfaceMethodRef {
+            class_index,
+            name_and_type_index,
+        } => format!(
+            "{}.{}",
+            describe(cp, class_index)?,
+            describe(cp, name_and_type_index)?
+        ),
+        ConstantPoolInfo::String { string_index } => cp.java_string(string_index)?.to_string(),
+        ConstantPoolInfo::ClassInfo { name_index }
+        | ConstantPoolInfo::Module { name_index }
+        | ConstantPoolInfo::Package { name_index } => cp.utf8(name_index)?,
+        ConstantPoolInfo::NameAndType {
+            name_index,
+            descriptor_index,
+        } => {
+            let name = cp.utf8(name_index)?;
+            let name = if name.starts_with('<') {
+                format!("\"{name}\"")
+            } else {
+                name
+            };
+            format!("{name}:{}", cp.utf8(descriptor_index)?)
+        }
+        ConstantPoolInfo::Utf { text } => text.to_string(),
+        ConstantPoolInfo::InvokeDynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        }
+        | ConstantPoolInfo::Dynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } => format!(
+            "#{bootstrap_method_attr_index}:{}",
+            describe(cp, name_and_type_index)?
+        ),
+        ConstantPoolInfo::Integer(val) => val.to_string(),
+        ConstantPoolInfo::MethodHandle {
+            reference_kind,
+            reference_index,
+        } => format!(
+            "{} {}",
+            MethodHandleKind::new(*reference_kind)
+                .map_or(format!("REF_{reference_kind}"), |kind| kind.to_string()),
+            describe(cp, reference_index)?
+        ),
+        ConstantPoolInfo::MethodType { descriptor_index } => cp.utf8(descriptor_index)?,
+        ConstantPoolInfo::Long(val) => format!("{val}l"),
+        ConstantPoolInfo::Float(val) => format!("{val:?}f"),
+        ConstantPoolInfo::Double(val) => format!("{val:?}d"),
+    })
+}
+
+/// An entry referenced from bytecode or an attribute, prefixed with its kind.
+fn reference(cp: &ConstantPool, index: &Index) -> Option<String> {
+    let kind = match cp.get(index)? {
+        ConstantPoolInfo::FieldRef { .. } => "Field",
+        ConstantPoolInfo::MethodRef { .. } => "Method",
+        ConstantPoolInfo::InterfaceMethodRef { .. } => "InterfaceMethod",
+        ConstantPoolInfo::String { .. } => "String",
+        ConstantPoolInfo::ClassInfo { .. } => "class",
+        ConstantPoolInfo::Integer(_) => "int",
+        ConstantPoolInfo::Long(_) => "long",
+        ConstantPoolInfo::Float(_) => "float",
+        ConstantPoolInfo::Double(_) => "double",
+        ConstantPoolInfo::InvokeDynamic { .. } => "InvokeDynamic",
+        ConstantPoolInfo::Dynamic { .. } => "Dynamic",
+        ConstantPoolInfo::MethodHandle { .. } => "MethodHandle",
+        ConstantPoolInfo::MethodType { .. } => "MethodType",
+        _ => return describe(cp, index),
+    };
+
+    Some(format!("{kind} {}", describe(cp, index)?))
+}
+
+fn class_name(cp: &ConstantPool, index: &Index) -> String {
+    describe(cp, index).unwrap_or_else(|| format!("<invalid #{}>", index.value()))
+}
+
+fn utf8(cp: &ConstantPool, index: &Index) -> String {
+    cp.utf8(index)
+        .unwrap_or_else(|| format!("<invalid #{}>", index.value()))
+}
+
+fn flags<T: Debug>(flags: &[T], bits: u16) -> String {
+    let names: Vec<String> = flags
+        .iter()
+        .map(|f| format!("ACC_{}", format!("{f:?}").to_uppercase()))
+        .collect();
+    format!("(0x{bits:04x}) {}", names.join(", "))
+}
+
+fn with_comment(text: String, comment: String) -> String {
+    format!("{text:<40}// {comment}")
+}
+
+fn line(out: &mut String, indent: usize, text: String) {
+    out.push_str(&" ".repeat(indent));
+    out.push_str(&text);
+    out.push('\n');
+}
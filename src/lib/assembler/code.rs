@@ -0,0 +1,649 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Context, Result};
+use lazy_static::lazy_static;
+
+use crate::parser::{
+    attribute::{exception::Exception, Attribute, Attributes},
+    constant_pool::{ConstantPool, ConstantPoolInfo, Index},
+    descriptor::{FieldType, MethodDescriptor},
+    instruction::{ArrayType, Instruction},
+    method::{Method, MethodFlag},
+};
+
+use super::{argument_slots, class, utf8, Token};
+
+lazy_static! {
+    /// Instructions without operands by mnemonic, found by decoding every opcode on its own.
+    static ref SIMPLE_INSTRUCTIONS: HashMap<&'static str, Instruction> = (0..=u8::MAX)
+        .filter_map(|opcode| Instruction::decode(&[opcode]).ok())
+        .flatten()
+        .map(|(_, instruction)| (instruction.mnemonic(), instruction))
+        .collect();
+}
+
+/// Mnemonics of the instructions with operands that are not branches or local variable
+/// instructions, to tell wrong operands from unknown instructions.
+const OPERAND_MNEMONICS: [&str; 23] = [
+    "bipush",
+    "sipush",
+    "iinc",
+    "ldc",
+    "ldc_w",
+    "ldc2_w",
+    "getstatic",
+    "putstatic",
+    "getfield",
+    "putfield",
+    "invokevirtual",
+    "invokespecial",
+    "invokestatic",
+    "invokeinterface",
+    "invokedynamic",
+    "new",
+    "newarray",
+    "anewarray",
+    "checkcast",
+    "instanceof",
+    "multianewarray",
+    "tableswitch",
+    "lookupswitch",
+];
+
+/// The body of a method between `.method` and `.end method`, whose labels are resolved once
+/// it is complete.
+pub(super) struct Code {
+    name: String,
+    descriptor: String,
+    access_flags: Vec<MethodFlag>,
+    max_stack: u16,
+    max_locals: Option<u16>,
+    items: Vec<Item>,
+    labels: HashSet<String>,
+    catches: Vec<Catch>,
+    throws: Vec<Index>,
+    /// A `tableswitch` or `lookupswitch` whose targets are being read.
+    switch: Option<Switch>,
+}
+
+enum Item {
+    Label(String),
+    Instruction(Instruction),
+    Branch { branch: Branch, label: String },
+    Switch(Switch),
+}
+
+#[derive(Clone, Copy)]
+enum Branch {
+    Short(fn(i16) -> Instruction),
+    Wide(fn(i32) -> Instruction),
+}
+
+enum Switch {
+    Table {
+        low: i32,
+        labels: Vec<String>,
+        default: String,
+    },
+    Lookup {
+        pairs: Vec<(i32, String)>,
+        default: String,
+    },
+}
+
+struct Catch {
+    catch_type: Index,
+    start: String,
+    end: String,
+    handler: String,
+}
+
+impl Code {
+    pub(super) fn new(name: &str, descriptor: &str, access_flags: Vec<MethodFlag>) -> Result<Self> {
+        MethodDescriptor::new(descriptor)?;
+
+        Ok(Self {
+            name: name.to_string(),
+            descriptor: descriptor.to_string(),
+            access_flags,
+            max_stack: 0,
+            max_locals: None,
+            items: Vec::new(),
+            labels: HashSet::new(),
+            catches: Vec::new(),
+            throws: Vec::new(),
+            switch: None,
+        })
+    }
+
+    pub(super) fn line(
+        &mut self,
+        tokens: &[Token],
+        constant_pool: &mut ConstantPool,
+    ) -> Result<()> {
+        if self.switch.is_some() {
+            return self.switch_line(tokens);
+        }
+
+        let tokens = match tokens {
+            [Token::Word(word), rest @ ..] if word.len() > 1 && word.ends_with(':') => {
+                let label = &word[..word.len() - 1];
+                if !self.labels.insert(label.to_string()) {
+                    bail!("label {label} is already defined");
+                }
+                self.items.push(Item::Label(label.to_string()));
+                rest
+            }
+            tokens => tokens,
+        };
+
+        let Some((mnemonic, operands)) = tokens.split_first() else {
+            return Ok(());
+        };
+
+        match (mnemonic.word()?, operands) {
+            (".limit", [kind, value]) => {
+                let value = value.word()?.parse().context("invalid limit")?;
+                match kind.word()? {
+                    "stack" => self.max_stack = value,
+                    "locals" => self.max_locals = Some(value),
+                    kind => bail!("unknown limit {kind}"),
+                }
+            }
+            (".catch", [catch_type, from, start, to, end, using, handler])
+                if from.word()? == "from" && to.word()? == "to" && using.word()? == "using" =>
+            {
+                let catch_type = match catch_type.word()? {
+                    "all" => Index::new(0u16),
+                    name => class(constant_pool, name)?,
+                };
+                self.catches.push(Catch {
+                    catch_type,
+                    start: start.word()?.to_string(),
+                    end: end.word()?.to_string(),
+                    handler: handler.word()?.to_string(),
+                });
+            }
+            (".catch", _) => bail!(".catch takes <class> from <label> to <label> using <label>"),
+            (".throws", [name]) => self.throws.push(class(constant_pool, name.word()?)?),
+            ("tableswitch", [low]) => {
+                self.switch = Some(Switch::Table {
+                    low: number(low)?,
+                    labels: Vec::new(),
+                    default: String::new(),
+                })
+            }
+            ("lookupswitch", []) => {
+                self.switch = Some(Switch::Lookup {
+                    pairs: Vec::new(),
+                    default: String::new(),
+                })
+            }
+            (".limit", _) => bail!(".limit takes stack or locals and a value"),
+            (".throws", _) => bail!(".throws takes a class name"),
+            (directive, _) if directive.starts_with('.') => bail!("unknown directive {directive}"),
+            (mnemonic, operands) => {
+                let item = instruction(mnemonic, operands, constant_pool)?;
+                self.items.push(item);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A target of the switch being read, `<label>` for `tableswitch` and `<key> : <label>`
+    /// for `lookupswitch`, until `default : <label>` ends it.
+    fn switch_line(&mut self, tokens: &[Token]) -> Result<()> {
+        let text = tokens
+            .iter()
+            .map(Token::word)
+            .collect::<Result<Vec<_>>>()?
+            .concat();
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let (key, label) = match text.split_once(':') {
+            Some((key, label)) => (Some(key), label.to_string()),
+            None => (None, text.clone()),
+        };
+
+        let mut switch = self.switch.take().context("no switch")?;
+        match (&mut switch, key) {
+            (Switch::Table { default, .. } | Switch::Lookup { default, .. }, Some("default")) => {
+                *default = label;
+                self.items.push(Item::Switch(switch));
+                return Ok(());
+            }
+            (Switch::Table { labels, .. }, None) => labels.push(label),
+            (Switch::Lookup { pairs, .. }, Some(key)) => {
+                pairs.push((key.parse().context(format!("invalid key {key}"))?, label))
+            }
+            (Switch::Table { .. }, Some(_)) => bail!("tableswitch targets take no key"),
+            (Switch::Lookup { .. }, None) => bail!("lookupswitch targets take a key"),
+        }
+
+        self.switch = Some(switch);
+        Ok(())
+    }
+
+    pub(super) fn method(self, constant_pool: &mut ConstantPool) -> Result<Method> {
+        if self.switch.is_some() {
+            bail!("missing default target of switch");
+        }
+
+        let mut attributes = Vec::new();
+        let has_code = !self
+            .access_flags
+            .iter()
+            .any(|flag| matches!(flag, MethodFlag::Abstract | MethodFlag::Native));
+        if has_code {
+            let (code, labels) = self.code()?;
+            let label = |name: &String| -> Result<u16> {
+                Ok(u16::try_from(
+                    *labels
+                        .get(name)
+                        .context(format!("undefined label {name}"))?,
+                )?)
+            };
+
+            let mut exceptions = Vec::new();
+            for catch in &self.catches {
+                exceptions.push(Exception {
+                    start_pc: label(&catch.start)?,
+                    end_pc: label(&catch.end)?,
+                    handler_pc: label(&catch.handler)?,
+                    catch_type: u16::try_from(catch.catch_type.value())?,
+                });
+            }
+
+            let is_static = self.access_flags.contains(&MethodFlag::Static);
+            let max_locals = match self.max_locals {
+                Some(max_locals) => max_locals,
+                None => u16::try_from(argument_slots(
+                    &MethodDescriptor::new(&self.descriptor)?,
+                    is_static,
+                ))?,
+            };
+
+            utf8(constant_pool, "Code")?;
+            attributes.push(Attribute::Code {
+                max_stacks: self.max_stack,
+                max_locals,
+                code,
+                exceptions,
+                attributes: Attributes::default(),
+            });
+        } else if !self.items.is_empty() {
+            bail!("abstract and native methods have no code");
+        }
+
+        if !self.throws.is_empty() {
+            utf8(constant_pool, "Exceptions")?;
+            attributes.push(Attribute::Exceptions {
+                exception_index_table: self.throws,
+            });
+        }
+
+        Ok(Method {
+            access_flags: self.access_flags,
+            name_index: utf8(constant_pool, &self.name)?,
+            descriptor_index: utf8(constant_pool, &self.descriptor)?,
+            attributes: attributes.into(),
+        })
+    }
+
+    /// Encodes the instructions, once to find the offsets of the labels and once more with
+    /// the branches pointing at them.
+    fn code(&self) -> Result<(Vec<u8>, HashMap<String, usize>)> {
+        let mut labels = HashMap::new();
+        let mut code = Vec::new();
+        for item in &self.items {
+            match item {
+                Item::Label(label) => {
+                    labels.insert(label.clone(), code.len());
+                }
+                item => item
+                    .instruction(code.len(), &|_, _| Ok(0))?
+                    .write(&mut code)?,
+            }
+        }
+
+        let target = |label: &str, offset: usize| -> Result<i32> {
+            let target = labels
+                .get(label)
+                .context(format!("undefined label {label}"))?;
+            Ok(*target as i32 - offset as i32)
+        };
+        let mut code = Vec::new();
+        for item in &self.items {
+            if !matches!(item, Item::Label(_)) {
+                item.instruction(code.len(), &target)?.write(&mut code)?;
+            }
+        }
+
+        Ok((code, labels))
+    }
+}
+
+impl Item {
+    /// The instruction at `offset`, with `target` giving the offset of a label relative to it.
+    fn instruction(
+        &self,
+        offset: usize,
+        target: &dyn Fn(&str, usize) -> Result<i32>,
+    ) -> Result<Instruction> {
+        Ok(match self {
+            Item::Label(label) => bail!("label {label} is not an instruction"),
+            Item::Instruction(instruction) => instruction.clone(),
+            Item::Branch {
+                branch: Branch::Short(branch),
+                label,
+            } => branch(
+                i16::try_from(target(label, offset)?)
+                    .context(format!("branch to {label} is out of range"))?,
+            ),
+            Item::Branch {
+                branch: Branch::Wide(branch),
+                label,
+            } => branch(target(label, offset)?),
+            Item::Switch(Switch::Table {
+                low,
+                labels,
+                default,
+            }) => {
+                if labels.is_empty() {
+                    bail!("tableswitch has no targets");
+                }
+                Instruction::TableSwitch {
+                    default: target(default, offset)?,
+                    low: *low,
+                    high: low + labels.len() as i32 - 1,
+                    offsets: labels
+                        .iter()
+                        .map(|label| target(label, offset))
+                        .collect::<Result<_>>()?,
+                }
+            }
+            Item::Switch(Switch::Lookup { pairs, default }) => {
+                let mut pairs = pairs
+                    .iter()
+                    .map(|(key, label)| Ok((*key, target(label, offset)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                pairs.sort_by_key(|(key, _)| *key);
+                Instruction::LookupSwitch {
+                    default: target(default, offset)?,
+                    pairs,
+                }
+            }
+        })
+    }
+}
+
+fn instruction(
+    mnemonic: &str,
+    operands: &[Token],
+    constant_pool: &mut ConstantPool,
+) -> Result<Item> {
+    if let Some(branch) = branch(mnemonic) {
+        let [label] = operands else {
+            bail!("{mnemonic} takes a label");
+        };
+        return Ok(Item::Branch {
+            branch,
+            label: label.word()?.to_string(),
+        });
+    }
+
+    if let Some(local) = local(mnemonic) {
+        let [index] = operands else {
+            bail!("{mnemonic} takes a local variable index");
+        };
+        let index: u16 = number(index)?;
+        return Ok(Item::Instruction(wide(
+            index > u8::MAX as u16,
+            local(index),
+        )));
+    }
+
+    let instruction = match (mnemonic, operands) {
+        (mnemonic, []) if SIMPLE_INSTRUCTIONS.contains_key(mnemonic) => {
+            SIMPLE_INSTRUCTIONS[mnemonic].clone()
+        }
+        (mnemonic, _) if SIMPLE_INSTRUCTIONS.contains_key(mnemonic) => {
+            bail!("{mnemonic} takes no operands")
+        }
+        ("bipush", [value]) => Instruction::Bipush(number(value)?),
+        ("sipush", [value]) => Instruction::Sipush(number(value)?),
+        ("iinc", [index, constant]) => {
+            let index: u16 = number(index)?;
+            let constant: i16 = number(constant)?;
+            wide(
+                index > u8::MAX as u16 || i8::try_from(constant).is_err(),
+                Instruction::Iinc { index, constant },
+            )
+        }
+        ("ldc" | "ldc_w", [value]) => {
+            let info = constant(value, constant_pool)?;
+            let index = constant_pool.add(info)?;
+            if mnemonic == "ldc" && index.value() <= u8::MAX as usize {
+                Instruction::Ldc(index)
+            } else {
+                Instruction::LdcW(index)
+            }
+        }
+        ("ldc2_w", [value]) => Instruction::Ldc2W(constant_pool.add(wide_constant(value)?)?),
+        ("getstatic" | "putstatic" | "getfield" | "putfield", [member, descriptor]) => {
+            let (class_name, name) = member_name(member.word()?)?;
+            let descriptor = descriptor.word()?;
+            FieldType::new(descriptor)?;
+            let info = ConstantPoolInfo::FieldRef {
+                class_index: class(constant_pool, class_name)?,
+                name_and_type_index: name_and_type(constant_pool, name, descriptor)?,
+            };
+            let index = constant_pool.add(info)?;
+            match mnemonic {
+                "getstatic" => Instruction::GetStatic(index),
+                "putstatic" => Instruction::PutStatic(index),
+                "getfield" => Instruction::GetField(index),
+                _ => Instruction::PutField(index),
+            }
+        }
+        ("invokevirtual" | "invokespecial" | "invokestatic", [member]) => {
+            let (class_index, name_and_type_index, _) = method(member, constant_pool)?;
+            let index = constant_pool.add(ConstantPoolInfo::MethodRef {
+                class_index,
+                name_and_type_index,
+            })?;
+            match mnemonic {
+                "invokevirtual" => Instruction::InvokeVirtual(index),
+                "invokespecial" => Instruction::InvokeSpecial(index),
+                _ => Instruction::InvokeStatic(index),
+            }
+        }
+        ("invokeinterface", [member, count @ ..]) if count.len() <= 1 => {
+            let (class_index, name_and_type_index, descriptor) = method(member, constant_pool)?;
+            let count = match count {
+                [count] => number(count)?,
+                _ => u8::try_from(argument_slots(&descriptor, false))?,
+            };
+            Instruction::InvokeInterface {
+                index: constant_pool.add(ConstantPoolInfo::InterfaceMethodRef {
+                    class_index,
+                    name_and_type_index,
+                })?,
+                count,
+            }
+        }
+        ("invokedynamic", _) => {
+            bail!("invokedynamic needs bootstrap methods, which are not supported")
+        }
+        ("new" | "anewarray" | "checkcast" | "instanceof", [name]) => {
+            let index = class(constant_pool, name.word()?)?;
+            match mnemonic {
+                "new" => Instruction::New(index),
+                "anewarray" => Instruction::ANewArray(index),
+                "checkcast" => Instruction::CheckCast(index),
+                _ => Instruction::InstanceOf(index),
+            }
+        }
+        ("newarray", [array_type]) => Instruction::NewArray(match array_type.word()? {
+            "boolean" => ArrayType::Boolean,
+            "char" => ArrayType::Char,
+            "float" => ArrayType::Float,
+            "double" => ArrayType::Double,
+            "byte" => ArrayType::Byte,
+            "short" => ArrayType::Short,
+            "int" => ArrayType::Int,
+            "long" => ArrayType::Long,
+            array_type => bail!("invalid array type {array_type}"),
+        }),
+        ("multianewarray", [name, dimensions]) => Instruction::MultiANewArray {
+            index: class(constant_pool, name.word()?)?,
+            dimensions: number(dimensions)?,
+        },
+        (mnemonic, _) if OPERAND_MNEMONICS.contains(&mnemonic) => {
+            bail!("invalid operands for {mnemonic}")
+        }
+        (mnemonic, _) => bail!("unknown instruction {mnemonic}"),
+    };
+
+    Ok(Item::Instruction(instruction))
+}
+
+fn branch(mnemonic: &str) -> Option<Branch> {
+    Some(match mnemonic {
+        "ifeq" => Branch::Short(Instruction::Ifeq),
+        "ifne" => Branch::Short(Instruction::Ifne),
+        "iflt" => Branch::Short(Instruction::Iflt),
+        "ifge" => Branch::Short(Instruction::Ifge),
+        "ifgt" => Branch::Short(Instruction::Ifgt),
+        "ifle" => Branch::Short(Instruction::Ifle),
+        "if_icmpeq" => Branch::Short(Instruction::IfIcmpeq),
+        "if_icmpne" => Branch::Short(Instruction::IfIcmpne),
+        "if_icmplt" => Branch::Short(Instruction::IfIcmplt),
+        "if_icmpge" => Branch::Short(Instruction::IfIcmpge),
+        "if_icmpgt" => Branch::Short(Instruction::IfIcmpgt),
+        "if_icmple" => Branch::Short(Instruction::IfIcmple),
+        "if_acmpeq" => Branch::Short(Instruction::IfAcmpeq),
+        "if_acmpne" => Branch::Short(Instruction::IfAcmpne),
+        "goto" => Branch::Short(Instruction::Goto),
+        "jsr" => Branch::Short(Instruction::Jsr),
+        "ifnull" => Branch::Short(Instruction::IfNull),
+        "ifnonnull" => Branch::Short(Instruction::IfNonNull),
+        "goto_w" => Branch::Wide(Instruction::GotoW),
+        "jsr_w" => Branch::Wide(Instruction::JsrW),
+        _ => return None,
+    })
+}
+
+fn local(mnemonic: &str) -> Option<fn(u16) -> Instruction> {
+    Some(match mnemonic {
+        "iload" => Instruction::Iload,
+        "lload" => Instruction::Lload,
+        "fload" => Instruction::Fload,
+        "dload" => Instruction::Dload,
+        "aload" => Instruction::Aload,
+        "istore" => Instruction::Istore,
+        "lstore" => Instruction::Lstore,
+        "fstore" => Instruction::Fstore,
+        "dstore" => Instruction::Dstore,
+        "astore" => Instruction::Astore,
+        "ret" => Instruction::Ret,
+        _ => return None,
+    })
+}
+
+/// `instruction` prefixed by `wide` if its operands do not fit in a byte.
+fn wide(is_wide: bool, instruction: Instruction) -> Instruction {
+    if is_wide {
+        Instruction::Wide(Box::new(instruction))
+    } else {
+        instruction
+    }
+}
+
+fn number<T: std::str::FromStr>(token: &Token) -> Result<T> {
+    let word = token.word()?;
+    word.parse().ok().context(format!("invalid number {word}"))
+}
+
+/// The constant of `ldc`, a string, an int, a float or a class.
+fn constant(token: &Token, constant_pool: &mut ConstantPool) -> Result<ConstantPoolInfo> {
+    let word = match token {
+        Token::String(text) => {
+            return Ok(ConstantPoolInfo::String {
+                string_index: utf8(constant_pool, text)?,
+            })
+        }
+        Token::Word(word) => word,
+    };
+
+    if let Ok(value) = word.parse() {
+        return Ok(ConstantPoolInfo::Integer(value));
+    }
+    if is_numeric(word) {
+        let value = word.strip_suffix(['f', 'F']).unwrap_or(word).parse();
+        return Ok(ConstantPoolInfo::Float(
+            value.context(format!("invalid float {word}"))?,
+        ));
+    }
+
+    Ok(ConstantPoolInfo::ClassInfo {
+        name_index: utf8(constant_pool, word)?,
+    })
+}
+
+/// The constant of `ldc2_w`, a long unless it has a fraction, an exponent or a `d` suffix.
+fn wide_constant(token: &Token) -> Result<ConstantPoolInfo> {
+    let word = token.word()?;
+    if let Ok(value) = word.strip_suffix(['l', 'L']).unwrap_or(word).parse() {
+        return Ok(ConstantPoolInfo::Long(value));
+    }
+    if is_numeric(word) {
+        if let Ok(value) = word.strip_suffix(['d', 'D']).unwrap_or(word).parse() {
+            return Ok(ConstantPoolInfo::Double(value));
+        }
+    }
+
+    bail!("invalid long or double {word}")
+}
+
+fn is_numeric(word: &str) -> bool {
+    word.starts_with(|c: char| c.is_ascii_digit() || matches!(c, '-' | '+' | '.'))
+        || matches!(word, "NaN" | "Infinity")
+}
+
+/// Splits `owner/name` into the class and the member name.
+fn member_name(text: &str) -> Result<(&str, &str)> {
+    text.rsplit_once('/')
+        .context(format!("{text} is not of the form class/name"))
+}
+
+/// The class and NameAndType entries of a method written as `owner/name(descriptor)`.
+fn method(
+    token: &Token,
+    constant_pool: &mut ConstantPool,
+) -> Result<(Index, Index, MethodDescriptor)> {
+    let text = token.word()?;
+    let (member, descriptor) = text.split_at(
+        text.find('(')
+            .context(format!("missing descriptor in {text}"))?,
+    );
+    let (class_name, name) = member_name(member)?;
+    let method_descriptor = MethodDescriptor::new(descriptor)?;
+
+    Ok((
+        class(constant_pool, class_name)?,
+        name_and_type(constant_pool, name, descriptor)?,
+        method_descriptor,
+    ))
+}
+
+fn name_and_type(constant_pool: &mut ConstantPool, name: &str, descriptor: &str) -> Result<Index> {
+    let info = ConstantPoolInfo::NameAndType {
+        name_index: utf8(constant_pool, name)?,
+        descriptor_index: utf8(constant_pool, descriptor)?,
+    };
+    constant_pool.add(info)
+}
@@ -0,0 +1,848 @@
+//! A Jasmin-like assembler, which builds a [`ClassFile`] from a textual description so tests
+//! can craft exactly the bytecode they need.
+//!
+//! ```text
+//! .class public Counter
+//! .super java/lang/Object
+//! .field private static count I = 0
+//!
+//! .method public static main([Ljava/lang/String;)V
+//!     .limit stack 2
+//!     getstatic Counter/count I
+//!     ifne Done
+//!     ldc "first"
+//!     pop
+//! Done:
+//!     return
+//! .end method
+//! ```
+//!
+//! Lines hold one directive or instruction, optionally preceded by a `Label:`, and `;` starts
+//! a comment. Classes are written in internal form (`java/lang/Object`), fields as
+//! `owner/name descriptor` and methods as `owner/name(descriptor)`. Constants are added to
+//! the constant pool as they are used.
+//!
+//! Class files default to version 49.0, whose methods are verified by type inference and
+//! so need no StackMapTable.
+
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::{
+    parser::{
+        attribute::{
+            inner_class::{self, InnerClass},
+            Attribute,
+        },
+        class::{AccessFlag, ClassFile, Version},
+        constant_pool::{ConstantPool, ConstantPoolInfo, Index},
+        descriptor::{FieldType, MethodDescriptor},
+        field::{Field, FieldFlag},
+        method::{Method, MethodFlag},
+        mutf8::JavaString,
+    },
+    ClassIdentifier, Package,
+};
+
+use code::Code;
+
+mod code;
+
+/// Assembles the class described by `source`.
+pub fn assemble(source: &str) -> Result<ClassFile> {
+    let mut assembler = Assembler::default();
+
+    for (i, line) in source.lines().enumerate() {
+        let tokens = tokens(line).and_then(|tokens| assembler.line(&tokens));
+        tokens.map_err(|err| anyhow!("line {}: {err:#}", i + 1))?;
+    }
+
+    if assembler.code.is_some() {
+        bail!("missing .end method");
+    }
+
+    assembler.class_file()
+}
+
+/// Assembles `sources` and writes each class below `dir` at its package path, the layout of a
+/// class path directory.
+pub fn write_classes(dir: &Path, sources: &[&str]) -> Result<Vec<ClassIdentifier>> {
+    let mut identifiers = Vec::new();
+    for source in sources {
+        let class_file = assemble(source)?;
+        let identifier = class_file.this_class.clone();
+
+        let mut path = dir.to_path_buf();
+        if identifier.package != Package::default() {
+            path.extend(identifier.package.name.split('.'));
+        }
+        fs::create_dir_all(&path).with_context(|| format!("cannot create {path:?}"))?;
+        path.push(format!("{}.class", identifier.name));
+        fs::write(&path, class_file.to_bytes()?)
+            .with_context(|| format!("cannot write {path:?}"))?;
+
+        identifiers.push(identifier);
+    }
+
+    Ok(identifiers)
+}
+
+/// A word of a line, with quoted strings kept apart so `"Foo"` is not taken for a class.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    String(String),
+}
+
+impl Token {
+    fn word(&self) -> Result<&str> {
+        match self {
+            Token::Word(word) => Ok(word),
+            Token::String(text) => bail!("unexpected string \"{text}\""),
+        }
+    }
+}
+
+/// Splits a line into tokens, dropping the comment. Comments start with a `;` at the start of
+/// a token, since descriptors contain `;` as well.
+fn tokens(line: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            ';' => break,
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next().context("unterminated string")? {
+                        '"' => break,
+                        '\\' => text.push(escape(&mut chars)?),
+                        c => text.push(c),
+                    }
+                }
+                tokens.push(Token::String(text));
+            }
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '"') {
+                    word.push(c);
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn escape(chars: &mut impl Iterator<Item = char>) -> Result<char> {
+    Ok(match chars.next().context("unterminated string")? {
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        '0' => '\0',
+        '"' => '"',
+        '\\' => '\\',
+        'u' => {
+            let hex: String = chars.take(4).collect();
+            let unit = u32::from_str_radix(&hex, 16).context(format!("invalid escape \\u{hex}"))?;
+            char::from_u32(unit).context(format!("invalid escape \\u{hex}"))?
+        }
+        c => bail!("invalid escape \\{c}"),
+    })
+}
+
+#[derive(Default)]
+struct Assembler {
+    constant_pool: ConstantPool,
+    version: Option<Version>,
+    access_flags: Vec<AccessFlag>,
+    this_class: Option<String>,
+    super_class: Option<String>,
+    interfaces: Vec<String>,
+    fields: Vec<Field>,
+    methods: Vec<Method>,
+    attributes: Vec<Attribute>,
+    nest_members: Vec<Index>,
+    inner_classes: Vec<InnerClass>,
+    /// The method whose body is being assembled.
+    code: Option<Code>,
+}
+
+impl Assembler {
+    fn line(&mut self, tokens: &[Token]) -> Result<()> {
+        if let Some(code) = &mut self.code {
+            if let [Token::Word(directive), Token::Word(kind)] = tokens {
+                if directive == ".end" && kind == "method" {
+                    let code = self.code.take().context("no method")?;
+                    let method = code.method(&mut self.constant_pool)?;
+                    self.methods.push(method);
+                    return Ok(());
+                }
+            }
+
+            return code.line(tokens, &mut self.constant_pool);
+        }
+
+        let Some((directive, operands)) = tokens.split_first() else {
+            return Ok(());
+        };
+
+        match directive.word()? {
+            ".version" => {
+                let (major, minor) = match operands {
+                    [major] => (major, "0"),
+                    [major, minor] => (major, minor.word()?),
+                    _ => bail!(".version takes a major and an optional minor version"),
+                };
+                self.version = Some(Version::new(
+                    major.word()?.parse()?,
+                    minor.parse().context("invalid minor version")?,
+                ));
+            }
+            ".class" | ".interface" => {
+                if self.this_class.is_some() {
+                    bail!("class is already declared");
+                }
+                let (name, flags) = operands.split_last().context("missing class name")?;
+                for flag in flags {
+                    self.access_flags.push(class_flag(flag.word()?)?);
+                }
+                if directive.word()? == ".interface" {
+                    self.access_flags
+                        .extend([AccessFlag::Interface, AccessFlag::Abstract]);
+                } else {
+                    self.access_flags.push(AccessFlag::Super);
+                }
+                self.this_class = Some(name.word()?.to_string());
+            }
+            ".super" => {
+                let [name] = operands else {
+                    bail!(".super takes a class name");
+                };
+                self.super_class = Some(name.word()?.to_string());
+            }
+            ".implements" => {
+                let [name] = operands else {
+                    bail!(".implements takes a class name");
+                };
+                self.interfaces.push(name.word()?.to_string());
+            }
+            ".source" => {
+                let [name] = operands else {
+                    bail!(".source takes a file name");
+                };
+                let index = utf8(&mut self.constant_pool, name.word()?)?;
+                utf8(&mut self.constant_pool, "SourceFile")?;
+                self.attributes.push(Attribute::SourceFile {
+                    source_file_index: index,
+                });
+            }
+            ".nesthost" => {
+                let [name] = operands else {
+                    bail!(".nesthost takes a class name");
+                };
+                let host_class_index = class(&mut self.constant_pool, name.word()?)?;
+                utf8(&mut self.constant_pool, "NestHost")?;
+                self.attributes
+                    .push(Attribute::NestHost { host_class_index });
+            }
+            ".nestmember" => {
+                let [name] = operands else {
+                    bail!(".nestmember takes a class name");
+                };
+                let index = class(&mut self.constant_pool, name.word()?)?;
+                self.nest_members.push(index);
+            }
+            ".inner" => {
+                let inner_class = self.inner_class(operands)?;
+                self.inner_classes.push(inner_class);
+            }
+            ".enclosing" => {
+                let (class_index, method_index) = match operands {
+                    [kind, name] if kind.word()? == "class" => (
+                        class(&mut self.constant_pool, name.word()?)?,
+                        Index::new(0u16),
+                    ),
+                    [kind, method] if kind.word()? == "method" => {
+                        let method = method.word()?;
+                        let (member, descriptor) = method.split_at(
+                            method
+                                .find('(')
+                                .context(format!("missing descriptor in {method}"))?,
+                        );
+                        let (owner, name) = member
+                            .rsplit_once('/')
+                            .context(format!("{member} is not of the form class/name"))?;
+                        MethodDescriptor::new(descriptor)?;
+                        let info = ConstantPoolInfo::NameAndType {
+                            name_index: utf8(&mut self.constant_pool, name)?,
+                            descriptor_index: utf8(&mut self.constant_pool, descriptor)?,
+                        };
+                        (
+                            class(&mut self.constant_pool, owner)?,
+                            self.constant_pool.add(info)?,
+                        )
+                    }
+                    _ => bail!(".enclosing takes class <class> or method <class/name(descriptor)>"),
+                };
+                utf8(&mut self.constant_pool, "EnclosingMethod")?;
+                self.attributes.push(Attribute::EnclosingMethod {
+                    class_index,
+                    method_index,
+                });
+            }
+            ".field" => {
+                let field = self.field(operands)?;
+                self.fields.push(field);
+            }
+            ".method" => {
+                let (header, flags) = operands.split_last().context("missing method name")?;
+                let header = header.word()?;
+                let (name, descriptor) = header.split_at(
+                    header
+                        .find('(')
+                        .context(format!("missing descriptor in {header}"))?,
+                );
+                let access_flags = flags
+                    .iter()
+                    .map(|flag| method_flag(flag.word()?))
+                    .collect::<Result<_>>()?;
+                self.code = Some(Code::new(name, descriptor, access_flags)?);
+            }
+            directive => bail!("unknown directive {directive}"),
+        }
+
+        Ok(())
+    }
+
+    /// `.field <flags> <name> <descriptor> [= <value>]`, the value becoming a ConstantValue.
+    fn field(&mut self, operands: &[Token]) -> Result<Field> {
+        let (operands, value) = match operands {
+            [operands @ .., Token::Word(equals), value] if equals == "=" => (operands, Some(value)),
+            operands => (operands, None),
+        };
+        let [flags @ .., name, descriptor] = operands else {
+            bail!(".field takes a name and a descriptor");
+        };
+
+        let descriptor = descriptor.word()?;
+        let field_type = FieldType::new(descriptor)?;
+        let mut attributes = Vec::new();
+        if let Some(value) = value {
+            let info = match (&field_type, value) {
+                (FieldType::Class(class), Token::String(text)) if class == "java/lang/String" => {
+                    ConstantPoolInfo::String {
+                        string_index: utf8(&mut self.constant_pool, text)?,
+                    }
+                }
+                (
+                    FieldType::Int
+                    | FieldType::Short
+                    | FieldType::Char
+                    | FieldType::Byte
+                    | FieldType::Boolean,
+                    Token::Word(value),
+                ) => ConstantPoolInfo::Integer(value.parse()?),
+                (FieldType::Long, Token::Word(value)) => {
+                    ConstantPoolInfo::Long(value.strip_suffix(['l', 'L']).unwrap_or(value).parse()?)
+                }
+                (FieldType::Float, Token::Word(value)) => ConstantPoolInfo::Float(
+                    value.strip_suffix(['f', 'F']).unwrap_or(value).parse()?,
+                ),
+                (FieldType::Double, Token::Word(value)) => ConstantPoolInfo::Double(
+                    value.strip_suffix(['d', 'D']).unwrap_or(value).parse()?,
+                ),
+                _ => bail!("invalid constant value for a field of type {field_type}"),
+            };
+            let constant_value_index = self.constant_pool.add(info)?;
+            utf8(&mut self.constant_pool, "ConstantValue")?;
+            attributes.push(Attribute::ConstantValue {
+                constant_value_index,
+            });
+        }
+
+        Ok(Field {
+            access_flags: flags
+                .iter()
+                .map(|flag| field_flag(flag.word()?))
+                .collect::<Result<_>>()?,
+            name_index: utf8(&mut self.constant_pool, name.word()?)?,
+            descriptor_index: utf8(&mut self.constant_pool, descriptor)?,
+            attributes: attributes.into(),
+        })
+    }
+
+    /// `.inner <flags> <class> [outer <class>] [name <simple name>]`, an entry of the
+    /// InnerClasses attribute.
+    fn inner_class(&mut self, operands: &[Token]) -> Result<InnerClass> {
+        let mut flags = Vec::new();
+        let mut operands = operands.iter();
+        let inner_class = loop {
+            let word = operands
+                .next()
+                .context("missing inner class name")?
+                .word()?;
+            match inner_class_flag(word) {
+                Some(flag) => flags.push(flag),
+                None => break word,
+            }
+        };
+
+        let mut inner_class = InnerClass {
+            inner_class_info_index: class(&mut self.constant_pool, inner_class)?,
+            outer_class_info_index: Index::new(0u16),
+            inner_name_index: Index::new(0u16),
+            inner_class_access_flags: flags,
+        };
+        while let Some(keyword) = operands.next() {
+            let value = operands
+                .next()
+                .context(format!("missing value of {keyword:?}"))?
+                .word()?;
+            match keyword.word()? {
+                "outer" => {
+                    inner_class.outer_class_info_index = class(&mut self.constant_pool, value)?
+                }
+                "name" => inner_class.inner_name_index = utf8(&mut self.constant_pool, value)?,
+                keyword => bail!("unknown .inner option {keyword}"),
+            }
+        }
+
+        Ok(inner_class)
+    }
+
+    fn class_file(mut self) -> Result<ClassFile> {
+        let name = self.this_class.context("missing .class")?;
+        let this_class = ClassIdentifier::from_utf8(name.clone()).context("invalid class name")?;
+        let this_class_index = class(&mut self.constant_pool, &name)?;
+
+        let super_class_name = self
+            .super_class
+            .unwrap_or_else(|| "java/lang/Object".to_string());
+        let super_class = ClassIdentifier::from_utf8(super_class_name.clone());
+        let super_class_index = class(&mut self.constant_pool, &super_class_name)?;
+
+        let mut interfaces = Vec::new();
+        let mut interface_indices = Vec::new();
+        for interface in self.interfaces {
+            interface_indices.push(class(&mut self.constant_pool, &interface)?);
+            interfaces.push(ClassIdentifier::from_utf8(interface).context("invalid interface")?);
+        }
+
+        if !self.nest_members.is_empty() {
+            utf8(&mut self.constant_pool, "NestMembers")?;
+            self.attributes.push(Attribute::NestMembers {
+                classes: self.nest_members,
+            });
+        }
+        if !self.inner_classes.is_empty() {
+            utf8(&mut self.constant_pool, "InnerClasses")?;
+            self.attributes.push(Attribute::InnerClasses {
+                classes: self.inner_classes,
+            });
+        }
+
+        Ok(ClassFile {
+            version: self.version.unwrap_or(Version::new(49, 0)),
+            this_class,
+            super_class,
+            interfaces,
+            this_class_index,
+            super_class_index,
+            interface_indices,
+            constant_pool: self.constant_pool,
+            methods: self.methods,
+            fields: self.fields,
+            access_flags: self.access_flags,
+            attributes: self.attributes.into(),
+        })
+    }
+}
+
+fn utf8(constant_pool: &mut ConstantPool, text: &str) -> Result<Index> {
+    constant_pool.add(ConstantPoolInfo::Utf {
+        text: JavaString::from(text),
+    })
+}
+
+fn class(constant_pool: &mut ConstantPool, name: &str) -> Result<Index> {
+    let name_index = utf8(constant_pool, name)?;
+    constant_pool.add(ConstantPoolInfo::ClassInfo { name_index })
+}
+
+fn class_flag(name: &str) -> Result<AccessFlag> {
+    Ok(match name {
+        "public" => AccessFlag::Public,
+        "final" => AccessFlag::Final,
+        "super" => AccessFlag::Super,
+        "interface" => AccessFlag::Interface,
+        "abstract" => AccessFlag::Abstract,
+        "synthetic" => AccessFlag::Synthetic,
+        "annotation" => AccessFlag::Annotation,
+        "enum" => AccessFlag::Enum,
+        _ => bail!("invalid class flag {name}"),
+    })
+}
+
+fn inner_class_flag(name: &str) -> Option<inner_class::AccessFlag> {
+    Some(match name {
+        "public" => inner_class::AccessFlag::Public,
+        "private" => inner_class::AccessFlag::Private,
+        "protected" => inner_class::AccessFlag::Protected,
+        "static" => inner_class::AccessFlag::Static,
+        "final" => inner_class::AccessFlag::Final,
+        "interface" => inner_class::AccessFlag::Interface,
+        "abstract" => inner_class::AccessFlag::Abstract,
+        "synthetic" => inner_class::AccessFlag::Synthetic,
+        "annotation" => inner_class::AccessFlag::Annotation,
+        "enum" => inner_class::AccessFlag::Enum,
+        _ => return None,
+    })
+}
+
+fn field_flag(name: &str) -> Result<FieldFlag> {
+    Ok(match name {
+        "public" => FieldFlag::Public,
+        "private" => FieldFlag::Private,
+        "protected" => FieldFlag::Protected,
+        "static" => FieldFlag::Static,
+        "final" => FieldFlag::Final,
+        "volatile" => FieldFlag::Volatile,
+        "transient" => FieldFlag::Transient,
+        "synthetic" => FieldFlag::Synthetic,
+        "enum" => FieldFlag::Enum,
+        _ => bail!("invalid field flag {name}"),
+    })
+}
+
+fn method_flag(name: &str) -> Result<MethodFlag> {
+    Ok(match name {
+        "public" => MethodFlag::Public,
+        "private" => MethodFlag::Private,
+        "protected" => MethodFlag::Protected,
+        "static" => MethodFlag::Static,
+        "final" => MethodFlag::Final,
+        "synchronized" => MethodFlag::Synchronized,
+        "bridge" => MethodFlag::Bridge,
+        "varargs" => MethodFlag::Varargs,
+        "native" => MethodFlag::Native,
+        "abstract" => MethodFlag::Abstract,
+        "strict" => MethodFlag::Strict,
+        "synthetic" => MethodFlag::Synthetic,
+        _ => bail!("invalid method flag {name}"),
+    })
+}
+
+/// Slots taken by the arguments of a method, including `this` unless it is static.
+fn argument_slots(descriptor: &MethodDescriptor, is_static: bool) -> usize {
+    let slots: usize = descriptor
+        .parameters
+        .iter()
+        .map(|parameter| match parameter {
+            FieldType::Long | FieldType::Double => 2,
+            _ => 1,
+        })
+        .sum();
+
+    if is_static {
+        slots
+    } else {
+        slots + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::{assemble, write_classes};
+    use crate::{
+        parser::{
+            attribute::Attribute, class::ClassFile, constant_pool::ConstantPoolItem,
+            instruction::Instruction,
+        },
+        verifier::{verify, ClassHierarchy},
+    };
+
+    fn round_trip(source: &str) -> ClassFile {
+        let class_file = assemble(source).unwrap();
+        let bytes = class_file.to_bytes().unwrap();
+        let parsed = ClassFile::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.to_bytes().unwrap(), bytes);
+        parsed
+    }
+
+    fn code(class_file: &ClassFile, method: usize) -> Vec<Instruction> {
+        let Some(Attribute::Code { code, .. }) = class_file.methods[method].attributes.first()
+        else {
+            panic!("no Code attribute");
+        };
+        Instruction::decode(code)
+            .unwrap()
+            .into_iter()
+            .map(|(_, instruction)| instruction)
+            .collect()
+    }
+
+    #[test]
+    fn test_class() {
+        let class_file = round_trip(
+            r#"
+            ; a comment
+            .class public Test
+            .super java/lang/Object
+            .implements java/lang/Runnable
+            .source Test.java
+            .field private static final NAME Ljava/lang/String; = "a;b"
+            .field static count J = 5
+
+            .method public run()V
+                return
+            .end method
+            "#,
+        );
+
+        assert_eq!(class_file.this_class.to_string(), "<default>.Test");
+        assert_eq!(class_file.interfaces[0].to_string(), "java.lang.Runnable");
+        assert_eq!(class_file.fields.len(), 2);
+        let Attribute::ConstantValue {
+            constant_value_index,
+        } = &class_file.fields[0].attributes[0]
+        else {
+            panic!("no ConstantValue attribute");
+        };
+        assert_eq!(
+            class_file
+                .constant_pool
+                .resolve(constant_value_index)
+                .unwrap(),
+            ConstantPoolItem::String {
+                value: "a;b".into()
+            }
+        );
+
+        let method = &class_file.methods[0];
+        assert_eq!(method.name(&class_file.constant_pool).unwrap(), "run");
+        let Attribute::Code { max_locals, .. } = method.attributes[0] else {
+            panic!("no Code attribute");
+        };
+        assert_eq!(max_locals, 1);
+    }
+
+    #[test]
+    fn test_labels() {
+        let class_file = round_trip(
+            "
+            .class Test
+            .method static test(IJ)I
+                .limit stack 2
+            Start:
+                iload_0
+                ifeq Zero
+                iload 0
+                tableswitch 1
+                    One
+                    Start
+                    default : Zero
+            One:
+                iconst_1
+                ireturn
+            Zero:
+                iconst_0
+                ireturn
+            .end method
+            ",
+        );
+
+        assert_eq!(
+            code(&class_file, 0),
+            vec![
+                Instruction::Iload0,
+                Instruction::Ifeq(29),
+                Instruction::Iload(0),
+                Instruction::TableSwitch {
+                    default: 24,
+                    low: 1,
+                    high: 2,
+                    offsets: vec![22, -6],
+                },
+                Instruction::Iconst1,
+                Instruction::Ireturn,
+                Instruction::Iconst0,
+                Instruction::Ireturn,
+            ]
+        );
+        let Attribute::Code { max_locals, .. } = class_file.methods[0].attributes[0] else {
+            panic!("no Code attribute");
+        };
+        assert_eq!(max_locals, 3);
+    }
+
+    #[test]
+    fn test_constants() {
+        let class_file = round_trip(
+            r#"
+            .class Test
+            .method static test()V
+                .limit stack 4
+                .limit locals 300
+                ldc "text"
+                ldc 1.5
+                ldc Test
+                ldc2_w 10
+                ldc2_w 2.5
+                getstatic java/lang/System/out Ljava/io/PrintStream;
+                invokeinterface java/util/List/size()I
+                iinc 299 1
+                astore 299
+                return
+            .end method
+            "#,
+        );
+
+        let constant_pool = &class_file.constant_pool;
+        let instructions = code(&class_file, 0);
+        let tags: Vec<_> = instructions
+            .iter()
+            .filter_map(|instruction| instruction.constant_pool_index())
+            .map(|index| constant_pool.get(index).unwrap().tag_name())
+            .collect();
+        assert_eq!(
+            tags,
+            vec![
+                "String",
+                "Float",
+                "Class",
+                "Long",
+                "Double",
+                "Fieldref",
+                "InterfaceMethodref",
+            ]
+        );
+        let float = instructions[1].constant_pool_index().unwrap();
+        assert_eq!(
+            constant_pool.resolve(float),
+            Some(ConstantPoolItem::Float { val: 1.5 })
+        );
+        assert!(matches!(
+            instructions[6],
+            Instruction::InvokeInterface { count: 1, .. }
+        ));
+        assert!(matches!(instructions[7], Instruction::Wide(_)));
+        assert!(matches!(instructions[8], Instruction::Wide(_)));
+    }
+
+    #[test]
+    fn test_catch() {
+        let class_file = round_trip(
+            "
+            .class Test
+            .method static test()V
+                .limit stack 1
+                .throws java/io/IOException
+                .catch java/lang/RuntimeException from Start to End using Handler
+            Start:
+                nop
+            End:
+                return
+            Handler:
+                athrow
+            .end method
+            ",
+        );
+
+        let Attribute::Code { exceptions, .. } = &class_file.methods[0].attributes[0] else {
+            panic!("no Code attribute");
+        };
+        assert_eq!(
+            (
+                exceptions[0].start_pc,
+                exceptions[0].end_pc,
+                exceptions[0].handler_pc
+            ),
+            (0, 1, 2)
+        );
+        assert!(matches!(
+            class_file.methods[0].attributes[1],
+            Attribute::Exceptions { .. }
+        ));
+    }
+
+    #[test]
+    fn test_verify() {
+        struct Hierarchy;
+        impl ClassHierarchy for Hierarchy {
+            fn super_class(&self, class: &str) -> Result<Option<String>> {
+                Ok((class != "java/lang/Object").then(|| "java/lang/Object".to_string()))
+            }
+
+            fn is_interface(&self, _: &str) -> Result<bool> {
+                Ok(false)
+            }
+        }
+
+        let valid = round_trip(
+            "
+            .class Test
+            .method static test()V
+                .limit stack 1
+                iconst_0
+                pop
+                return
+            .end method
+            ",
+        );
+        verify(&valid, &Hierarchy).unwrap();
+
+        let underflow = round_trip(
+            "
+            .class Test
+            .method static test()V
+                .limit stack 1
+                pop
+                return
+            .end method
+            ",
+        );
+        assert!(verify(&underflow, &Hierarchy).is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |source: &str| assemble(source).unwrap_err().to_string();
+
+        assert_eq!(
+            err(".class Test\n.method static test()V\ngoto Nowhere\n.end method"),
+            "line 4: undefined label Nowhere"
+        );
+        assert_eq!(
+            err(".class Test\n.method static test()V\nfrob\n.end method"),
+            "line 3: unknown instruction frob"
+        );
+        assert_eq!(
+            err(".class Test\n.method static test()V\nreturn 1\n.end method"),
+            "line 3: return takes no operands"
+        );
+        assert_eq!(
+            err(".class Test\n.method static test()V\nreturn"),
+            "missing .end method"
+        );
+        assert_eq!(err(".field static x I"), "missing .class");
+    }
+
+    #[test]
+    fn test_write_classes() {
+        let dir = std::env::temp_dir().join(format!("jvm-assembler-test-{}", std::process::id()));
+        let identifiers = write_classes(&dir, &[".class Plain", ".class app/util/Nested"]).unwrap();
+
+        let names: Vec<_> = identifiers.iter().map(|i| i.to_string()).collect();
+        assert_eq!(names, ["<default>.Plain", "app.util.Nested"]);
+        assert!(dir.join("Plain.class").is_file());
+        assert!(dir.join("app/util/Nested.class").is_file());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
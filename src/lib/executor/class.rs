@@ -3,6 +3,7 @@ use std::fmt::Display;
 
 use crate::{
     parser::{
+        attribute::inner_class::{self, InnerClassItem},
         class::{AccessFlag, ClassFile},
         constant_pool::{ConstantPool, ConstantPoolItem, Index, NameAndType},
         descriptor::MethodDescriptor,
@@ -19,6 +20,9 @@ pub struct Class {
     methods: Vec<Method>,
     pub fields: Vec<Field>,
     access_flags: Vec<AccessFlag>,
+    nest_host: Option<ClassIdentifier>,
+    nest_members: Vec<ClassIdentifier>,
+    inner_classes: Vec<InnerClassItem>,
 }
 
 impl Display for Class {
@@ -30,11 +34,14 @@ impl Display for Class {
 impl Class {
     pub fn new(class_file: ClassFile) -> Result<Self> {
         Ok(Self {
-            identifier: class_file.class_identifier,
-            constant_pool: class_file.constant_pool.clone(),
+            nest_host: class_file.nest_host()?,
+            nest_members: class_file.nest_members()?,
+            inner_classes: class_file.inner_classes()?,
             fields: Field::fields(class_file.fields, &class_file.constant_pool)?,
             methods: Method::methods(class_file.methods, &class_file.constant_pool)?,
-            access_flags: class_file.access_flags.clone(),
+            identifier: class_file.this_class,
+            constant_pool: class_file.constant_pool,
+            access_flags: class_file.access_flags,
         })
     }
 
@@ -42,10 +49,50 @@ impl Class {
         self.identifier.package.clone()
     }
 
+    /// The modifiers `Class.getModifiers` reports, those of the source code for nested classes.
+    pub fn modifiers(&self) -> u16 {
+        match self.inner_access_flags() {
+            Some(flags) => inner_class::AccessFlag::bits(flags),
+            None => AccessFlag::bits(&self.access_flags) & !AccessFlag::bits(&[AccessFlag::Super]),
+        }
+    }
+
     pub fn is_public(&self) -> bool {
         self.access_flags.contains(&AccessFlag::Public)
     }
 
+    /// The nest host named by the `NestHost` attribute, which the host's `NestMembers` still
+    /// has to confirm, see `ClassLoader::nest_host`.
+    pub fn declared_nest_host(&self) -> Option<&ClassIdentifier> {
+        self.nest_host.as_ref()
+    }
+
+    /// The members named by the `NestMembers` attribute of a nest host.
+    pub fn declared_nest_members(&self) -> &[ClassIdentifier] {
+        &self.nest_members
+    }
+
+    pub fn inner_classes(&self) -> &[InnerClassItem] {
+        &self.inner_classes
+    }
+
+    /// The `InnerClasses` entry describing this class itself, `None` for top level classes.
+    pub fn inner_class_entry(&self) -> Option<&InnerClassItem> {
+        self.inner_classes
+            .iter()
+            .find(|entry| entry.inner_class == self.identifier)
+    }
+
+    /// The class this class is a member of, as this class declares it.
+    pub fn declared_outer_class(&self) -> Option<&ClassIdentifier> {
+        self.inner_class_entry()?.outer_class.as_ref()
+    }
+
+    /// The access flags in source code of a nested class, such as `private` or `static`.
+    pub fn inner_access_flags(&self) -> Option<&[inner_class::AccessFlag]> {
+        Some(&self.inner_class_entry()?.access_flags)
+    }
+
     pub fn main_method(&self) -> Result<Method> {
         for method in &self.methods {
             if method.is_main() {
@@ -1,10 +1,11 @@
 use anyhow::{bail, Result};
 
-use crate::parser::attribute::Attribute;
+use crate::parser::{attribute::Attribute, instruction::Instruction};
 
 #[derive(Debug, Clone, Default)]
 pub struct Code {
-    opcodes: Vec<u8>,
+    instructions: Vec<(usize, Instruction)>,
+    length: usize,
 }
 
 impl Code {
@@ -17,13 +18,33 @@ impl Code {
             attributes: _,
         } = code_attribute
         {
-            return Ok(Self { opcodes: code });
+            return Ok(Self {
+                instructions: Instruction::decode(&code)?,
+                length: code.len(),
+            });
         }
 
         bail!("can't construct Code out of {:?}", code_attribute);
     }
 
-    pub fn get_opcode(&self, i: usize) -> Option<u8> {
-        self.opcodes.get(i).cloned()
+    fn position(&self, pc: usize) -> Option<usize> {
+        self.instructions
+            .binary_search_by_key(&pc, |(offset, _)| *offset)
+            .ok()
+    }
+
+    pub fn get_instruction(&self, pc: usize) -> Option<Instruction> {
+        self.position(pc)
+            .map(|position| self.instructions[position].1.clone())
+    }
+
+    /// Offset of the instruction following the one at `pc`, or the end of the code array.
+    pub fn next_pc(&self, pc: usize) -> Option<usize> {
+        let position = self.position(pc)?;
+        Some(
+            self.instructions
+                .get(position + 1)
+                .map_or(self.length, |(offset, _)| *offset),
+        )
     }
 }
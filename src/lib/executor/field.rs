@@ -2,18 +2,21 @@ use std::fmt::Display;
 
 use crate::parser::{
     self,
-    constant_pool::ConstantPool,
+    attribute::Attribute,
+    constant_pool::{ConstantPool, ConstantPoolItem},
     descriptor::{Descriptor, FieldType},
+    field::FieldFlag,
 };
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 
-use super::stack::Word;
+use super::stack::{Reference, Word};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Field {
     pub name: String,
     pub descriptor: Descriptor,
     pub value: Word,
+    pub access_flags: Vec<FieldFlag>,
 }
 
 impl Field {
@@ -23,19 +26,81 @@ impl Field {
     ) -> Result<Vec<Field>> {
         let mut fields = Vec::new();
         for field in parser_fields {
-            let field_type =
-                FieldType::new(&cp.utf8(&field.descriptor_index).context("no utf8 found")?)?;
+            let name = field.name(cp)?;
+            let field_type = field.descriptor(cp)?;
+            let is_static = field.access_flags.contains(&FieldFlag::Static);
+
+            // static fields with a ConstantValue start with it, others with their default
+            // value (JVMS 5.5), the attribute is ignored on instance fields (JVMS 4.7.2)
+            let value = match Self::constant_value(&field, cp) {
+                Some(item) if is_static => Self::word(item?, &field_type)
+                    .with_context(|| format!("ConstantValue of {name}"))?,
+                _ => Word::from_field_type(field_type.clone()),
+            };
+
             fields.push(Field {
-                name: cp
-                    .utf8(&field.name_index)
-                    .context("no utf8 at index {:?}")?,
-                descriptor: Descriptor::Field(field_type.clone()),
-                value: Word::from_field_type(field_type),
+                name,
+                descriptor: Descriptor::Field(field_type),
+                value,
+                access_flags: field.access_flags,
             })
         }
 
         Ok(fields)
     }
+
+    fn constant_value(
+        field: &parser::field::Field,
+        cp: &ConstantPool,
+    ) -> Option<Result<ConstantPoolItem>> {
+        field
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::ConstantValue {
+                    constant_value_index,
+                } => Some(
+                    cp.resolve(constant_value_index)
+                        .with_context(|| format!("no constant at {constant_value_index:?}")),
+                ),
+                _ => None,
+            })
+    }
+
+    /// The initial value of a field of type `field_type` from a ConstantValue entry, with
+    /// `int` constants narrowed as the field's type requires.
+    fn word(item: ConstantPoolItem, field_type: &FieldType) -> Result<Word> {
+        Ok(match (item, field_type) {
+            (ConstantPoolItem::Integer { val }, FieldType::Int) => Word::Int(val),
+            (ConstantPoolItem::Integer { val }, FieldType::Byte) => Word::_Byte(val as i8),
+            (ConstantPoolItem::Integer { val }, FieldType::Short) => Word::_Short(val as i16),
+            (ConstantPoolItem::Integer { val }, FieldType::Char) => Word::_Char(val as u16),
+            (ConstantPoolItem::Integer { val }, FieldType::Boolean) => Word::_Boolean(val & 1 != 0),
+            (ConstantPoolItem::Long { val }, FieldType::Long) => Word::_Long(val),
+            (ConstantPoolItem::Float { val }, FieldType::Float) => Word::_Float(val),
+            (ConstantPoolItem::Double { val }, FieldType::Double) => Word::_Double(val),
+            // TODO: initialize with the interned string once java/lang/String instances exist,
+            // like ldc the executor cannot create them yet
+            (ConstantPoolItem::String { .. }, FieldType::Class(class))
+                if class == "java/lang/String" =>
+            {
+                Word::Reference(Reference::Null)
+            }
+            (item, field_type) => bail!("{item:?} cannot initialize a field of type {field_type}"),
+        })
+    }
+
+    pub fn is_private(&self) -> bool {
+        self.access_flags.contains(&FieldFlag::Private)
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.access_flags.contains(&FieldFlag::Static)
+    }
+
+    pub fn is_final(&self) -> bool {
+        self.access_flags.contains(&FieldFlag::Final)
+    }
 }
 
 impl Display for Field {
@@ -43,3 +108,69 @@ impl Display for Field {
         write!(f, "{} {}", self.name, self.descriptor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        assembler::assemble,
+        executor::{
+            class::Class,
+            stack::{Reference, Word},
+        },
+        parser::field::FieldFlag,
+    };
+
+    #[test]
+    fn test_constant_value() {
+        let class_file = assemble(
+            "
+            .class Constants
+            .field public static final INT I = 42
+            .field static final CHAR C = 65
+            .field static final FLAG Z = 1
+            .field static final LONG J = -7
+            .field static final DOUBLE D = 2.5
+            .field static volatile counter I
+            .field final transient instance I = 3
+            ",
+        )
+        .unwrap();
+        let class = Class::new(class_file).unwrap();
+
+        let values: Vec<_> = class.fields.iter().map(|f| f.value.clone()).collect();
+        assert_eq!(
+            values,
+            vec![
+                Word::Int(42),
+                Word::_Char(65),
+                Word::_Boolean(true),
+                Word::_Long(-7),
+                Word::_Double(2.5),
+                Word::Int(0),
+                Word::Int(0),
+            ]
+        );
+
+        let flags = |i: usize| class.fields[i].access_flags.clone();
+        assert_eq!(
+            flags(0),
+            vec![FieldFlag::Public, FieldFlag::Static, FieldFlag::Final]
+        );
+        assert_eq!(flags(5), vec![FieldFlag::Static, FieldFlag::Volatile]);
+        assert_eq!(flags(6), vec![FieldFlag::Final, FieldFlag::Transient]);
+        assert!(!class.fields[6].is_static() && class.fields[6].is_final());
+    }
+
+    #[test]
+    fn test_string_constant_value() {
+        let class_file = assemble(
+            "
+            .class Constants
+            .field static final NAME Ljava/lang/String; = \"name\"
+            ",
+        )
+        .unwrap();
+        let class = Class::new(class_file).unwrap();
+        assert_eq!(class.fields[0].value, Word::Reference(Reference::Null));
+    }
+}
@@ -11,7 +11,7 @@ pub struct Instance {
 
 impl Instance {
     pub fn new(class: Class) -> Result<Self> {
-        if !class.fields.is_empty() {
+        if class.fields.iter().any(|field| !field.is_static()) {
             bail!("instance fields are not supported yet");
         }
 
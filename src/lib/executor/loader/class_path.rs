@@ -1,75 +1,430 @@
-use std::{env, fs::File, io::Read, path::PathBuf};
+use std::{
+    cell::{Cell, OnceCell, RefCell},
+    collections::{HashMap, HashSet},
+    env,
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+};
 
 use anyhow::{bail, Context, Result};
-use zip::ZipArchive;
+use zip::{result::ZipError, ZipArchive};
 
-use crate::ClassIdentifier;
+use crate::{parser::class::ClassFile, ClassIdentifier, Package};
+
+use super::jimage::JImage;
 
 pub struct ClassPath {
-    paths: Vec<PathBuf>,
+    entries: Vec<Entry>,
+    /// The entries holding each package looked up so far, in class path order.
+    packages: RefCell<HashMap<Package, Vec<usize>>>,
+}
+
+enum Entry {
+    Directory(PathBuf),
+    Archive(Archive),
+    Image(JImage),
+    Modules(Modules),
 }
 
-const JMOD_FILES: [&str; 1] = ["java.base.jmod"];
+/// A jar, zip or jmod on the class path, opened and indexed on first use and kept open.
+struct Archive {
+    path: PathBuf,
+    contents: OnceCell<Contents>,
+}
+
+struct Contents {
+    archive: RefCell<ZipArchive<File>>,
+    /// Index of every class entry in the archive by its path relative to the package root.
+    classes: HashMap<String, usize>,
+    /// Packages with at least one class, in binary form, empty for the unnamed package.
+    packages: HashSet<String>,
+}
+
+/// The jmods of a JDK without a runtime image, one archive per module.
+///
+/// Modules are indexed in name order until one holds the package looked up, so `java.base`
+/// classes only open `java.base.jmod`, a package no module holds opens all of them.
+struct Modules {
+    archives: Vec<Archive>,
+    /// The archive holding each package of the archives indexed so far, in binary form.
+    packages: RefCell<HashMap<String, usize>>,
+    /// The number of archives, from the first, whose packages are indexed.
+    indexed: Cell<usize>,
+}
+
+const MANIFEST: &str = "META-INF/MANIFEST.MF";
+
+const MODULE_INFO: &str = "module-info.class";
 
 impl ClassPath {
-    pub fn load(mut paths: Vec<PathBuf>) -> Result<ClassPath> {
+    pub fn load(paths: Vec<PathBuf>) -> Result<ClassPath> {
         for p in &paths {
             if !p.exists() {
                 bail!("invalid classpath: {p:?}");
             }
         }
 
-        let java_home = env::var("JAVA_HOME")?;
-        let jmods = PathBuf::from(java_home).join("jmods");
+        let mut entries = Vec::new();
+        let mut visited = HashSet::new();
+        for path in paths {
+            Self::add(&mut entries, &mut visited, path)?;
+        }
+        entries.push(Self::boot()?);
+
+        Ok(ClassPath {
+            entries,
+            packages: RefCell::new(HashMap::new()),
+        })
+    }
+
+    /// The JDK classes, from the `lib/modules` runtime image or the jmods of a JDK without one.
+    fn boot() -> Result<Entry> {
+        let java_home = PathBuf::from(env::var("JAVA_HOME")?);
+
+        let modules = java_home.join("lib").join("modules");
+        if modules.is_file() {
+            return Ok(Entry::Image(JImage::open(&modules)?));
+        }
+
+        let jmods = java_home.join("jmods");
         if !jmods.exists() {
-            bail!("jmods/ not found in $JAVA_HOME");
+            bail!("neither lib/modules nor jmods/ found in $JAVA_HOME");
+        }
+        Ok(Entry::Modules(Modules::open(&jmods)?))
+    }
+
+    /// Adds `path` followed by the `Class-Path` entries of its manifest, if it is an archive.
+    ///
+    /// The manifest entries come right after their archive in the search order, so every
+    /// archive is opened and indexed here rather than on first use.
+    fn add(entries: &mut Vec<Entry>, visited: &mut HashSet<PathBuf>, path: PathBuf) -> Result<()> {
+        if !visited.insert(path.canonicalize().unwrap_or_else(|_| path.clone())) {
+            return Ok(());
+        }
+
+        if path.is_dir() {
+            entries.push(Entry::Directory(path));
+            return Ok(());
+        }
+
+        let archive = Archive::new(&path);
+        let class_path = archive.manifest_class_path()?;
+        entries.push(Entry::Archive(archive));
+
+        // relative URLs in the manifest are resolved against the jar's directory, entries
+        // that don't exist are ignored like the JDK does
+        let base = path.parent().unwrap_or(Path::new(""));
+        for dependency in class_path {
+            let dependency = base.join(dependency);
+            if dependency.exists() {
+                Self::add(entries, visited, dependency)?;
+            }
         }
-        paths.push(jmods);
 
-        Ok(ClassPath { paths })
+        Ok(())
     }
 
-    pub fn find(&self, identifier: &ClassIdentifier) -> Result<Vec<u8>> {
-        let file_name = format!("{}.class", identifier.name);
+    /// The class file of `identifier` from the first entry that has it, `None` if no entry does.
+    ///
+    /// Only entries that contain the class's package are searched.
+    pub fn find(&self, identifier: &ClassIdentifier) -> Result<Option<Vec<u8>>> {
+        let entry_name = Self::entry_name(identifier);
 
-        for path in &self.paths {
-            for dir_entry in path.read_dir()? {
-                let path = dir_entry?.path();
+        for index in self.entries_with(&identifier.package)? {
+            if let Some(data) = self.entries[index].find(identifier, &entry_name)? {
+                return Ok(Some(data));
+            }
+        }
 
-                if JMOD_FILES.contains(
-                    &path
-                        .file_name()
-                        .context(format!("invalid file_name at {path:?}"))?
-                        .to_str()
-                        .context(format!("invalid file_name at {path:?}"))?,
-                ) {
-                    if let Some(p) = Self::find_in_jmod(&path, identifier)? {
-                        return Ok(p);
-                    }
-                }
+        Ok(None)
+    }
+
+    fn entries_with(&self, package: &Package) -> Result<Vec<usize>> {
+        if let Some(entries) = self.packages.borrow().get(package) {
+            return Ok(entries.clone());
+        }
 
-                if *path
-                    .file_name()
-                    .context(format!("invalid file_name at {path:?}"))?
-                    == *file_name
-                {
-                    return Ok(std::fs::read(path)?);
+        let binary_name = Self::binary_name(package);
+        let mut entries = Vec::new();
+        for (index, entry) in self.entries.iter().enumerate() {
+            if entry.contains(package, &binary_name)? {
+                entries.push(index);
+            }
+        }
+
+        self.packages
+            .borrow_mut()
+            .insert(package.clone(), entries.clone());
+        Ok(entries)
+    }
+
+    /// The package as a path below the class path root, empty for the unnamed package.
+    fn binary_name(package: &Package) -> String {
+        if *package == Package::default() {
+            String::new()
+        } else {
+            package.name.replace(".", "/")
+        }
+    }
+
+    fn entry_name(identifier: &ClassIdentifier) -> String {
+        if identifier.package == Package::default() {
+            format!("{}.class", identifier.name)
+        } else {
+            let package = identifier.package.name.replace(".", "/");
+            format!("{package}/{}.class", identifier.name)
+        }
+    }
+}
+
+impl Entry {
+    fn contains(&self, package: &Package, binary_name: &str) -> Result<bool> {
+        Ok(match self {
+            Entry::Directory(path) => path.join(binary_name).is_dir(),
+            Entry::Archive(archive) => archive.contents()?.packages.contains(binary_name),
+            Entry::Image(image) => image.contains(package)?,
+            Entry::Modules(modules) => modules.module(binary_name)?.is_some(),
+        })
+    }
+
+    fn find(&self, identifier: &ClassIdentifier, entry_name: &str) -> Result<Option<Vec<u8>>> {
+        match self {
+            Entry::Directory(path) => {
+                // directories hold classes at their package path below the root, like archives
+                let class_file = path.join(entry_name);
+                if !class_file.is_file() {
+                    return Ok(None);
                 }
+                let data = std::fs::read(&class_file)
+                    .with_context(|| format!("cannot read {class_file:?}"))?;
+                Ok(Some(data))
             }
+            Entry::Archive(archive) => archive.find(entry_name),
+            Entry::Image(image) => image.find(identifier),
+            Entry::Modules(modules) => modules.find(identifier, entry_name),
         }
+    }
+}
 
-        bail!("class {identifier} not found");
+impl Archive {
+    fn new(path: &Path) -> Archive {
+        Archive {
+            path: path.to_path_buf(),
+            contents: OnceCell::new(),
+        }
     }
 
-    fn find_in_jmod(path: &PathBuf, identifier: &ClassIdentifier) -> Result<Option<Vec<u8>>> {
-        let file = File::open(path)?;
-        let mut archive = ZipArchive::new(file)?;
+    fn contents(&self) -> Result<&Contents> {
+        if let Some(contents) = self.contents.get() {
+            return Ok(contents);
+        }
 
-        let package = identifier.package.name.replace(".", "/");
-        let file_path = format!("classes/{package}/{}.class", identifier.name);
+        let path = &self.path;
+        let file = File::open(path).with_context(|| format!("cannot open {path:?}"))?;
+        let archive = ZipArchive::new(file)
+            .with_context(|| format!("invalid archive on classpath: {path:?}"))?;
+
+        // classes in a jmod live under classes/, everything else is at the root
+        let prefix = if path.extension().is_some_and(|e| e == "jmod") {
+            "classes/"
+        } else {
+            ""
+        };
 
+        let classes: HashMap<String, usize> = archive
+            .file_names()
+            .filter(|name| name.ends_with(".class"))
+            .filter_map(|name| {
+                let class = name.strip_prefix(prefix)?.to_string();
+                Some((class, archive.index_for_name(name)?))
+            })
+            .collect();
+        let packages = classes
+            .keys()
+            .map(|name| match name.rsplit_once('/') {
+                Some((package, _)) => package.to_string(),
+                None => String::new(),
+            })
+            .collect();
+
+        Ok(self.contents.get_or_init(|| Contents {
+            archive: RefCell::new(archive),
+            classes,
+            packages,
+        }))
+    }
+
+    fn find(&self, entry_name: &str) -> Result<Option<Vec<u8>>> {
+        let contents = self.contents()?;
+        let Some(index) = contents.classes.get(entry_name) else {
+            return Ok(None);
+        };
+
+        let mut archive = contents.archive.borrow_mut();
+        let mut entry = archive
+            .by_index(*index)
+            .with_context(|| format!("cannot read {entry_name} from {:?}", self.path))?;
         let mut data = Vec::new();
-        archive.by_name(&file_path)?.read_to_end(&mut data)?;
+        entry.read_to_end(&mut data)?;
         Ok(Some(data))
     }
+
+    /// The packages of a module, from the `ModulePackages` attribute of its `module-info.class`
+    /// or the classes it contains if the attribute is missing.
+    fn packages(&self) -> Result<Vec<String>> {
+        if let Some(data) = self.find(MODULE_INFO)? {
+            let identifier = ClassIdentifier::from_utf8("module-info".to_string()).unwrap();
+            let module = ClassFile::new(&data, identifier)?.module()?;
+            if let Some(module) = module.filter(|m| !m.packages.is_empty()) {
+                return Ok(module.packages);
+            }
+        }
+
+        let mut packages: Vec<String> = self.contents()?.packages.iter().cloned().collect();
+        packages.sort();
+        Ok(packages)
+    }
+
+    /// The relative URLs in the `Class-Path` attribute of the archive's manifest.
+    fn manifest_class_path(&self) -> Result<Vec<String>> {
+        let mut text = String::new();
+        match self.contents()?.archive.borrow_mut().by_name(MANIFEST) {
+            Ok(mut manifest) => manifest
+                .read_to_string(&mut text)
+                .with_context(|| format!("invalid manifest in {:?}", self.path))?,
+            Err(ZipError::FileNotFound) => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(manifest_attribute(&text, "Class-Path")
+            .map(|value| value.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default())
+    }
+}
+
+impl Modules {
+    fn open(jmods: &Path) -> Result<Modules> {
+        let mut paths = Vec::new();
+        for dir_entry in jmods.read_dir()? {
+            let path = dir_entry?.path();
+            if path.extension().is_some_and(|e| e == "jmod") {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        Ok(Modules {
+            archives: paths.iter().map(|path| Archive::new(path)).collect(),
+            packages: RefCell::new(HashMap::new()),
+            indexed: Cell::new(0),
+        })
+    }
+
+    /// The archive holding `package`, in binary form, indexing the modules up to it.
+    fn module(&self, package: &str) -> Result<Option<usize>> {
+        // named modules cannot hold the unnamed package
+        if package.is_empty() {
+            return Ok(None);
+        }
+
+        while self.packages.borrow().get(package).is_none() {
+            let index = self.indexed.get();
+            let Some(archive) = self.archives.get(index) else {
+                return Ok(None);
+            };
+
+            let packages = archive
+                .packages()
+                .with_context(|| format!("invalid module in {:?}", archive.path))?;
+            let mut indexed = self.packages.borrow_mut();
+            for package in packages {
+                indexed.entry(package).or_insert(index);
+            }
+            self.indexed.set(index + 1);
+        }
+
+        Ok(self.packages.borrow().get(package).copied())
+    }
+
+    /// Looks up a class only in the module that contains its package.
+    fn find(&self, identifier: &ClassIdentifier, entry_name: &str) -> Result<Option<Vec<u8>>> {
+        let package = identifier.package.name.replace(".", "/");
+        match self.module(&package)? {
+            Some(index) => self.archives[index].find(entry_name),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Looks up `name` in the main section of a manifest, joining continuation lines.
+fn manifest_attribute(text: &str, name: &str) -> Option<String> {
+    let mut attributes: Vec<String> = Vec::new();
+    for line in text.lines() {
+        if line.is_empty() {
+            break;
+        }
+
+        match (line.strip_prefix(' '), attributes.last_mut()) {
+            (Some(continuation), Some(attribute)) => attribute.push_str(continuation),
+            _ => attributes.push(line.to_string()),
+        }
+    }
+
+    attributes.into_iter().find_map(|attribute| {
+        let (key, value) = attribute.split_once(':')?;
+        key.eq_ignore_ascii_case(name)
+            .then(|| value.trim().to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, path::PathBuf};
+
+    use crate::ClassIdentifier;
+
+    use super::{manifest_attribute, Modules};
+
+    #[test]
+    fn test_modules() {
+        let jmods = PathBuf::from(env::var("JAVA_HOME").unwrap()).join("jmods");
+        let modules = Modules::open(&jmods).unwrap();
+
+        let find = |name: &str| {
+            let identifier = ClassIdentifier::from_utf8(name.to_string()).unwrap();
+            let entry_name = format!("{}.class", name.replace('.', "/"));
+            modules.find(&identifier, &entry_name).unwrap()
+        };
+        assert!(find("java.lang.Object").is_some());
+        // java.base sorts first, the other jmods are not opened yet
+        assert_eq!(modules.indexed.get(), 1);
+        assert!(find("java.util.logging.Logger").is_some());
+        assert!(find("java.sql.Connection").is_some());
+        assert!(find("javax.xml.parsers.DocumentBuilder").is_some());
+        assert!(find("java.lang.Missing").is_none());
+        assert!(find("no.such.Package").is_none());
+        assert_eq!(modules.indexed.get(), modules.archives.len());
+    }
+
+    #[test]
+    fn test_manifest_attribute() {
+        let manifest = "Manifest-Version: 1.0\r\n\
+                        Class-Path: lib/a.jar lib/b\r\n \
+                        .jar lib/c.jar\r\n\
+                        Main-Class: app.Main\r\n\
+                        \r\n\
+                        Name: lib/\r\n\
+                        Sealed: true\r\n";
+
+        assert_eq!(
+            manifest_attribute(manifest, "class-path").as_deref(),
+            Some("lib/a.jar lib/b.jar lib/c.jar")
+        );
+        assert_eq!(
+            manifest_attribute(manifest, "Main-Class").as_deref(),
+            Some("app.Main")
+        );
+        assert_eq!(manifest_attribute(manifest, "Sealed"), None);
+    }
 }
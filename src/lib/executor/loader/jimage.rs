@@ -0,0 +1,677 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, ensure, Context, Result};
+use flate2::read::ZlibDecoder;
+
+use crate::{parser::mutf8::JavaString, ClassIdentifier, Package};
+
+const MAGIC: u32 = 0xCAFEDADA;
+const MAJOR_VERSION: u16 = 1;
+const HEADER_SIZE: usize = 7 * 4;
+
+/// Seed of the perfect hash over location names, also the FNV prime it multiplies with.
+const HASH_MULTIPLIER: i32 = 0x01000193;
+
+const COMPRESSED_MAGIC: u32 = 0xCAFEFAFA;
+const COMPRESSED_HEADER_SIZE: usize = 29;
+
+/// Tags of the constant pool entries in a class file compressed by the `compact-cp` plugin.
+const CONSTANT_UTF8: u8 = 1;
+const EXTERNALIZED_STRING: u8 = 23;
+const EXTERNALIZED_STRING_DESCRIPTOR: u8 = 25;
+
+#[derive(Clone, Copy)]
+enum Attribute {
+    Module = 1,
+    Parent,
+    Base,
+    Extension,
+    Offset,
+    Compressed,
+    Uncompressed,
+}
+
+/// The attributes of a resource in the image, indexed by [`Attribute`].
+struct Location {
+    attributes: [u64; 8],
+}
+
+impl Location {
+    fn get(&self, attribute: Attribute) -> u64 {
+        self.attributes[attribute as usize]
+    }
+}
+
+/// A jimage runtime image, the `lib/modules` file of a JDK or jlinked runtime.
+///
+/// The index (header, perfect hash tables, locations and strings) is read once, resources are
+/// read from the file as they are looked up.
+pub struct JImage {
+    path: PathBuf,
+    file: RefCell<File>,
+    big_endian: bool,
+    redirect: Vec<i32>,
+    offsets: Vec<u32>,
+    locations: Vec<u8>,
+    strings: Vec<u8>,
+    index_size: u64,
+    /// The module of each package looked up so far.
+    modules: RefCell<HashMap<Package, Option<String>>>,
+}
+
+impl JImage {
+    pub fn open(path: &Path) -> Result<JImage> {
+        let mut file = File::open(path).with_context(|| format!("cannot open {path:?}"))?;
+
+        let mut header = [0; HEADER_SIZE];
+        file.read_exact(&mut header)
+            .with_context(|| format!("truncated jimage header in {path:?}"))?;
+        let big_endian = match u32::from_le_bytes(header[..4].try_into()?) {
+            MAGIC => false,
+            magic if magic.swap_bytes() == MAGIC => true,
+            magic => bail!("invalid jimage magic {magic:#010x} in {path:?}"),
+        };
+        let u4 = |i: usize| read_u32(&header[i * 4..], big_endian);
+
+        let major_version = (u4(1) >> 16) as u16;
+        ensure!(
+            major_version == MAJOR_VERSION,
+            "unsupported jimage version {major_version} in {path:?}"
+        );
+        let table_length = u4(4) as usize;
+        let locations_size = u4(5) as usize;
+        let strings_size = u4(6) as usize;
+
+        let mut read = |size: usize| -> Result<Vec<u8>> {
+            let mut data = vec![0; size];
+            file.read_exact(&mut data)
+                .with_context(|| format!("truncated jimage index in {path:?}"))?;
+            Ok(data)
+        };
+        let redirect = read(table_length * 4)?
+            .chunks(4)
+            .map(|c| read_u32(c, big_endian) as i32)
+            .collect();
+        let offsets = read(table_length * 4)?
+            .chunks(4)
+            .map(|c| read_u32(c, big_endian))
+            .collect();
+        let locations = read(locations_size)?;
+        let strings = read(strings_size)?;
+
+        Ok(JImage {
+            path: path.to_path_buf(),
+            file: RefCell::new(file),
+            big_endian,
+            redirect,
+            offsets,
+            locations,
+            strings,
+            index_size: (HEADER_SIZE + table_length * 8 + locations_size + strings_size) as u64,
+            modules: RefCell::new(HashMap::new()),
+        })
+    }
+
+    /// The class file of `identifier` from the module that contains its package.
+    pub fn find(&self, identifier: &ClassIdentifier) -> Result<Option<Vec<u8>>> {
+        let Some(module) = self.module(&identifier.package)? else {
+            return Ok(None);
+        };
+        let package = identifier.package.name.replace(".", "/");
+        let name = format!("/{module}/{package}/{}.class", identifier.name);
+
+        match self.location(&name)? {
+            Some(location) => Ok(Some(self.resource(&location)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn contains(&self, package: &Package) -> Result<bool> {
+        Ok(self.module(package)?.is_some())
+    }
+
+    fn module(&self, package: &Package) -> Result<Option<String>> {
+        if let Some(module) = self.modules.borrow().get(package) {
+            return Ok(module.clone());
+        }
+
+        let module = if *package == Package::default() {
+            None
+        } else {
+            self.package_module(&package.name)?
+        };
+        self.modules
+            .borrow_mut()
+            .insert(package.clone(), module.clone());
+        Ok(module)
+    }
+
+    /// The module containing `package`, from the image's `/packages/{package}` entry.
+    fn package_module(&self, package: &str) -> Result<Option<String>> {
+        let Some(location) = self.location(&format!("/packages/{package}"))? else {
+            return Ok(None);
+        };
+
+        // pairs of an "is empty" flag and the module name's offset in the strings table, a
+        // package can be split across modules but only one of them has classes in it
+        let data = self.resource(&location)?;
+        let modules: Vec<(u32, u32)> = data
+            .chunks_exact(8)
+            .map(|c| {
+                (
+                    read_u32(&c[..4], self.big_endian),
+                    read_u32(&c[4..], self.big_endian),
+                )
+            })
+            .collect();
+        let Some(&(_, module)) = modules
+            .iter()
+            .find(|(is_empty, _)| *is_empty == 0)
+            .or(modules.first())
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(String::from_utf8(
+            self.string(module as u64)?.to_vec(),
+        )?))
+    }
+
+    fn location(&self, name: &str) -> Result<Option<Location>> {
+        let name = JavaString::from(name).to_modified_utf8();
+        let length = self.redirect.len() as i32;
+        if length == 0 {
+            return Ok(None);
+        }
+
+        let index = match self.redirect[(hash(&name, HASH_MULTIPLIER) % length) as usize] {
+            0 => return Ok(None),
+            index if index < 0 => !index,
+            seed => hash(&name, seed) % length,
+        };
+        let offset = *self
+            .offsets
+            .get(index as usize)
+            .with_context(|| format!("location index {index} out of bounds"))?;
+        let location = self.decode_location(offset as usize)?;
+
+        // the perfect hash maps names that aren't in the image to arbitrary locations
+        Ok((self.location_name(&location)? == name).then_some(location))
+    }
+
+    fn decode_location(&self, mut offset: usize) -> Result<Location> {
+        let mut attributes = [0; 8];
+        loop {
+            let byte = *self
+                .locations
+                .get(offset)
+                .with_context(|| format!("location at {offset} out of bounds"))?;
+            let kind = (byte >> 3) as usize;
+            if kind == 0 {
+                break;
+            }
+            ensure!(kind < attributes.len(), "invalid location attribute {kind}");
+
+            let length = (byte & 7) as usize + 1;
+            let bytes = self
+                .locations
+                .get(offset + 1..offset + 1 + length)
+                .with_context(|| format!("location at {offset} out of bounds"))?;
+            attributes[kind] = bytes.iter().fold(0, |value, b| value << 8 | *b as u64);
+            offset += 1 + length;
+        }
+
+        Ok(Location { attributes })
+    }
+
+    /// The full name of `location`, `/module/parent/base.extension`.
+    fn location_name(&self, location: &Location) -> Result<Vec<u8>> {
+        let mut name = Vec::new();
+        let module = self.string(location.get(Attribute::Module))?;
+        if !module.is_empty() {
+            name.push(b'/');
+            name.extend_from_slice(module);
+            name.push(b'/');
+        }
+        let parent = self.string(location.get(Attribute::Parent))?;
+        if !parent.is_empty() {
+            name.extend_from_slice(parent);
+            name.push(b'/');
+        }
+        name.extend_from_slice(self.string(location.get(Attribute::Base))?);
+        let extension = self.string(location.get(Attribute::Extension))?;
+        if !extension.is_empty() {
+            name.push(b'.');
+            name.extend_from_slice(extension);
+        }
+
+        Ok(name)
+    }
+
+    /// The NUL terminated modified UTF-8 string at `offset` in the strings table.
+    fn string(&self, offset: u64) -> Result<&[u8]> {
+        let strings = self
+            .strings
+            .get(offset as usize..)
+            .with_context(|| format!("string at {offset} out of bounds"))?;
+        let end = strings
+            .iter()
+            .position(|b| *b == 0)
+            .with_context(|| format!("unterminated string at {offset}"))?;
+        Ok(&strings[..end])
+    }
+
+    fn resource(&self, location: &Location) -> Result<Vec<u8>> {
+        let compressed = location.get(Attribute::Compressed);
+        let uncompressed = location.get(Attribute::Uncompressed);
+        let size = if compressed == 0 {
+            uncompressed
+        } else {
+            compressed
+        };
+
+        // the sizes come from the index, check them before allocating
+        let mut file = self.file.borrow_mut();
+        let start = self
+            .index_size
+            .saturating_add(location.get(Attribute::Offset));
+        let available = file.metadata()?.len().saturating_sub(start);
+        ensure!(
+            size <= available,
+            "resource of {size} bytes at {start} is past the end of {:?}",
+            self.path
+        );
+
+        let mut data = vec![0; size as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut data)
+            .with_context(|| format!("truncated resource in {:?}", self.path))?;
+
+        if compressed != 0 {
+            data = self.decompress(data)?;
+        }
+        ensure!(
+            data.len() as u64 == uncompressed,
+            "resource is {} bytes instead of {uncompressed}",
+            data.len()
+        );
+
+        Ok(data)
+    }
+
+    /// Undoes the compression plugins jlink applied to a resource, each one prefixed with a
+    /// header naming the decompressor.
+    fn decompress(&self, mut data: Vec<u8>) -> Result<Vec<u8>> {
+        while data.len() >= COMPRESSED_HEADER_SIZE
+            && read_u32(&data, self.big_endian) == COMPRESSED_MAGIC
+        {
+            let uncompressed = read_u64(&data[12..], self.big_endian) as usize;
+            let decompressor = self.string(read_u32(&data[20..], self.big_endian) as u64)?;
+            let content = &data[COMPRESSED_HEADER_SIZE..];
+
+            data = match decompressor {
+                b"zip" => {
+                    let mut inflated = Vec::new();
+                    ZlibDecoder::new(content)
+                        .read_to_end(&mut inflated)
+                        .context("invalid zip compressed resource")?;
+                    inflated
+                }
+                b"compact-cp" => self
+                    .expand_strings(content)
+                    .context("invalid compact-cp compressed resource")?,
+                name => bail!("unknown decompressor {}", String::from_utf8_lossy(name)),
+            };
+            ensure!(
+                data.len() == uncompressed,
+                "decompressed resource is {} bytes instead of {uncompressed}",
+                data.len()
+            );
+        }
+
+        Ok(data)
+    }
+
+    /// Restores a class file whose constant pool strings were moved to the image's strings
+    /// table by the `compact-cp` plugin.
+    fn expand_strings(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut reader = Reader { data, position: 0 };
+        let mut class = Vec::with_capacity(data.len());
+
+        // magic, minor and major version
+        class.extend_from_slice(reader.bytes(8)?);
+        let count = reader.u2()?;
+        class.extend_from_slice(&count.to_be_bytes());
+
+        let mut i = 1;
+        while i < count {
+            let tag = reader.u1()?;
+            match tag {
+                CONSTANT_UTF8 => {
+                    let length = reader.u2()?;
+                    let text = reader.bytes(length as usize)?;
+                    push_utf8(&mut class, text)?;
+                }
+                EXTERNALIZED_STRING => {
+                    let index = reader.compressed_int()?;
+                    push_utf8(&mut class, self.string(index as u64)?)?;
+                }
+                EXTERNALIZED_STRING_DESCRIPTOR => {
+                    let descriptor = self.expand_descriptor(&mut reader)?;
+                    push_utf8(&mut class, &descriptor)?;
+                }
+                _ => {
+                    let size = match tag {
+                        7 | 8 | 16 | 19 | 20 => 2,
+                        15 => 3,
+                        3 | 4 | 9 | 10 | 11 | 12 | 17 | 18 => 4,
+                        5 | 6 => 8,
+                        tag => bail!("invalid constant pool tag {tag}"),
+                    };
+                    class.push(tag);
+                    class.extend_from_slice(reader.bytes(size)?);
+                    if tag == 5 || tag == 6 {
+                        i += 1;
+                    }
+                }
+            }
+            i += 1;
+        }
+
+        class.extend_from_slice(&data[reader.position..]);
+        Ok(class)
+    }
+
+    /// A descriptor stored as a template whose class names are replaced by string indexes,
+    /// a package and a simple name for each `L`.
+    fn expand_descriptor(&self, reader: &mut Reader) -> Result<Vec<u8>> {
+        let template = self.string(reader.compressed_int()? as u64)?;
+        let length = reader.compressed_int()? as usize;
+        let mut indexes = Reader {
+            data: reader.bytes(length)?,
+            position: 0,
+        };
+
+        let mut descriptor = Vec::new();
+        for &c in template {
+            descriptor.push(c);
+            if c == b'L' {
+                let package = self.string(indexes.compressed_int()? as u64)?;
+                if !package.is_empty() {
+                    descriptor.extend_from_slice(package);
+                    descriptor.push(b'/');
+                }
+                descriptor.extend_from_slice(self.string(indexes.compressed_int()? as u64)?);
+            }
+        }
+
+        Ok(descriptor)
+    }
+}
+
+/// A cursor over the big endian data of a compressed class file.
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.position..self.position + length)
+            .with_context(|| format!("{length} bytes at {} out of bounds", self.position))?;
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn u1(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u2(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.bytes(2)?.try_into()?))
+    }
+
+    /// An int whose first byte, if its high bit is set, holds the total length in the next
+    /// two bits and the top five bits of the value, otherwise a plain four byte int.
+    fn compressed_int(&mut self) -> Result<u32> {
+        let header = self.u1()?;
+        let (length, mut value) = if header & 0x80 != 0 {
+            ((header >> 5) & 3, (header & 0x1f) as u32)
+        } else {
+            (4, header as u32)
+        };
+        for _ in 1..length {
+            value = value << 8 | self.u1()? as u32;
+        }
+        Ok(value)
+    }
+}
+
+fn push_utf8(class: &mut Vec<u8>, text: &[u8]) -> Result<()> {
+    class.push(CONSTANT_UTF8);
+    class.extend_from_slice(&u16::try_from(text.len())?.to_be_bytes());
+    class.extend_from_slice(text);
+    Ok(())
+}
+
+/// The perfect hash of a location name, FNV-1 over its bytes.
+fn hash(name: &[u8], seed: i32) -> i32 {
+    let hash = name.iter().fold(seed, |hash, b| {
+        hash.wrapping_mul(HASH_MULTIPLIER) ^ *b as i32
+    });
+    hash & 0x7fffffff
+}
+
+fn read_u32(bytes: &[u8], big_endian: bool) -> u32 {
+    let bytes = bytes[..4].try_into().unwrap();
+    if big_endian {
+        u32::from_be_bytes(bytes)
+    } else {
+        u32::from_le_bytes(bytes)
+    }
+}
+
+fn read_u64(bytes: &[u8], big_endian: bool) -> u64 {
+    let bytes = bytes[..8].try_into().unwrap();
+    if big_endian {
+        u64::from_be_bytes(bytes)
+    } else {
+        u64::from_le_bytes(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env,
+        fs::{self, File},
+        io::{Read, Write},
+        path::PathBuf,
+    };
+
+    use flate2::{write::ZlibEncoder, Compression};
+    use zip::ZipArchive;
+
+    use crate::ClassIdentifier;
+
+    use super::{Attribute, JImage, Location, Reader, COMPRESSED_MAGIC, MAGIC};
+
+    fn image() -> JImage {
+        let java_home = PathBuf::from(env::var("JAVA_HOME").unwrap());
+        JImage::open(&java_home.join("lib").join("modules")).unwrap()
+    }
+
+    fn find(image: &JImage, name: &str) -> Option<Vec<u8>> {
+        image
+            .find(&ClassIdentifier::from_utf8(name.to_string()).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_find() {
+        let image = image();
+
+        let java_home = PathBuf::from(env::var("JAVA_HOME").unwrap());
+        let jmod = File::open(java_home.join("jmods").join("java.base.jmod")).unwrap();
+        let mut expected = Vec::new();
+        ZipArchive::new(jmod)
+            .unwrap()
+            .by_name("classes/java/lang/Object.class")
+            .unwrap()
+            .read_to_end(&mut expected)
+            .unwrap();
+        assert_eq!(find(&image, "java.lang.Object"), Some(expected));
+
+        assert!(find(&image, "java.util.logging.Logger").is_some());
+        assert_eq!(find(&image, "java.lang.Missing"), None);
+        assert_eq!(find(&image, "no.such.Package"), None);
+        assert_eq!(find(&image, "Object"), None);
+    }
+
+    #[test]
+    fn test_compressed_int() {
+        let mut reader = Reader {
+            data: &[0xa5, 0xc1, 0x02, 0xe1, 0x02, 0x03, 0x00, 0x01, 0x00, 0x00],
+            position: 0,
+        };
+
+        assert_eq!(reader.compressed_int().unwrap(), 5);
+        assert_eq!(reader.compressed_int().unwrap(), 0x102);
+        assert_eq!(reader.compressed_int().unwrap(), 0x10203);
+        assert_eq!(reader.compressed_int().unwrap(), 0x10000);
+        assert!(reader.compressed_int().is_err());
+    }
+
+    /// Strings of the hand built image, each at the offset [`string`] gives.
+    const STRINGS: [&str; 7] = [
+        "",
+        "zip",
+        "compact-cp",
+        "Hello",
+        "(L;)V",
+        "java/lang",
+        "String",
+    ];
+
+    fn string(text: &str) -> u32 {
+        let index = STRINGS.iter().position(|s| *s == text).unwrap();
+        STRINGS[..index].iter().map(|s| s.len() as u32 + 1).sum()
+    }
+
+    /// A little endian image without resources, holding only [`STRINGS`].
+    fn strings_image(name: &str) -> JImage {
+        image_with_redirect(name, &[])
+    }
+
+    /// A little endian image whose perfect hash table is `redirect`, with a location offset of
+    /// zero for each slot and no locations.
+    fn image_with_redirect(name: &str, redirect: &[i32]) -> JImage {
+        let strings: Vec<u8> = STRINGS.iter().flat_map(|s| s.bytes().chain([0])).collect();
+        let mut data = Vec::new();
+        let length = redirect.len() as u32;
+        for value in [MAGIC, 1 << 16, 0, 0, length, 0, strings.len() as u32] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in redirect {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend(vec![0; redirect.len() * 4]);
+        data.extend_from_slice(&strings);
+
+        let path = env::temp_dir().join(format!("jvm-jimage-test-{}-{name}", std::process::id()));
+        fs::write(&path, data).unwrap();
+        let image = JImage::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        image
+    }
+
+    /// `content` behind a compressed resource header naming `decompressor`.
+    fn compressed(decompressor: &str, content: &[u8], uncompressed: usize) -> Vec<u8> {
+        let mut data = COMPRESSED_MAGIC.to_le_bytes().to_vec();
+        data.extend_from_slice(&(content.len() as u64).to_le_bytes());
+        data.extend_from_slice(&(uncompressed as u64).to_le_bytes());
+        data.extend_from_slice(&string(decompressor).to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.push(0);
+        data.extend_from_slice(content);
+        data
+    }
+
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_decompress_zip() {
+        let image = strings_image("zip");
+        let class = fs::read("testdata/Main.class").unwrap();
+
+        let data = compressed("zip", &zlib(&class), class.len());
+        assert_eq!(image.decompress(data).unwrap(), class);
+
+        let data = compressed("zip", &zlib(&class), class.len() + 1);
+        assert!(image.decompress(data).is_err());
+    }
+
+    #[test]
+    fn test_decompress_compact_cp() {
+        let image = strings_image("compact-cp");
+        // two byte compressed ints
+        let index = |text: &str| [0xc0 | (string(text) >> 8) as u8, string(text) as u8];
+
+        let header = [0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 61, 0, 5];
+        // an externalized string, a descriptor with four bytes of indexes, then entries kept as
+        // they are: a Utf8 and a Class
+        let mut content = header.to_vec();
+        content.push(23);
+        content.extend_from_slice(&index("Hello"));
+        content.push(25);
+        content.extend_from_slice(&index("(L;)V"));
+        content.push(0xa4);
+        content.extend_from_slice(&index("java/lang"));
+        content.extend_from_slice(&index("String"));
+        content.extend_from_slice(&[1, 0, 1, b'V', 7, 0, 1]);
+        content.extend_from_slice(&[0, 0x21]);
+
+        let mut expected = header.to_vec();
+        expected.extend_from_slice(&[1, 0, 5]);
+        expected.extend_from_slice(b"Hello");
+        expected.extend_from_slice(&[1, 0, 21]);
+        expected.extend_from_slice(b"(Ljava/lang/String;)V");
+        expected.extend_from_slice(&[1, 0, 1, b'V', 7, 0, 1]);
+        expected.extend_from_slice(&[0, 0x21]);
+
+        let data = compressed("compact-cp", &content, expected.len());
+        assert_eq!(image.decompress(data).unwrap(), expected);
+
+        // jlink applies zip on top of compact-cp
+        let inner = compressed("compact-cp", &content, expected.len());
+        let data = compressed("zip", &zlib(&inner), inner.len());
+        assert_eq!(image.decompress(data).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_corrupt_index() {
+        // a redirect of i32::MIN points past the offsets table
+        let image = image_with_redirect("redirect", &[i32::MIN]);
+        assert!(image.location("/java.base/java/lang/Object.class").is_err());
+
+        let image = strings_image("resource");
+        let mut attributes = [0; 8];
+        attributes[Attribute::Offset as usize] = u64::MAX / 2;
+        attributes[Attribute::Uncompressed as usize] = 1 << 40;
+        assert!(image.resource(&Location { attributes }).is_err());
+        attributes[Attribute::Offset as usize] = 0;
+        assert!(image.resource(&Location { attributes }).is_err());
+    }
+}
@@ -1,15 +1,24 @@
-use anyhow::{bail, Result};
-use std::collections::HashMap;
+use anyhow::{bail, Context, Result};
+use std::{cell::RefCell, collections::HashMap};
 
 use class_path::ClassPath;
+use tracing::debug;
 
-use crate::{executor::class::Class, parser::class::ClassFile, ClassIdentifier};
+use crate::{
+    executor::class::Class,
+    parser::class::{AccessFlag, ClassFile},
+    verifier::{self, ClassHierarchy},
+    ClassIdentifier,
+};
 
 pub mod class_path;
+mod jimage;
 
 pub struct ClassLoader {
     class_path: ClassPath,
     classes: HashMap<ClassIdentifier, Class>,
+    /// Super class and interface flag of the classes the verifier has looked up.
+    hierarchy: RefCell<HashMap<String, (Option<String>, bool)>>,
 }
 
 impl ClassLoader {
@@ -17,6 +26,7 @@ impl ClassLoader {
         ClassLoader {
             class_path,
             classes: HashMap::new(),
+            hierarchy: RefCell::new(HashMap::new()),
         }
     }
 
@@ -25,9 +35,7 @@ impl ClassLoader {
             return Ok(c.clone());
         }
 
-        let data = self.class_path.find(&class_identifier)?;
-        let class_file = ClassFile::new(&data, class_identifier.clone())?;
-        let class = Class::new(class_file)?;
+        let class = self.define(&class_identifier)?;
 
         self.classes.insert(class_identifier, class.clone());
         Ok(class)
@@ -38,13 +46,116 @@ impl ClassLoader {
             return Ok(());
         }
 
-        let data = self.class_path.find(&class_identifier)?;
-        let class_file = ClassFile::new(&data, class_identifier.clone())?;
-        let class = Class::new(class_file)?;
+        let class = self.define(&class_identifier)?;
         if !class.has_main() {
             bail!("No main method in class {class_identifier}");
         }
         self.classes.insert(class_identifier, class.clone());
         Ok(())
     }
+
+    fn define(&self, class_identifier: &ClassIdentifier) -> Result<Class> {
+        let data = self
+            .class_path
+            .find(class_identifier)?
+            .with_context(|| format!("NoClassDefFoundError: {class_identifier}"))?;
+        let class_file = ClassFile::new(&data, class_identifier.clone())?;
+        class_file.check()?;
+        verifier::verify(&class_file, self)?;
+        debug!(
+            "loaded {class_file} (version {}, super class {:?}, interfaces {:?})",
+            class_file.version, class_file.super_class, class_file.interfaces
+        );
+        Class::new(class_file)
+    }
+}
+
+/// Nests and nested classes (JVMS 5.4.4), which have to be confirmed by both classes.
+impl ClassLoader {
+    /// The host of the nest `class` belongs to. A class whose declared host cannot be loaded,
+    /// is in another package or does not list it as a member is the host of its own nest.
+    pub fn nest_host(&mut self, class: &Class) -> Result<ClassIdentifier> {
+        let Some(host) = class.declared_nest_host() else {
+            return Ok(class.identifier.clone());
+        };
+
+        let is_valid = host.package == class.identifier.package
+            && self
+                .load(host.clone())
+                .is_ok_and(|host| host.declared_nest_members().contains(&class.identifier));
+        if !is_valid {
+            debug!("{class} is not a member of the nest of {host}, its own nest host");
+            return Ok(class.identifier.clone());
+        }
+
+        Ok(host.clone())
+    }
+
+    pub fn is_nestmate(&mut self, class: &Class, other: &Class) -> Result<bool> {
+        Ok(class.identifier == other.identifier
+            || self.nest_host(class)? == self.nest_host(other)?)
+    }
+
+    /// The class `class` is a member of, if both list it in their InnerClasses attribute.
+    pub fn declaring_class(&mut self, class: &Class) -> Result<Option<ClassIdentifier>> {
+        let Some(outer) = class.declared_outer_class() else {
+            return Ok(None);
+        };
+
+        let outer_class = self.load(outer.clone())?;
+        let confirmed = outer_class.inner_classes().iter().any(|entry| {
+            entry.inner_class == class.identifier && entry.outer_class.as_ref() == Some(outer)
+        });
+        if !confirmed {
+            bail!("IncompatibleClassChangeError: {outer} and {class} disagree on InnerClasses attribute");
+        }
+
+        Ok(Some(outer.clone()))
+    }
+}
+
+impl ClassLoader {
+    fn hierarchy_entry(&self, class: &str) -> Result<(Option<String>, bool)> {
+        if let Some(entry) = self.hierarchy.borrow().get(class) {
+            return Ok(entry.clone());
+        }
+
+        let identifier = ClassIdentifier::from_utf8(class.to_string())
+            .with_context(|| format!("invalid class name {class}"))?;
+        let data = self
+            .class_path
+            .find(&identifier)?
+            .with_context(|| format!("NoClassDefFoundError: {class}"))?;
+        let class_file = ClassFile::new(&data, identifier)?;
+
+        let super_class = if class_file.super_class_index.is_zero() {
+            None
+        } else {
+            Some(
+                class_file
+                    .constant_pool
+                    .class_name(&class_file.super_class_index)
+                    .context("invalid super class")?,
+            )
+        };
+        let entry = (
+            super_class,
+            class_file.access_flags.contains(&AccessFlag::Interface),
+        );
+
+        self.hierarchy
+            .borrow_mut()
+            .insert(class.to_string(), entry.clone());
+        Ok(entry)
+    }
+}
+
+impl ClassHierarchy for ClassLoader {
+    fn super_class(&self, class: &str) -> Result<Option<String>> {
+        Ok(self.hierarchy_entry(class)?.0)
+    }
+
+    fn is_interface(&self, class: &str) -> Result<bool> {
+        Ok(self.hierarchy_entry(class)?.1)
+    }
 }
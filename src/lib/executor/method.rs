@@ -1,18 +1,18 @@
 use crate::parser::{
     self,
-    attribute::Attribute,
+    attribute::{Attribute, Attributes},
     constant_pool::ConstantPool,
     descriptor::{FieldType, MethodDescriptor, ReturnDescriptor},
     method::MethodFlag,
 };
-use anyhow::{bail, Context, Result};
+use anyhow::{bail, Result};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Method {
     pub name: String,
     pub descriptor: MethodDescriptor,
     access_flags: Vec<MethodFlag>,
-    attributes: Vec<Attribute>,
+    attributes: Attributes,
 }
 
 impl Method {
@@ -24,13 +24,8 @@ impl Method {
 
         for method in &parser_methods {
             methods.push(Method {
-                name: cp
-                    .utf8(&method.name_index)
-                    .context(format!("no utf8 entry at {:?}", method.name_index))?,
-                descriptor: MethodDescriptor::new(
-                    &cp.utf8(&method.descriptor_index)
-                        .context(format!("no utf8 entry at {:?}", method.name_index))?,
-                )?,
+                name: method.name(cp)?,
+                descriptor: method.descriptor(cp)?,
                 access_flags: method.access_flags.clone(),
                 attributes: method.attributes.clone(),
             })
@@ -47,6 +42,10 @@ impl Method {
         self.name == "<clinit>" && self.descriptor.return_descriptor == ReturnDescriptor::Void
     }
 
+    pub fn is_private(&self) -> bool {
+        self.access_flags.contains(&MethodFlag::Private)
+    }
+
     fn is_public(&self) -> bool {
         self.access_flags.contains(&MethodFlag::Public)
     }
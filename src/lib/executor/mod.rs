@@ -1,5 +1,5 @@
 use anyhow::{Context, Result};
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Display};
 
 use anyhow::bail;
 use class::Class;
@@ -99,13 +99,13 @@ impl Executor {
 
     fn execute_code(&mut self) -> Result<()> {
         loop {
-            let op_code = self.stack.get_opcode()?;
-            let op = op::get_op(op_code)?;
+            let instruction = self.stack.get_instruction()?;
             debug!("{}", self.stack);
-            info!("Executing {}", op::name(op_code)?);
-            op(self)?;
+            info!("Executing {instruction}");
+            self.stack.next()?;
+            op::perform(self, &instruction)?;
 
-            if op::is_return(op_code) {
+            if op::is_return(&instruction) {
                 return Ok(());
             }
         }
@@ -118,8 +118,14 @@ impl Executor {
     ) -> Result<()> {
         debug!("Invoking {name_and_type} in {class_identifier}");
         let class = self.class_loader.load(class_identifier.clone())?;
-        self.initialize_class(class.clone())?;
         let method_descriptor = &name_and_type.descriptor.method_descriptor()?;
+        if class
+            .method(&name_and_type.name, method_descriptor)?
+            .is_private()
+        {
+            self.check_private_access(&class, &name_and_type)?;
+        }
+        self.initialize_class(class.clone())?;
 
         let operands = self
             .stack
@@ -149,6 +155,9 @@ impl Executor {
         let (class_identifier, name_and_type) = self.stack.lookup_field(field_index)?;
         let class = self.resolve_class(class_identifier.clone())?;
         let field = class.field(&name_and_type)?;
+        if field.is_private() {
+            self.check_private_access(&class, &field)?;
+        }
         self.initialize_class(class)?;
         Ok(field)
     }
@@ -162,6 +171,38 @@ impl Executor {
         Ok(class)
     }
 
+    /// Private members are only accessible from the nest of the class declaring them
+    /// (JVMS 5.4.4).
+    fn check_private_access(&mut self, class: &Class, member: &dyn Display) -> Result<()> {
+        let current = self.stack.current_class()?;
+        if !self.class_loader.is_nestmate(&current, class)? {
+            bail!("IllegalAccessError: {current} cannot access private member {member} of {class}");
+        }
+
+        Ok(())
+    }
+
+    /// Calls the native instance `method` with the objectref and arguments on the operand stack.
+    fn invoke_native(&mut self, class: &Class, method: &Method) -> Result<()> {
+        let mut operands = self
+            .stack
+            .pop_operands(method.descriptor.parameters.len() + 1)?;
+        let this = operands.pop().context("operand stack is empty")?;
+
+        if let Some(word) = native::invoke(
+            self,
+            class.identifier.clone(),
+            method.name.clone(),
+            method.descriptor.parameters.clone(),
+            this,
+            operands,
+        )? {
+            self.stack.push_operand(word);
+        }
+
+        Ok(())
+    }
+
     fn assign_static_field(&mut self, field: &Field, value: &Word) -> Result<()> {
         if let Some(ref mut class) = self.class_being_initialized {
             debug!("Assigning {field} in {class}");
@@ -172,8 +213,4 @@ impl Executor {
             bail!("no class is being initialized")
         }
     }
-
-    fn pc(&mut self, n: usize) -> Result<()> {
-        self.stack.pc(n)
-    }
 }
@@ -7,12 +7,18 @@ use crate::{
     },
     ClassIdentifier,
 };
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use lazy_static::lazy_static;
 
-use super::{stack::Word, Executor};
+use super::{
+    class::Class,
+    stack::{Reference, Word},
+    Executor,
+};
 
 type NativeMethod = fn(&mut Executor, Vec<Word>) -> Result<Option<Word>>;
+/// A native instance method, called with `this` and the arguments.
+type NativeInstanceMethod = fn(&mut Executor, Word, Vec<Word>) -> Result<Option<Word>>;
 
 lazy_static! {
     static ref NATIVE_STATIC_METHODS: HashMap<(ClassIdentifier, String, Vec<FieldType>), NativeMethod> = {
@@ -37,6 +43,37 @@ lazy_static! {
     };
 }
 
+lazy_static! {
+    static ref NATIVE_METHODS: HashMap<(ClassIdentifier, String, Vec<FieldType>), NativeInstanceMethod> = {
+        let mut h = HashMap::new();
+        h.insert(
+            (
+                ClassIdentifier::from("java.lang".to_string(), "Class".to_string()),
+                "getNestHost0".to_string(),
+                vec![],
+            ),
+            get_nest_host_class as NativeInstanceMethod,
+        );
+        h.insert(
+            (
+                ClassIdentifier::from("java.lang".to_string(), "Class".to_string()),
+                "getDeclaringClass0".to_string(),
+                vec![],
+            ),
+            get_declaring_class_class as NativeInstanceMethod,
+        );
+        h.insert(
+            (
+                ClassIdentifier::from("java.lang".to_string(), "Class".to_string()),
+                "getModifiers".to_string(),
+                vec![],
+            ),
+            get_modifiers_class as NativeInstanceMethod,
+        );
+        h
+    };
+}
+
 pub fn invoke_static(
     executor: &mut Executor,
     class_identifier: ClassIdentifier,
@@ -67,3 +104,188 @@ fn register_natives_system(executor: &mut Executor, _operands: Vec<Word>) -> Res
 fn register_natives_class(_executor: &mut Executor, _operands: Vec<Word>) -> Result<Option<Word>> {
     Ok(None)
 }
+
+pub fn invoke(
+    executor: &mut Executor,
+    class_identifier: ClassIdentifier,
+    name: String,
+    parameters: Vec<FieldType>,
+    this: Word,
+    operands: Vec<Word>,
+) -> Result<Option<Word>> {
+    NATIVE_METHODS
+        .get(&(class_identifier.clone(), name.clone(), parameters.clone()))
+        .context(format!(
+        "native method {name} in {class_identifier} with parameters {parameters:?} not implemented"
+    ))?(executor, this, operands)
+}
+
+/// The class a `java.lang.Class` instance stands for.
+fn mirrored_class(this: Word) -> Result<Class> {
+    match this {
+        Word::Class { class } => Ok(class),
+        word => bail!("{word} is not a java.lang.Class"),
+    }
+}
+
+fn get_nest_host_class(
+    executor: &mut Executor,
+    this: Word,
+    _operands: Vec<Word>,
+) -> Result<Option<Word>> {
+    let class = mirrored_class(this)?;
+    let host = executor.class_loader.nest_host(&class)?;
+    let host = executor.class_loader.load(host)?;
+    Ok(Some(Word::Class { class: host }))
+}
+
+fn get_declaring_class_class(
+    executor: &mut Executor,
+    this: Word,
+    _operands: Vec<Word>,
+) -> Result<Option<Word>> {
+    let class = mirrored_class(this)?;
+    Ok(Some(
+        match executor.class_loader.declaring_class(&class)? {
+            Some(outer) => Word::Class {
+                class: executor.class_loader.load(outer)?,
+            },
+            None => Word::Reference(Reference::Null),
+        },
+    ))
+}
+
+fn get_modifiers_class(
+    _executor: &mut Executor,
+    this: Word,
+    _operands: Vec<Word>,
+) -> Result<Option<Word>> {
+    let class = mirrored_class(this)?;
+    Ok(Some(Word::Int(class.modifiers() as i32)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::invoke;
+    use crate::{
+        assembler::write_classes,
+        executor::{
+            loader::{class_path::ClassPath, ClassLoader},
+            stack::{Reference, Word},
+            Executor,
+        },
+        ClassIdentifier,
+    };
+
+    const CLASSES: [&str; 6] = [
+        "
+        .class public Outer
+        .nestmember Outer$Inner
+        .nestmember Outer$Liar
+        .inner private static Outer$Inner outer Outer name Inner
+        ",
+        "
+        .class Outer$Inner
+        .nesthost Outer
+        .inner private static Outer$Inner outer Outer name Inner
+        ",
+        // claims a nest that does not list it
+        "
+        .class Stranger
+        .nesthost Outer
+        ",
+        // listed by the nest, but claims no host
+        "
+        .class Outer$Liar
+        ",
+        "
+        .class final Outer$1
+        .inner final Outer$1
+        .enclosing method Outer/run()V
+        ",
+        // declares an outer class that does not list it
+        "
+        .class Orphan$Inner
+        .inner public Orphan$Inner outer Outer name Inner
+        ",
+    ];
+
+    /// An executor with every class in [`CLASSES`] loaded, so their directory is already gone.
+    fn executor(name: &str) -> Executor {
+        let dir =
+            std::env::temp_dir().join(format!("jvm-native-test-{}-{name}", std::process::id()));
+        let identifiers = write_classes(&dir, &CLASSES).unwrap();
+
+        let class_path = ClassPath::load(vec![dir.clone()]).unwrap();
+        let mut executor = Executor::new(ClassLoader::new(class_path));
+        let loaded = identifiers
+            .into_iter()
+            .try_for_each(|identifier| executor.class_loader.load(identifier).map(|_| ()));
+        fs::remove_dir_all(&dir).unwrap();
+        loaded.unwrap();
+        executor
+    }
+
+    fn call(executor: &mut Executor, name: &str, class: &str) -> anyhow::Result<Option<Word>> {
+        let identifier = ClassIdentifier::from_utf8(class.to_string()).unwrap();
+        let class = executor.class_loader.load(identifier).unwrap();
+        invoke(
+            executor,
+            ClassIdentifier::from("java.lang".to_string(), "Class".to_string()),
+            name.to_string(),
+            vec![],
+            Word::Class { class },
+            vec![],
+        )
+    }
+
+    fn class_name(word: Option<Word>) -> String {
+        match word {
+            Some(Word::Class { class }) => class.identifier.name.name,
+            word => panic!("{word:?} is not a class"),
+        }
+    }
+
+    #[test]
+    fn test_get_nest_host() {
+        let mut executor = executor("nest-host");
+
+        for (class, host) in [
+            ("Outer", "Outer"),
+            ("Outer$Inner", "Outer"),
+            ("Stranger", "Stranger"),
+            ("Outer$Liar", "Outer$Liar"),
+        ] {
+            let word = call(&mut executor, "getNestHost0", class).unwrap();
+            assert_eq!(class_name(word), host, "nest host of {class}");
+        }
+    }
+
+    #[test]
+    fn test_get_declaring_class() {
+        let mut executor = executor("declaring-class");
+
+        let word = call(&mut executor, "getDeclaringClass0", "Outer$Inner").unwrap();
+        assert_eq!(class_name(word), "Outer");
+        for class in ["Outer", "Outer$1"] {
+            let word = call(&mut executor, "getDeclaringClass0", class).unwrap();
+            assert_eq!(word, Some(Word::Reference(Reference::Null)));
+        }
+
+        let err = call(&mut executor, "getDeclaringClass0", "Orphan$Inner").unwrap_err();
+        assert!(err.to_string().starts_with("IncompatibleClassChangeError"));
+    }
+
+    #[test]
+    fn test_get_modifiers() {
+        let mut executor = executor("modifiers");
+
+        // private static from InnerClasses, ACC_SUPER is dropped
+        let inner = call(&mut executor, "getModifiers", "Outer$Inner").unwrap();
+        assert_eq!(inner, Some(Word::Int(0x000a)));
+        let outer = call(&mut executor, "getModifiers", "Outer").unwrap();
+        assert_eq!(outer, Some(Word::Int(0x0001)));
+    }
+}
@@ -1,5 +1,5 @@
-use crate::executor::{stack::Word, Executor};
-use anyhow::{bail, Context, Result};
+use crate::executor::Executor;
+use anyhow::{Context, Result};
 
 pub fn aload_0(executor: &mut Executor) -> Result<()> {
     let local_variables = executor.stack.local_variables()?;
@@ -7,12 +7,7 @@ pub fn aload_0(executor: &mut Executor) -> Result<()> {
         .first()
         .context("local variables are empty")?;
 
-    if !matches!(reference, Word::Reference { .. }) {
-        bail!("variable has to be a reference");
-    }
-
     executor.stack.push_operand(reference.clone());
-    executor.pc(1)?;
 
     Ok(())
 }
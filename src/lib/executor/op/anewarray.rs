@@ -7,17 +7,11 @@ use crate::{
 };
 use anyhow::{bail, Context, Result};
 
-pub fn perform(executor: &mut Executor) -> Result<()> {
+pub fn perform(executor: &mut Executor, index: &Index) -> Result<()> {
     let operands = executor.stack.pop_operands(1)?;
     match operands.first().context("local variables are empty")? {
         Word::Int(count) => {
-            executor.pc(1)?;
-            let indexbyte1 = executor.stack.get_opcode()? as u16;
-            executor.pc(1)?;
-            let indexbyte2 = executor.stack.get_opcode()? as u16;
-            executor.pc(1)?;
-            let index = Index::new((indexbyte1 << 8) | indexbyte2);
-            let cp_item = executor.stack.resolve_in_cp(&index)?;
+            let cp_item = executor.stack.resolve_in_cp(index)?;
 
             match cp_item {
                 ConstantPoolItem::ClassInfo { identifier } => {
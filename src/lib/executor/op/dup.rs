@@ -5,7 +5,6 @@ pub fn perform(executor: &mut Executor) -> Result<()> {
     let operands = executor.stack.pop_operands(1)?;
     executor.stack.push_operand(operands[0].clone());
     executor.stack.push_operand(operands[0].clone());
-    executor.pc(1)?;
 
     Ok(())
 }
@@ -5,14 +5,8 @@ use crate::{
 };
 use anyhow::{bail, Context, Result};
 
-pub fn perform(executor: &mut Executor) -> Result<()> {
-    executor.pc(1)?;
-    let indexbyte1 = executor.stack.get_opcode()? as u16;
-    executor.pc(1)?;
-    let indexbyte2 = executor.stack.get_opcode()? as u16;
-    executor.pc(1)?;
-    let method_index = Index::new((indexbyte1 << 8) | indexbyte2);
-    let (class_identifier, name_and_type) = executor.stack.lookup_method(&method_index)?;
+pub fn perform(executor: &mut Executor, index: &Index) -> Result<()> {
+    let (class_identifier, name_and_type) = executor.stack.lookup_method(index)?;
     let class = executor.class_loader.load(class_identifier.clone())?;
     executor.initialize_class(class)?;
     let class = executor
@@ -27,6 +21,14 @@ pub fn perform(executor: &mut Executor) -> Result<()> {
         bail!("signature polymorphic methods not implemented for invokevirtual");
     }
 
+    if method.is_private() {
+        executor.check_private_access(&class, &name_and_type)?;
+    }
+
+    if method.is_native() {
+        return executor.invoke_native(&class, &method);
+    }
+
     bail!("invokevirtual objectref??");
 }
 
@@ -4,11 +4,8 @@ use crate::{
 };
 use anyhow::{bail, Result};
 
-pub fn perform(executor: &mut Executor) -> Result<()> {
-    executor.pc(1)?;
-    let index = Index::new(executor.stack.get_opcode()?);
-    executor.pc(1)?;
-    let cp_item = executor.stack.resolve_in_cp(&index)?;
+pub fn perform(executor: &mut Executor, index: &Index) -> Result<()> {
+    let cp_item = executor.stack.resolve_in_cp(index)?;
 
     if matches!(cp_item, ConstantPoolItem::Long { .. }) {
         bail!("constant pool item cannot be of type Long");
@@ -21,7 +18,7 @@ pub fn perform(executor: &mut Executor) -> Result<()> {
 
         ConstantPoolItem::ClassInfo { identifier } => {
             let class = executor.resolve_class(identifier)?;
-            executor.stack.push_operand(Word::Class { _class: class })
+            executor.stack.push_operand(Word::Class { class })
         }
         _ => bail!("constant pool item {cp_item:?} not yet supported by ldc"),
     }
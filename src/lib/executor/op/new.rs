@@ -8,15 +8,8 @@ use crate::{
 };
 use anyhow::Result;
 
-pub fn perform(executor: &mut Executor) -> Result<()> {
-    executor.pc(1)?;
-    let indexbyte1 = executor.stack.get_opcode()? as u16;
-    executor.pc(1)?;
-    let indexbyte2 = executor.stack.get_opcode()? as u16;
-    executor.pc(1)?;
-    let class_index = Index::new((indexbyte1 << 8) | indexbyte2);
-
-    let identifier = executor.stack.lookup_class(&class_index)?;
+pub fn perform(executor: &mut Executor, index: &Index) -> Result<()> {
+    let identifier = executor.stack.lookup_class(index)?;
     let class = executor.resolve_class(identifier)?;
     let instance = Instance::new(class)?;
     let reference = Word::Reference(Reference::Instance(instance));
@@ -5,6 +5,7 @@ use crate::{
     parser::{
         constant_pool::{ConstantPoolItem, Index, NameAndType},
         descriptor::FieldType,
+        instruction::Instruction,
     },
     ClassIdentifier,
 };
@@ -45,7 +46,7 @@ pub enum Word {
     _Boolean(bool),
     _ReturnAdress(usize),
     Reference(Reference),
-    Class { _class: Class },
+    Class { class: Class },
     _Null,
 }
 impl Word {
@@ -78,7 +79,7 @@ impl Display for Word {
             Word::_Boolean(val) => write!(f, "Boolean({val})"),
             Word::_ReturnAdress(val) => write!(f, "ReturnAdress({val})"),
             Word::Reference(val) => write!(f, "Reference({val})"),
-            Word::Class { _class } => write!(f, "Class({_class})"),
+            Word::Class { class } => write!(f, "Class({class})"),
             Word::_Null => write!(f, "Null"),
         }
     }
@@ -110,14 +111,18 @@ impl Frame {
             .context("no entry at {index:?} in constant pool")
     }
 
-    fn pc(&mut self, n: usize) {
-        self.pc += n;
+    fn next(&mut self) -> Result<()> {
+        self.pc = self
+            .code
+            .next_pc(self.pc)
+            .context(format!("no instruction after offset {}", self.pc))?;
+        Ok(())
     }
 
-    fn get_op_code(&self) -> Result<u8> {
+    fn get_instruction(&self) -> Result<Instruction> {
         self.code
-            .get_opcode(self.pc)
-            .context(format!("no op code at index {}", self.pc))
+            .get_instruction(self.pc)
+            .context(format!("no instruction at offset {}", self.pc))
     }
 }
 
@@ -157,8 +162,8 @@ impl Stack {
         Ok(class.is_public() || class.identifier.package == self.current_frame()?.class.package())
     }
 
-    pub fn get_opcode(&self) -> Result<u8> {
-        self.current_frame()?.get_op_code()
+    pub fn get_instruction(&self) -> Result<Instruction> {
+        self.current_frame()?.get_instruction()
     }
 
     pub fn pop_operands(&mut self, n: usize) -> Result<Vec<Word>> {
@@ -178,13 +183,16 @@ impl Stack {
         Ok(self.current_frame()?.local_variables.clone())
     }
 
+    pub fn current_class(&self) -> Result<Class> {
+        Ok(self.current_frame()?.class.clone())
+    }
+
     pub fn current_method(&self) -> Result<Method> {
         Ok(self.current_frame()?.method.clone())
     }
 
-    pub fn pc(&mut self, n: usize) -> Result<()> {
-        self.current_frame_mut()?.pc(n);
-        Ok(())
+    pub fn next(&mut self) -> Result<()> {
+        self.current_frame_mut()?.next()
     }
 
     pub fn pop(&mut self) -> Option<Frame> {
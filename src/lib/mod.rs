@@ -1,13 +1,14 @@
 use anyhow::Result;
 use std::{fmt::Display, path::PathBuf};
 
-use executor::{
-    loader::{class_path::ClassPath, ClassLoader},
-    Executor,
-};
+use executor::{loader::ClassLoader, Executor};
 
+pub mod assembler;
 mod executor;
-mod parser;
+pub mod parser;
+pub mod verifier;
+
+pub use executor::loader::class_path::ClassPath;
 
 pub fn run(class_path: Vec<PathBuf>, main_class: ClassIdentifier) -> Result<()> {
     let class_path = ClassPath::load(class_path)?;
@@ -82,7 +83,11 @@ impl ClassIdentifier {
         let text = text.replace("/", ".");
         let parts: Vec<&str> = text.split(".").collect();
         let name = ClassName::new(parts.last()?.to_string());
-        let package = Package::new(parts[..parts.len() - 1].join("."));
+        let package = if parts.len() == 1 {
+            Package::default()
+        } else {
+            Package::new(parts[..parts.len() - 1].join("."))
+        };
         Some(Self { package, name })
     }
 }
@@ -1,22 +1,31 @@
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use std::io::Cursor;
 
-use crate::parser::{constant_pool::Index, parse_u16, parse_u8};
+use crate::parser::{
+    constant_pool::{ConstantPool, ConstantPoolItem, Index},
+    descriptor::{FieldType, ReturnDescriptor},
+    parse_u16, parse_u8, write_count, write_index, write_u16, write_u8,
+};
+
+use super::Attribute;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Annotation {
-    type_index: Index,
-    element_value_pairs: Vec<(u16, ElementValue)>,
+    pub type_index: Index,
+    pub element_value_pairs: Vec<ElementValuePair>,
 }
 
 impl Annotation {
-    pub fn new(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    pub fn new(c: &mut Cursor<&[u8]>) -> Result<Self> {
         let type_index = Index::new(parse_u16(c)?);
-        let num_element_value_pairs = parse_u16(c)?;
+        let num_element_value_pairs = parse_u16(c)? as usize;
 
-        let mut element_value_pairs = Vec::new();
+        let mut element_value_pairs = Vec::with_capacity(num_element_value_pairs);
         for _ in 0..num_element_value_pairs {
-            element_value_pairs.push((parse_u16(c)?, ElementValue::new(c)?));
+            element_value_pairs.push(ElementValuePair {
+                element_name_index: Index::new(parse_u16(c)?),
+                value: ElementValue::new(c)?,
+            });
         }
 
         Ok(Self {
@@ -24,26 +33,740 @@ impl Annotation {
             element_value_pairs,
         })
     }
+
+    pub fn annotations(c: &mut Cursor<&[u8]>) -> Result<Vec<Self>> {
+        let num_annotations = parse_u16(c)? as usize;
+
+        let mut annotations = Vec::with_capacity(num_annotations);
+        for _ in 0..num_annotations {
+            annotations.push(Self::new(c)?);
+        }
+
+        Ok(annotations)
+    }
+
+    pub fn parameter_annotations(c: &mut Cursor<&[u8]>) -> Result<Vec<Vec<Self>>> {
+        let num_parameters = parse_u8(c)? as usize;
+
+        let mut parameter_annotations = Vec::with_capacity(num_parameters);
+        for _ in 0..num_parameters {
+            parameter_annotations.push(Self::annotations(c)?);
+        }
+
+        Ok(parameter_annotations)
+    }
+
+    pub fn write(&self, w: &mut Vec<u8>) -> Result<()> {
+        write_index(w, &self.type_index)?;
+        write_count(w, self.element_value_pairs.len())?;
+        for pair in &self.element_value_pairs {
+            write_index(w, &pair.element_name_index)?;
+            pair.value.write(w)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_annotations(annotations: &[Self], w: &mut Vec<u8>) -> Result<()> {
+        write_count(w, annotations.len())?;
+        for annotation in annotations {
+            annotation.write(w)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_parameter_annotations(
+        parameter_annotations: &[Vec<Self>],
+        w: &mut Vec<u8>,
+    ) -> Result<()> {
+        write_u8(w, u8::try_from(parameter_annotations.len())?);
+        for annotations in parameter_annotations {
+            Self::write_annotations(annotations, w)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn resolve(&self, constant_pool: &ConstantPool) -> Result<AnnotationItem> {
+        let mut elements = Vec::with_capacity(self.element_value_pairs.len());
+        for pair in &self.element_value_pairs {
+            elements.push((
+                utf8(constant_pool, &pair.element_name_index)?,
+                pair.value.resolve(constant_pool)?,
+            ));
+        }
+
+        Ok(AnnotationItem {
+            annotation_type: FieldType::new(&utf8(constant_pool, &self.type_index)?)?,
+            elements,
+        })
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
-enum ElementValue {
-    String { const_value_index: Index },
-    Boolean { const_value_index: Index },
+pub struct ElementValuePair {
+    pub element_name_index: Index,
+    pub value: ElementValue,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ElementValue {
+    Byte {
+        const_value_index: Index,
+    },
+    Char {
+        const_value_index: Index,
+    },
+    Double {
+        const_value_index: Index,
+    },
+    Float {
+        const_value_index: Index,
+    },
+    Int {
+        const_value_index: Index,
+    },
+    Long {
+        const_value_index: Index,
+    },
+    Short {
+        const_value_index: Index,
+    },
+    Boolean {
+        const_value_index: Index,
+    },
+    String {
+        const_value_index: Index,
+    },
+    Enum {
+        type_name_index: Index,
+        const_name_index: Index,
+    },
+    Class {
+        class_info_index: Index,
+    },
+    Annotation(Annotation),
+    Array {
+        values: Vec<ElementValue>,
+    },
 }
 
 impl ElementValue {
-    fn new(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    pub fn new(c: &mut Cursor<&[u8]>) -> Result<Self> {
         let tag = parse_u8(c)? as char;
 
         Ok(match tag {
-            'Z' => ElementValue::Boolean {
+            'B' => Self::Byte {
+                const_value_index: Index::new(parse_u16(c)?),
+            },
+            'C' => Self::Char {
+                const_value_index: Index::new(parse_u16(c)?),
+            },
+            'D' => Self::Double {
+                const_value_index: Index::new(parse_u16(c)?),
+            },
+            'F' => Self::Float {
+                const_value_index: Index::new(parse_u16(c)?),
+            },
+            'I' => Self::Int {
                 const_value_index: Index::new(parse_u16(c)?),
             },
-            's' => ElementValue::String {
+            'J' => Self::Long {
                 const_value_index: Index::new(parse_u16(c)?),
             },
+            'S' => Self::Short {
+                const_value_index: Index::new(parse_u16(c)?),
+            },
+            'Z' => Self::Boolean {
+                const_value_index: Index::new(parse_u16(c)?),
+            },
+            's' => Self::String {
+                const_value_index: Index::new(parse_u16(c)?),
+            },
+            'e' => Self::Enum {
+                type_name_index: Index::new(parse_u16(c)?),
+                const_name_index: Index::new(parse_u16(c)?),
+            },
+            'c' => Self::Class {
+                class_info_index: Index::new(parse_u16(c)?),
+            },
+            '@' => Self::Annotation(Annotation::new(c)?),
+            '[' => {
+                let num_values = parse_u16(c)? as usize;
+                let mut values = Vec::with_capacity(num_values);
+                for _ in 0..num_values {
+                    values.push(Self::new(c)?);
+                }
+                Self::Array { values }
+            }
             _ => bail!("Unknown element value tag {tag}"),
         })
     }
+
+    pub fn write(&self, w: &mut Vec<u8>) -> Result<()> {
+        match self {
+            Self::Byte { const_value_index } => write_const(w, 'B', const_value_index)?,
+            Self::Char { const_value_index } => write_const(w, 'C', const_value_index)?,
+            Self::Double { const_value_index } => write_const(w, 'D', const_value_index)?,
+            Self::Float { const_value_index } => write_const(w, 'F', const_value_index)?,
+            Self::Int { const_value_index } => write_const(w, 'I', const_value_index)?,
+            Self::Long { const_value_index } => write_const(w, 'J', const_value_index)?,
+            Self::Short { const_value_index } => write_const(w, 'S', const_value_index)?,
+            Self::Boolean { const_value_index } => write_const(w, 'Z', const_value_index)?,
+            Self::String { const_value_index } => write_const(w, 's', const_value_index)?,
+            Self::Enum {
+                type_name_index,
+                const_name_index,
+            } => {
+                write_u8(w, b'e');
+                write_index(w, type_name_index)?;
+                write_index(w, const_name_index)?;
+            }
+            Self::Class { class_info_index } => write_const(w, 'c', class_info_index)?,
+            Self::Annotation(annotation) => {
+                write_u8(w, b'@');
+                annotation.write(w)?;
+            }
+            Self::Array { values } => {
+                write_u8(w, b'[');
+                write_count(w, values.len())?;
+                for value in values {
+                    value.write(w)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn resolve(&self, constant_pool: &ConstantPool) -> Result<ElementValueItem> {
+        Ok(match self {
+            Self::Byte { const_value_index } => {
+                ElementValueItem::Byte(integer(constant_pool, const_value_index)? as i8)
+            }
+            Self::Char { const_value_index } => {
+                ElementValueItem::Char(integer(constant_pool, const_value_index)? as u16)
+            }
+            Self::Double { const_value_index } => {
+                match resolve(constant_pool, const_value_index)? {
+                    ConstantPoolItem::Double { val } => ElementValueItem::Double(val),
+                    item => bail!("expected Double constant for element value, got {item:?}"),
+                }
+            }
+            Self::Float { const_value_index } => match resolve(constant_pool, const_value_index)? {
+                ConstantPoolItem::Float { val } => ElementValueItem::Float(val),
+                item => bail!("expected Float constant for element value, got {item:?}"),
+            },
+            Self::Int { const_value_index } => {
+                ElementValueItem::Int(integer(constant_pool, const_value_index)?)
+            }
+            Self::Long { const_value_index } => match resolve(constant_pool, const_value_index)? {
+                ConstantPoolItem::Long { val } => ElementValueItem::Long(val),
+                item => bail!("expected Long constant for element value, got {item:?}"),
+            },
+            Self::Short { const_value_index } => {
+                ElementValueItem::Short(integer(constant_pool, const_value_index)? as i16)
+            }
+            Self::Boolean { const_value_index } => {
+                ElementValueItem::Boolean(integer(constant_pool, const_value_index)? != 0)
+            }
+            Self::String { const_value_index } => {
+                ElementValueItem::String(utf8(constant_pool, const_value_index)?)
+            }
+            Self::Enum {
+                type_name_index,
+                const_name_index,
+            } => ElementValueItem::Enum {
+                enum_type: FieldType::new(&utf8(constant_pool, type_name_index)?)?,
+                const_name: utf8(constant_pool, const_name_index)?,
+            },
+            Self::Class { class_info_index } => ElementValueItem::Class(ReturnDescriptor::new(
+                &utf8(constant_pool, class_info_index)?,
+            )?),
+            Self::Annotation(annotation) => {
+                ElementValueItem::Annotation(annotation.resolve(constant_pool)?)
+            }
+            Self::Array { values } => ElementValueItem::Array(
+                values
+                    .iter()
+                    .map(|v| v.resolve(constant_pool))
+                    .collect::<Result<Vec<ElementValueItem>>>()?,
+            ),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeAnnotation {
+    pub target_type: u8,
+    pub target_info: TargetInfo,
+    pub target_path: Vec<TypePathEntry>,
+    pub annotation: Annotation,
+}
+
+impl TypeAnnotation {
+    pub fn new(c: &mut Cursor<&[u8]>) -> Result<Self> {
+        let target_type = parse_u8(c)?;
+        let target_info = TargetInfo::new(c, target_type)?;
+
+        let path_length = parse_u8(c)? as usize;
+        let mut target_path = Vec::with_capacity(path_length);
+        for _ in 0..path_length {
+            target_path.push(TypePathEntry {
+                type_path_kind: parse_u8(c)?,
+                type_argument_index: parse_u8(c)?,
+            });
+        }
+
+        Ok(Self {
+            target_type,
+            target_info,
+            target_path,
+            annotation: Annotation::new(c)?,
+        })
+    }
+
+    pub fn annotations(c: &mut Cursor<&[u8]>) -> Result<Vec<Self>> {
+        let num_annotations = parse_u16(c)? as usize;
+
+        let mut annotations = Vec::with_capacity(num_annotations);
+        for _ in 0..num_annotations {
+            annotations.push(Self::new(c)?);
+        }
+
+        Ok(annotations)
+    }
+
+    pub fn write(&self, w: &mut Vec<u8>) -> Result<()> {
+        write_u8(w, self.target_type);
+        self.target_info.write(w)?;
+        write_u8(w, u8::try_from(self.target_path.len())?);
+        for entry in &self.target_path {
+            write_u8(w, entry.type_path_kind);
+            write_u8(w, entry.type_argument_index);
+        }
+        self.annotation.write(w)
+    }
+
+    pub fn write_annotations(annotations: &[Self], w: &mut Vec<u8>) -> Result<()> {
+        write_count(w, annotations.len())?;
+        for annotation in annotations {
+            annotation.write(w)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn resolve(&self, constant_pool: &ConstantPool) -> Result<TypeAnnotationItem> {
+        Ok(TypeAnnotationItem {
+            target_type: self.target_type,
+            target_info: self.target_info.clone(),
+            target_path: self.target_path.clone(),
+            annotation: self.annotation.resolve(constant_pool)?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TargetInfo {
+    TypeParameter {
+        type_parameter_index: u8,
+    },
+    Supertype {
+        supertype_index: u16,
+    },
+    TypeParameterBound {
+        type_parameter_index: u8,
+        bound_index: u8,
+    },
+    Empty,
+    FormalParameter {
+        formal_parameter_index: u8,
+    },
+    Throws {
+        throws_type_index: u16,
+    },
+    Localvar {
+        table: Vec<LocalvarTarget>,
+    },
+    Catch {
+        exception_table_index: u16,
+    },
+    Offset {
+        offset: u16,
+    },
+    TypeArgument {
+        offset: u16,
+        type_argument_index: u8,
+    },
+}
+
+impl TargetInfo {
+    fn new(c: &mut Cursor<&[u8]>, target_type: u8) -> Result<Self> {
+        Ok(match target_type {
+            0x00 | 0x01 => Self::TypeParameter {
+                type_parameter_index: parse_u8(c)?,
+            },
+            0x10 => Self::Supertype {
+                supertype_index: parse_u16(c)?,
+            },
+            0x11 | 0x12 => Self::TypeParameterBound {
+                type_parameter_index: parse_u8(c)?,
+                bound_index: parse_u8(c)?,
+            },
+            0x13..=0x15 => Self::Empty,
+            0x16 => Self::FormalParameter {
+                formal_parameter_index: parse_u8(c)?,
+            },
+            0x17 => Self::Throws {
+                throws_type_index: parse_u16(c)?,
+            },
+            0x40 | 0x41 => {
+                let table_length = parse_u16(c)? as usize;
+                let mut table = Vec::with_capacity(table_length);
+                for _ in 0..table_length {
+                    table.push(LocalvarTarget {
+                        start_pc: parse_u16(c)?,
+                        length: parse_u16(c)?,
+                        index: parse_u16(c)?,
+                    });
+                }
+                Self::Localvar { table }
+            }
+            0x42 => Self::Catch {
+                exception_table_index: parse_u16(c)?,
+            },
+            0x43..=0x46 => Self::Offset {
+                offset: parse_u16(c)?,
+            },
+            0x47..=0x4B => Self::TypeArgument {
+                offset: parse_u16(c)?,
+                type_argument_index: parse_u8(c)?,
+            },
+            _ => bail!("invalid type annotation target type 0x{target_type:X}"),
+        })
+    }
+
+    fn write(&self, w: &mut Vec<u8>) -> Result<()> {
+        match self {
+            Self::TypeParameter {
+                type_parameter_index,
+            } => write_u8(w, *type_parameter_index),
+            Self::Supertype { supertype_index } => write_u16(w, *supertype_index),
+            Self::TypeParameterBound {
+                type_parameter_index,
+                bound_index,
+            } => {
+                write_u8(w, *type_parameter_index);
+                write_u8(w, *bound_index);
+            }
+            Self::Empty => {}
+            Self::FormalParameter {
+                formal_parameter_index,
+            } => write_u8(w, *formal_parameter_index),
+            Self::Throws { throws_type_index } => write_u16(w, *throws_type_index),
+            Self::Localvar { table } => {
+                write_count(w, table.len())?;
+                for entry in table {
+                    write_u16(w, entry.start_pc);
+                    write_u16(w, entry.length);
+                    write_u16(w, entry.index);
+                }
+            }
+            Self::Catch {
+                exception_table_index,
+            } => write_u16(w, *exception_table_index),
+            Self::Offset { offset } => write_u16(w, *offset),
+            Self::TypeArgument {
+                offset,
+                type_argument_index,
+            } => {
+                write_u16(w, *offset);
+                write_u8(w, *type_argument_index);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalvarTarget {
+    pub start_pc: u16,
+    pub length: u16,
+    pub index: u16,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypePathEntry {
+    pub type_path_kind: u8,
+    pub type_argument_index: u8,
+}
+
+/// An annotation with all constant pool references resolved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnnotationItem {
+    pub annotation_type: FieldType,
+    pub elements: Vec<(String, ElementValueItem)>,
+}
+
+impl AnnotationItem {
+    pub fn element(&self, name: &str) -> Option<&ElementValueItem> {
+        self.elements
+            .iter()
+            .find(|(element_name, _)| element_name == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Whether this annotation is of the class with the given binary name, e.g. `java/lang/Deprecated`.
+    pub fn is(&self, class_name: &str) -> bool {
+        matches!(&self.annotation_type, FieldType::Class(name) if name == class_name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ElementValueItem {
+    Byte(i8),
+    Char(u16),
+    Double(f64),
+    Float(f32),
+    Int(i32),
+    Long(i64),
+    Short(i16),
+    Boolean(bool),
+    String(String),
+    Enum {
+        enum_type: FieldType,
+        const_name: String,
+    },
+    Class(ReturnDescriptor),
+    Annotation(AnnotationItem),
+    Array(Vec<ElementValueItem>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeAnnotationItem {
+    pub target_type: u8,
+    pub target_info: TargetInfo,
+    pub target_path: Vec<TypePathEntry>,
+    pub annotation: AnnotationItem,
+}
+
+/// All annotations attached to a class, field or method, resolved through the constant pool.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Annotations {
+    pub visible: Vec<AnnotationItem>,
+    pub invisible: Vec<AnnotationItem>,
+    pub visible_parameters: Vec<Vec<AnnotationItem>>,
+    pub invisible_parameters: Vec<Vec<AnnotationItem>>,
+    pub visible_type: Vec<TypeAnnotationItem>,
+    pub invisible_type: Vec<TypeAnnotationItem>,
+    pub default: Option<ElementValueItem>,
+}
+
+impl Annotations {
+    pub fn new(attributes: &[Attribute], constant_pool: &ConstantPool) -> Result<Self> {
+        let mut annotations = Self::default();
+
+        for attribute in attributes {
+            match attribute {
+                Attribute::RuntimeVisibleAnnotations { annotations: a } => {
+                    annotations.visible = resolve_all(a, constant_pool)?
+                }
+                Attribute::RuntimeInvisibleAnnotations { annotations: a } => {
+                    annotations.invisible = resolve_all(a, constant_pool)?
+                }
+                Attribute::RuntimeVisibleParameterAnnotations {
+                    parameter_annotations,
+                } => {
+                    annotations.visible_parameters = parameter_annotations
+                        .iter()
+                        .map(|a| resolve_all(a, constant_pool))
+                        .collect::<Result<_>>()?
+                }
+                Attribute::RuntimeInvisibleParameterAnnotations {
+                    parameter_annotations,
+                } => {
+                    annotations.invisible_parameters = parameter_annotations
+                        .iter()
+                        .map(|a| resolve_all(a, constant_pool))
+                        .collect::<Result<_>>()?
+                }
+                Attribute::RuntimeVisibleTypeAnnotations { annotations: a } => {
+                    annotations.visible_type = a
+                        .iter()
+                        .map(|a| a.resolve(constant_pool))
+                        .collect::<Result<_>>()?
+                }
+                Attribute::RuntimeInvisibleTypeAnnotations { annotations: a } => {
+                    annotations.invisible_type = a
+                        .iter()
+                        .map(|a| a.resolve(constant_pool))
+                        .collect::<Result<_>>()?
+                }
+                Attribute::AnnotationDefault { default_value } => {
+                    annotations.default = Some(default_value.resolve(constant_pool)?)
+                }
+                _ => {}
+            }
+        }
+
+        Ok(annotations)
+    }
+
+    /// Looks up a visible or invisible annotation by the binary name of its type.
+    pub fn find(&self, class_name: &str) -> Option<&AnnotationItem> {
+        self.visible
+            .iter()
+            .chain(self.invisible.iter())
+            .find(|a| a.is(class_name))
+    }
+}
+
+fn resolve_all(
+    annotations: &[Annotation],
+    constant_pool: &ConstantPool,
+) -> Result<Vec<AnnotationItem>> {
+    annotations
+        .iter()
+        .map(|a| a.resolve(constant_pool))
+        .collect()
+}
+
+fn write_const(w: &mut Vec<u8>, tag: char, index: &Index) -> Result<()> {
+    write_u8(w, tag as u8);
+    write_index(w, index)
+}
+
+fn resolve(constant_pool: &ConstantPool, index: &Index) -> Result<ConstantPoolItem> {
+    constant_pool
+        .resolve(index)
+        .context(format!("no constant pool entry found for {index:?}"))
+}
+
+fn utf8(constant_pool: &ConstantPool, index: &Index) -> Result<String> {
+    constant_pool
+        .utf8(index)
+        .context(format!("no constant pool utf8 entry found for {index:?}"))
+}
+
+fn integer(constant_pool: &ConstantPool, index: &Index) -> Result<i32> {
+    match resolve(constant_pool, index)? {
+        ConstantPoolItem::Integer { val } => Ok(val),
+        item => bail!("expected Integer constant for element value, got {item:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parser::{
+            class::ClassFile,
+            descriptor::{FieldType, ReturnDescriptor},
+        },
+        ClassIdentifier,
+    };
+
+    use super::{ElementValueItem, TargetInfo, TypePathEntry};
+
+    fn class_file(name: &str) -> ClassFile {
+        let data = std::fs::read(format!("testdata/{name}.class")).unwrap();
+        ClassFile::new(&data, ClassIdentifier::from_utf8(name.to_string()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_class_annotations() {
+        let annotations = class_file("Annotated").annotations().unwrap();
+        assert!(annotations.find("java/lang/Deprecated").is_some());
+
+        let info = annotations.find("Annotated$Info").unwrap();
+        assert_eq!(info.element("b"), Some(&ElementValueItem::Byte(1)));
+        assert_eq!(info.element("c"), Some(&ElementValueItem::Char('c' as u16)));
+        assert_eq!(info.element("d"), Some(&ElementValueItem::Double(1.5)));
+        assert_eq!(info.element("f"), Some(&ElementValueItem::Float(2.5)));
+        assert_eq!(info.element("i"), Some(&ElementValueItem::Int(3)));
+        assert_eq!(info.element("j"), Some(&ElementValueItem::Long(4)));
+        assert_eq!(info.element("s"), Some(&ElementValueItem::Short(5)));
+        assert_eq!(info.element("z"), Some(&ElementValueItem::Boolean(true)));
+        assert_eq!(
+            info.element("str"),
+            Some(&ElementValueItem::String("text".to_string()))
+        );
+        assert_eq!(
+            info.element("e"),
+            Some(&ElementValueItem::Enum {
+                enum_type: FieldType::Class("java/lang/annotation/ElementType".to_string()),
+                const_name: "FIELD".to_string(),
+            })
+        );
+        assert_eq!(
+            info.element("cls"),
+            Some(&ElementValueItem::Class(ReturnDescriptor::Type(
+                FieldType::Class("java/lang/String".to_string())
+            )))
+        );
+        match info.element("nested") {
+            Some(ElementValueItem::Annotation(nested)) => {
+                assert!(nested.is("java/lang/annotation/Retention"))
+            }
+            e => panic!("expected nested annotation, got {e:?}"),
+        }
+        assert_eq!(
+            info.element("arr"),
+            Some(&ElementValueItem::Array(vec![
+                ElementValueItem::Int(1),
+                ElementValueItem::Int(2)
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_method_annotations() {
+        let class_file = class_file("Annotated");
+        let method = class_file
+            .methods
+            .iter()
+            .find(|m| class_file.constant_pool.utf8(&m.name_index).unwrap() == "method")
+            .unwrap();
+        let annotations = method.annotations(&class_file.constant_pool).unwrap();
+
+        assert!(annotations.visible.is_empty());
+        assert!(annotations.invisible[0].is("Annotated$Invisible"));
+        assert_eq!(annotations.visible_parameters.len(), 2);
+        assert!(annotations.visible_parameters[0][0].is("Annotated$Info"));
+        assert!(annotations.visible_parameters[1].is_empty());
+
+        let type_annotation = &annotations.visible_type[1];
+        assert_eq!(type_annotation.target_type, 0x16);
+        assert_eq!(
+            type_annotation.target_info,
+            TargetInfo::FormalParameter {
+                formal_parameter_index: 1
+            }
+        );
+        assert_eq!(
+            type_annotation.target_path,
+            vec![TypePathEntry {
+                type_path_kind: 3,
+                type_argument_index: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_annotation_default() {
+        let class_file = class_file("Annotated$Info");
+        let method = class_file
+            .methods
+            .iter()
+            .find(|m| class_file.constant_pool.utf8(&m.name_index).unwrap() == "str")
+            .unwrap();
+        let annotations = method.annotations(&class_file.constant_pool).unwrap();
+
+        assert_eq!(
+            annotations.default,
+            Some(ElementValueItem::String("default".to_string()))
+        );
+    }
 }
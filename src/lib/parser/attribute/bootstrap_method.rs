@@ -1,14 +1,14 @@
-use crate::parser::parse_u16;
+use crate::parser::{parse_u16, write_count, write_u16};
 use anyhow::Result;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct BootstrapMethod {
-    bootstrap_method_ref: u16,
-    bootstrap_arguments: Vec<u16>,
+    pub bootstrap_method_ref: u16,
+    pub bootstrap_arguments: Vec<u16>,
 }
 
 impl BootstrapMethod {
-    pub fn new(c: &mut std::io::Cursor<&Vec<u8>>) -> Result<Self> {
+    pub fn new(c: &mut std::io::Cursor<&[u8]>) -> Result<Self> {
         let bootstrap_method_ref = parse_u16(c)?;
         let num_bootstrap_arguments = parse_u16(c)? as usize;
 
@@ -22,4 +22,14 @@ impl BootstrapMethod {
             bootstrap_arguments,
         })
     }
+
+    pub fn write(&self, w: &mut Vec<u8>) -> Result<()> {
+        write_u16(w, self.bootstrap_method_ref);
+        write_count(w, self.bootstrap_arguments.len())?;
+        for argument in &self.bootstrap_arguments {
+            write_u16(w, *argument);
+        }
+
+        Ok(())
+    }
 }
@@ -1,16 +1,16 @@
-use crate::parser::parse_u16;
+use crate::parser::{parse_u16, write_u16};
 use anyhow::Result;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Exception {
-    start_pc: u16,
-    end_pc: u16,
-    handler_pc: u16,
-    catch_type: u16,
+    pub start_pc: u16,
+    pub end_pc: u16,
+    pub handler_pc: u16,
+    pub catch_type: u16,
 }
 
 impl Exception {
-    pub fn new(c: &mut std::io::Cursor<&Vec<u8>>) -> Result<Self> {
+    pub fn new(c: &mut std::io::Cursor<&[u8]>) -> Result<Self> {
         Ok(Self {
             start_pc: parse_u16(c)?,
             end_pc: parse_u16(c)?,
@@ -18,4 +18,11 @@ impl Exception {
             catch_type: parse_u16(c)?,
         })
     }
+
+    pub fn write(&self, w: &mut Vec<u8>) {
+        write_u16(w, self.start_pc);
+        write_u16(w, self.end_pc);
+        write_u16(w, self.handler_pc);
+        write_u16(w, self.catch_type);
+    }
 }
@@ -1,16 +1,22 @@
-use crate::parser::{constant_pool::Index, parse_u16};
-use anyhow::Result;
+use crate::{
+    parser::{
+        constant_pool::{ConstantPool, Index},
+        parse_u16, write_index, write_u16,
+    },
+    ClassIdentifier,
+};
+use anyhow::{Context, Result};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct InnerClass {
-    inner_class_info_index: Index,
-    outer_class_info_index: Index,
-    inner_name_index: Index,
-    inner_class_access_flags: Vec<AccessFlag>,
+    pub inner_class_info_index: Index,
+    pub outer_class_info_index: Index,
+    pub inner_name_index: Index,
+    pub inner_class_access_flags: Vec<AccessFlag>,
 }
 
 impl InnerClass {
-    pub fn new(c: &mut std::io::Cursor<&Vec<u8>>) -> Result<Self> {
+    pub fn new(c: &mut std::io::Cursor<&[u8]>) -> Result<Self> {
         Ok(Self {
             inner_class_info_index: Index::new(parse_u16(c)?),
             outer_class_info_index: Index::new(parse_u16(c)?),
@@ -18,6 +24,51 @@ impl InnerClass {
             inner_class_access_flags: AccessFlag::flags(parse_u16(c)?),
         })
     }
+
+    pub fn write(&self, w: &mut Vec<u8>) -> Result<()> {
+        write_index(w, &self.inner_class_info_index)?;
+        write_index(w, &self.outer_class_info_index)?;
+        write_index(w, &self.inner_name_index)?;
+        write_u16(w, AccessFlag::bits(&self.inner_class_access_flags));
+        Ok(())
+    }
+
+    pub fn resolve(&self, constant_pool: &ConstantPool) -> Result<InnerClassItem> {
+        let class = |index: &Index| {
+            constant_pool
+                .class_identifier(index)
+                .context(format!("no class entry at {index:?}"))
+        };
+
+        Ok(InnerClassItem {
+            inner_class: class(&self.inner_class_info_index)?,
+            outer_class: if self.outer_class_info_index.is_zero() {
+                None
+            } else {
+                Some(class(&self.outer_class_info_index)?)
+            },
+            simple_name: if self.inner_name_index.is_zero() {
+                None
+            } else {
+                Some(
+                    constant_pool
+                        .utf8(&self.inner_name_index)
+                        .context(format!("no utf8 entry at {:?}", self.inner_name_index))?,
+                )
+            },
+            access_flags: self.inner_class_access_flags.clone(),
+        })
+    }
+}
+
+/// An entry of the `InnerClasses` attribute. `outer_class` is `None` unless the class is a
+/// member of another class, and `simple_name` is `None` for anonymous classes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InnerClassItem {
+    pub inner_class: ClassIdentifier,
+    pub outer_class: Option<ClassIdentifier>,
+    pub simple_name: Option<String>,
+    pub access_flags: Vec<AccessFlag>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -35,6 +86,25 @@ pub enum AccessFlag {
 }
 
 impl AccessFlag {
+    fn mask(&self) -> u16 {
+        match self {
+            Self::Public => 0x0001,
+            Self::Private => 0x0002,
+            Self::Protected => 0x0004,
+            Self::Static => 0x0008,
+            Self::Final => 0x0010,
+            Self::Interface => 0x0200,
+            Self::Abstract => 0x0400,
+            Self::Synthetic => 0x1000,
+            Self::Annotation => 0x2000,
+            Self::Enum => 0x4000,
+        }
+    }
+
+    pub fn bits(flags: &[Self]) -> u16 {
+        flags.iter().fold(0, |bits, flag| bits | flag.mask())
+    }
+
     fn flags(val: u16) -> Vec<Self> {
         let mut flags = Vec::new();
 
@@ -1,4 +1,4 @@
-use crate::parser::parse_u16;
+use crate::parser::{parse_u16, write_u16};
 use anyhow::Result;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -8,10 +8,15 @@ pub struct LineNumberTableEntry {
 }
 
 impl LineNumberTableEntry {
-    pub fn new(c: &mut std::io::Cursor<&Vec<u8>>) -> Result<LineNumberTableEntry> {
+    pub fn new(c: &mut std::io::Cursor<&[u8]>) -> Result<LineNumberTableEntry> {
         Ok(LineNumberTableEntry {
             start_pc: parse_u16(c)?,
             line_number: parse_u16(c)?,
         })
     }
+
+    pub fn write(&self, w: &mut Vec<u8>) {
+        write_u16(w, self.start_pc);
+        write_u16(w, self.line_number);
+    }
 }
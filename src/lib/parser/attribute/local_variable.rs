@@ -1,19 +1,19 @@
 use anyhow::Result;
 use std::io::Cursor;
 
-use crate::parser::{constant_pool::Index, parse_u16};
+use crate::parser::{constant_pool::Index, parse_u16, write_count, write_index, write_u16};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct LocalVariable {
-    start_pc: u16,
-    length: u16,
-    name_index: Index,
-    descriptor_index: Index,
-    index: usize,
+    pub start_pc: u16,
+    pub length: u16,
+    pub name_index: Index,
+    pub descriptor_index: Index,
+    pub index: usize,
 }
 
 impl LocalVariable {
-    pub fn new(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    pub fn new(c: &mut Cursor<&[u8]>) -> Result<Self> {
         Ok(Self {
             start_pc: parse_u16(c)?,
             length: parse_u16(c)?,
@@ -22,19 +22,27 @@ impl LocalVariable {
             index: parse_u16(c)? as usize,
         })
     }
+
+    pub fn write(&self, w: &mut Vec<u8>) -> Result<()> {
+        write_u16(w, self.start_pc);
+        write_u16(w, self.length);
+        write_index(w, &self.name_index)?;
+        write_index(w, &self.descriptor_index)?;
+        write_count(w, self.index)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct LocalVariableType {
-    start_pc: u16,
-    length: u16,
-    name_index: Index,
-    signature_index: Index,
-    index: usize,
+    pub start_pc: u16,
+    pub length: u16,
+    pub name_index: Index,
+    pub signature_index: Index,
+    pub index: usize,
 }
 
 impl LocalVariableType {
-    pub fn new(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    pub fn new(c: &mut Cursor<&[u8]>) -> Result<Self> {
         Ok(Self {
             start_pc: parse_u16(c)?,
             length: parse_u16(c)?,
@@ -43,4 +51,12 @@ impl LocalVariableType {
             index: parse_u16(c)? as usize,
         })
     }
+
+    pub fn write(&self, w: &mut Vec<u8>) -> Result<()> {
+        write_u16(w, self.start_pc);
+        write_u16(w, self.length);
+        write_index(w, &self.name_index)?;
+        write_index(w, &self.signature_index)?;
+        write_count(w, self.index)
+    }
 }
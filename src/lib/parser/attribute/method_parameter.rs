@@ -0,0 +1,92 @@
+use anyhow::{Context, Result};
+use std::io::Cursor;
+
+use crate::parser::{
+    constant_pool::{ConstantPool, Index},
+    parse_u16, write_index, write_u16,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MethodParameter {
+    pub name_index: Index,
+    pub access_flags: Vec<AccessFlag>,
+}
+
+impl MethodParameter {
+    pub fn new(c: &mut Cursor<&[u8]>) -> Result<Self> {
+        Ok(Self {
+            name_index: Index::new(parse_u16(c)?),
+            access_flags: AccessFlag::flags(parse_u16(c)?),
+        })
+    }
+
+    pub fn write(&self, w: &mut Vec<u8>) -> Result<()> {
+        write_index(w, &self.name_index)?;
+        write_u16(w, AccessFlag::bits(&self.access_flags));
+        Ok(())
+    }
+
+    pub fn resolve(&self, constant_pool: &ConstantPool) -> Result<MethodParameterItem> {
+        let name = if self.name_index.is_zero() {
+            None
+        } else {
+            Some(
+                constant_pool
+                    .utf8(&self.name_index)
+                    .context(format!("no utf8 entry at {:?}", self.name_index))?,
+            )
+        };
+
+        Ok(MethodParameterItem {
+            name,
+            access_flags: self.access_flags.clone(),
+        })
+    }
+}
+
+/// A formal parameter from the `MethodParameters` attribute; `name` is `None` for
+/// parameters compiled without a name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MethodParameterItem {
+    pub name: Option<String>,
+    pub access_flags: Vec<AccessFlag>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccessFlag {
+    Final,
+    Synthetic,
+    Mandated,
+}
+
+impl AccessFlag {
+    fn mask(&self) -> u16 {
+        match self {
+            Self::Final => 0x0010,
+            Self::Synthetic => 0x1000,
+            Self::Mandated => 0x8000,
+        }
+    }
+
+    fn bits(flags: &[Self]) -> u16 {
+        flags.iter().fold(0, |bits, flag| bits | flag.mask())
+    }
+
+    fn flags(val: u16) -> Vec<Self> {
+        let mut flags = Vec::new();
+
+        if (val & 0x0010) != 0 {
+            flags.push(Self::Final);
+        }
+
+        if (val & 0x1000) != 0 {
+            flags.push(Self::Synthetic);
+        }
+
+        if (val & 0x8000) != 0 {
+            flags.push(Self::Mandated);
+        }
+
+        flags
+    }
+}
@@ -1,27 +1,37 @@
-use anyhow::{bail, Context, Result};
-use std::io::{Cursor, Seek};
+use anyhow::{anyhow, bail, Context, Result};
+use std::{
+    io::Cursor,
+    ops::{Deref, DerefMut},
+};
 
-use annotation::Annotation;
+use annotation::{Annotation, ElementValue, TypeAnnotation};
 use bootstrap_method::BootstrapMethod;
 use exception::Exception;
 use inner_class::InnerClass;
 use line_number_table_entry::LineNumberTableEntry;
 use local_variable::{LocalVariable, LocalVariableType};
+use method_parameter::MethodParameter;
+use module::Module;
+use record::RecordComponent;
 use stack_map_frame::StackMapFrame;
 use tracing::{instrument, trace};
 
 use super::{
     constant_pool::{ConstantPool, Index},
-    parse_u16, parse_u32, parse_vec,
+    parse_u16, parse_u32, parse_u8, parse_vec, write_count, write_index, write_u16, write_u32,
+    write_u8,
 };
 
-mod annotation;
-mod bootstrap_method;
-mod exception;
-mod inner_class;
-mod line_number_table_entry;
-mod local_variable;
-mod stack_map_frame;
+pub mod annotation;
+pub mod bootstrap_method;
+pub mod exception;
+pub mod inner_class;
+pub mod line_number_table_entry;
+pub mod local_variable;
+pub mod method_parameter;
+pub mod module;
+pub mod record;
+pub mod stack_map_frame;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Attribute {
@@ -30,7 +40,7 @@ pub enum Attribute {
         max_locals: u16,
         code: Vec<u8>,
         exceptions: Vec<Exception>,
-        attributes: Vec<Attribute>,
+        attributes: Attributes,
     },
     LineNumberTable {
         table: Vec<LineNumberTableEntry>,
@@ -45,6 +55,24 @@ pub enum Attribute {
     RuntimeVisibleAnnotations {
         annotations: Vec<Annotation>,
     },
+    RuntimeInvisibleAnnotations {
+        annotations: Vec<Annotation>,
+    },
+    RuntimeVisibleParameterAnnotations {
+        parameter_annotations: Vec<Vec<Annotation>>,
+    },
+    RuntimeInvisibleParameterAnnotations {
+        parameter_annotations: Vec<Vec<Annotation>>,
+    },
+    RuntimeVisibleTypeAnnotations {
+        annotations: Vec<TypeAnnotation>,
+    },
+    RuntimeInvisibleTypeAnnotations {
+        annotations: Vec<TypeAnnotation>,
+    },
+    AnnotationDefault {
+        default_value: ElementValue,
+    },
     LocalVariableTable {
         local_variable_table: Vec<LocalVariable>,
     },
@@ -78,23 +106,161 @@ pub enum Attribute {
     NestHost {
         host_class_index: Index,
     },
+    Module {
+        module: Module,
+    },
+    ModulePackages {
+        package_index: Vec<Index>,
+    },
+    ModuleMainClass {
+        main_class_index: Index,
+    },
+    Record {
+        components: Vec<RecordComponent>,
+    },
+    PermittedSubclasses {
+        classes: Vec<Index>,
+    },
+    MethodParameters {
+        parameters: Vec<MethodParameter>,
+    },
+    SourceDebugExtension {
+        debug_extension: Vec<u8>,
+    },
+    /// Attribute not known to this parser, kept verbatim as required by JVMS 4.7.1.
+    Unknown {
+        name: String,
+        bytes: Vec<u8>,
+    },
+}
+
+/// The attributes of a class, field, method, record component or `Code` attribute.
+///
+/// Parsed attributes keep the constant pool index their name was read from, so that a pool
+/// with duplicate Utf8 entries is written back unchanged. Attributes pushed later are written
+/// with the first matching Utf8 entry.
+#[derive(Clone, Debug, Default, Eq)]
+pub struct Attributes {
+    attributes: Vec<Attribute>,
+    name_indexes: Vec<Option<Index>>,
+}
+
+impl Attributes {
+    pub fn new(c: &mut Cursor<&[u8]>, constant_pool: &ConstantPool) -> Result<Self> {
+        let attributes_count = parse_u16(c)? as usize;
+        let mut attributes = Self::default();
+        for _ in 0..attributes_count {
+            let (name_index, attribute) = Attribute::new(c, constant_pool)?;
+            attributes.attributes.push(attribute);
+            attributes.name_indexes.push(Some(name_index));
+        }
+
+        Ok(attributes)
+    }
+
+    pub fn push(&mut self, attribute: Attribute) {
+        self.attributes.push(attribute);
+        self.name_indexes.push(None);
+    }
+
+    pub fn write(&self, w: &mut Vec<u8>, constant_pool: &ConstantPool) -> Result<()> {
+        write_count(w, self.attributes.len())?;
+        for (attribute, name_index) in self.attributes.iter().zip(&self.name_indexes) {
+            attribute.write(w, constant_pool, name_index.as_ref())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Deref for Attributes {
+    type Target = [Attribute];
+
+    fn deref(&self) -> &Self::Target {
+        &self.attributes
+    }
+}
+
+impl DerefMut for Attributes {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.attributes
+    }
+}
+
+impl<'a> IntoIterator for &'a Attributes {
+    type Item = &'a Attribute;
+    type IntoIter = std::slice::Iter<'a, Attribute>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.attributes.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Attributes {
+    type Item = &'a mut Attribute;
+    type IntoIter = std::slice::IterMut<'a, Attribute>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.attributes.iter_mut()
+    }
+}
+
+impl From<Vec<Attribute>> for Attributes {
+    fn from(attributes: Vec<Attribute>) -> Self {
+        let name_indexes = vec![None; attributes.len()];
+        Self {
+            attributes,
+            name_indexes,
+        }
+    }
+}
+
+/// Attributes are equal if they have the same contents, wherever their names are stored.
+impl PartialEq for Attributes {
+    fn eq(&self, other: &Self) -> bool {
+        self.attributes == other.attributes
+    }
 }
 
 impl Attribute {
+    /// Parses an attribute, returning it with the index of its name.
     #[instrument(skip_all, name = "attribute")]
-    pub fn new(c: &mut Cursor<&Vec<u8>>, constant_pool: &ConstantPool) -> Result<Attribute> {
+    pub fn new(c: &mut Cursor<&[u8]>, constant_pool: &ConstantPool) -> Result<(Index, Attribute)> {
         let name_index = Index::new(parse_u16(c)?);
-        c.seek_relative(4)?;
+        let length = parse_u32(c)? as usize;
 
         let text = Attribute::get_text(constant_pool, &name_index)?;
         trace!("parsing attribute {text}");
 
-        match text.as_str() {
+        let start = c.position();
+        let attribute = match text.as_str() {
             "Code" => Self::code(c, constant_pool),
             "LineNumberTable" => Self::line_number_table(c),
             "SourceFile" => Self::source_file(c),
             "ConstantValue" => Self::constant_value(c),
-            "RuntimeVisibleAnnotations" => Self::runtime_visible_annotations(c),
+            "RuntimeVisibleAnnotations" => Ok(Self::RuntimeVisibleAnnotations {
+                annotations: Annotation::annotations(c)?,
+            }),
+            "RuntimeInvisibleAnnotations" => Ok(Self::RuntimeInvisibleAnnotations {
+                annotations: Annotation::annotations(c)?,
+            }),
+            "RuntimeVisibleParameterAnnotations" => Ok(Self::RuntimeVisibleParameterAnnotations {
+                parameter_annotations: Annotation::parameter_annotations(c)?,
+            }),
+            "RuntimeInvisibleParameterAnnotations" => {
+                Ok(Self::RuntimeInvisibleParameterAnnotations {
+                    parameter_annotations: Annotation::parameter_annotations(c)?,
+                })
+            }
+            "RuntimeVisibleTypeAnnotations" => Ok(Self::RuntimeVisibleTypeAnnotations {
+                annotations: TypeAnnotation::annotations(c)?,
+            }),
+            "RuntimeInvisibleTypeAnnotations" => Ok(Self::RuntimeInvisibleTypeAnnotations {
+                annotations: TypeAnnotation::annotations(c)?,
+            }),
+            "AnnotationDefault" => Ok(Self::AnnotationDefault {
+                default_value: ElementValue::new(c)?,
+            }),
             "LocalVariableTable" => Self::local_variable_table(c),
             "StackMapTable" => Self::stack_map_table(c),
             "Exceptions" => Self::exceptions(c),
@@ -106,33 +272,233 @@ impl Attribute {
             "InnerClasses" => Self::inner_classes(c),
             "EnclosingMethod" => Self::enclosing_method(c),
             "NestHost" => Self::nest_host(c),
-            i => bail!("unknown attribute {i}"),
+            "Module" => Ok(Self::Module {
+                module: Module::new(c)?,
+            }),
+            "ModulePackages" => Self::module_packages(c),
+            "Record" => Self::record(c, constant_pool),
+            "PermittedSubclasses" => Self::permitted_subclasses(c),
+            "MethodParameters" => Self::method_parameters(c),
+            "SourceDebugExtension" => Ok(Self::SourceDebugExtension {
+                debug_extension: parse_vec(c, length)?,
+            }),
+            "ModuleMainClass" => Ok(Self::ModuleMainClass {
+                main_class_index: Index::new(parse_u16(c)?),
+            }),
+            _ => Ok(Self::Unknown {
+                bytes: parse_vec(c, length)?,
+                name: text.clone(),
+            }),
+        }
+        .with_context(|| format!("attribute {text}"))?;
+
+        let parsed = (c.position() - start) as usize;
+        if parsed != length {
+            return Err(anyhow!(
+                "attribute_length is {length}, but the attribute has {parsed} bytes"
+            ))
+            .with_context(|| format!("attribute {text}"));
         }
+
+        Ok((name_index, attribute))
     }
 
-    fn get_text(constant_pool: &ConstantPool, name_index: &Index) -> Result<String> {
-        constant_pool.utf8(name_index).context(format!(
-            "no constant pool utf8 entry found for {name_index:?}"
-        ))
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Code { .. } => "Code",
+            Self::LineNumberTable { .. } => "LineNumberTable",
+            Self::SourceFile { .. } => "SourceFile",
+            Self::ConstantValue { .. } => "ConstantValue",
+            Self::RuntimeVisibleAnnotations { .. } => "RuntimeVisibleAnnotations",
+            Self::RuntimeInvisibleAnnotations { .. } => "RuntimeInvisibleAnnotations",
+            Self::RuntimeVisibleParameterAnnotations { .. } => "RuntimeVisibleParameterAnnotations",
+            Self::RuntimeInvisibleParameterAnnotations { .. } => {
+                "RuntimeInvisibleParameterAnnotations"
+            }
+            Self::RuntimeVisibleTypeAnnotations { .. } => "RuntimeVisibleTypeAnnotations",
+            Self::RuntimeInvisibleTypeAnnotations { .. } => "RuntimeInvisibleTypeAnnotations",
+            Self::AnnotationDefault { .. } => "AnnotationDefault",
+            Self::LocalVariableTable { .. } => "LocalVariableTable",
+            Self::LocalVariableTypeTable { .. } => "LocalVariableTypeTable",
+            Self::StackMapTable { .. } => "StackMapTable",
+            Self::Exceptions { .. } => "Exceptions",
+            Self::Signature { .. } => "Signature",
+            Self::Deprecated => "Deprecated",
+            Self::NestMembers { .. } => "NestMembers",
+            Self::BootstrapMethods { .. } => "BootstrapMethods",
+            Self::InnerClasses { .. } => "InnerClasses",
+            Self::EnclosingMethod { .. } => "EnclosingMethod",
+            Self::NestHost { .. } => "NestHost",
+            Self::Module { .. } => "Module",
+            Self::ModulePackages { .. } => "ModulePackages",
+            Self::ModuleMainClass { .. } => "ModuleMainClass",
+            Self::Record { .. } => "Record",
+            Self::PermittedSubclasses { .. } => "PermittedSubclasses",
+            Self::MethodParameters { .. } => "MethodParameters",
+            Self::SourceDebugExtension { .. } => "SourceDebugExtension",
+            Self::Unknown { name, .. } => name,
+        }
     }
 
-    pub fn attributes(c: &mut Cursor<&Vec<u8>>, constant_pool: &ConstantPool) -> Result<Vec<Self>> {
-        let attributes_count = parse_u16(c)? as usize;
-        let mut attributes = Vec::with_capacity(attributes_count);
-        for _ in 0..attributes_count {
-            attributes.push(Self::new(c, constant_pool)?);
+    /// Writes the attribute, its name at `name_index` or the first Utf8 entry holding it.
+    pub fn write(
+        &self,
+        w: &mut Vec<u8>,
+        constant_pool: &ConstantPool,
+        name_index: Option<&Index>,
+    ) -> Result<()> {
+        let name = self.name();
+        let name_index = match name_index {
+            Some(index) => index.clone(),
+            None => constant_pool
+                .utf8_index(name)
+                .context(format!("no constant pool utf8 entry found for {name}"))?,
+        };
+
+        let mut body = Vec::new();
+        self.write_body(&mut body, constant_pool)?;
+
+        write_index(w, &name_index)?;
+        write_u32(w, u32::try_from(body.len())?);
+        w.extend(body);
+        Ok(())
+    }
+
+    fn write_body(&self, w: &mut Vec<u8>, constant_pool: &ConstantPool) -> Result<()> {
+        match self {
+            Self::Code {
+                max_stacks,
+                max_locals,
+                code,
+                exceptions,
+                attributes,
+            } => {
+                write_u16(w, *max_stacks);
+                write_u16(w, *max_locals);
+                write_u32(w, u32::try_from(code.len())?);
+                w.extend(code);
+                write_count(w, exceptions.len())?;
+                for exception in exceptions {
+                    exception.write(w);
+                }
+                attributes.write(w, constant_pool)?;
+            }
+            Self::LineNumberTable { table } => {
+                write_count(w, table.len())?;
+                for entry in table {
+                    entry.write(w);
+                }
+            }
+            Self::SourceFile { source_file_index } => write_index(w, source_file_index)?,
+            Self::ConstantValue {
+                constant_value_index,
+            } => write_index(w, constant_value_index)?,
+            Self::RuntimeVisibleAnnotations { annotations }
+            | Self::RuntimeInvisibleAnnotations { annotations } => {
+                Annotation::write_annotations(annotations, w)?
+            }
+            Self::RuntimeVisibleParameterAnnotations {
+                parameter_annotations,
+            }
+            | Self::RuntimeInvisibleParameterAnnotations {
+                parameter_annotations,
+            } => Annotation::write_parameter_annotations(parameter_annotations, w)?,
+            Self::RuntimeVisibleTypeAnnotations { annotations }
+            | Self::RuntimeInvisibleTypeAnnotations { annotations } => {
+                TypeAnnotation::write_annotations(annotations, w)?
+            }
+            Self::AnnotationDefault { default_value } => default_value.write(w)?,
+            Self::LocalVariableTable {
+                local_variable_table,
+            } => {
+                write_count(w, local_variable_table.len())?;
+                for local_variable in local_variable_table {
+                    local_variable.write(w)?;
+                }
+            }
+            Self::LocalVariableTypeTable {
+                local_variable_type_table,
+            } => {
+                write_count(w, local_variable_type_table.len())?;
+                for local_variable_type in local_variable_type_table {
+                    local_variable_type.write(w)?;
+                }
+            }
+            Self::StackMapTable { entries } => {
+                write_count(w, entries.len())?;
+                for entry in entries {
+                    entry.write(w)?;
+                }
+            }
+            Self::Exceptions {
+                exception_index_table: indices,
+            }
+            | Self::NestMembers { classes: indices }
+            | Self::ModulePackages {
+                package_index: indices,
+            }
+            | Self::PermittedSubclasses { classes: indices } => {
+                write_count(w, indices.len())?;
+                for index in indices {
+                    write_index(w, index)?;
+                }
+            }
+            Self::Signature { signature_index } => write_index(w, signature_index)?,
+            Self::Deprecated => {}
+            Self::BootstrapMethods { bootstrap_methods } => {
+                write_count(w, bootstrap_methods.len())?;
+                for bootstrap_method in bootstrap_methods {
+                    bootstrap_method.write(w)?;
+                }
+            }
+            Self::InnerClasses { classes } => {
+                write_count(w, classes.len())?;
+                for class in classes {
+                    class.write(w)?;
+                }
+            }
+            Self::EnclosingMethod {
+                class_index,
+                method_index,
+            } => {
+                write_index(w, class_index)?;
+                write_index(w, method_index)?;
+            }
+            Self::NestHost { host_class_index } => write_index(w, host_class_index)?,
+            Self::Module { module } => module.write(w)?,
+            Self::ModuleMainClass { main_class_index } => write_index(w, main_class_index)?,
+            Self::Record { components } => {
+                write_count(w, components.len())?;
+                for component in components {
+                    component.write(w, constant_pool)?;
+                }
+            }
+            Self::MethodParameters { parameters } => {
+                write_u8(w, u8::try_from(parameters.len())?);
+                for parameter in parameters {
+                    parameter.write(w)?;
+                }
+            }
+            Self::SourceDebugExtension { debug_extension } => w.extend(debug_extension),
+            Self::Unknown { bytes, .. } => w.extend(bytes),
         }
 
-        Ok(attributes)
+        Ok(())
+    }
+
+    fn get_text(constant_pool: &ConstantPool, name_index: &Index) -> Result<String> {
+        constant_pool.utf8(name_index).context(format!(
+            "no constant pool utf8 entry found for {name_index:?}"
+        ))
     }
 
-    fn source_file(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    fn source_file(c: &mut Cursor<&[u8]>) -> Result<Self> {
         Ok(Attribute::SourceFile {
             source_file_index: Index::new(parse_u16(c)?),
         })
     }
 
-    fn line_number_table(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    fn line_number_table(c: &mut Cursor<&[u8]>) -> Result<Self> {
         let table_length = parse_u16(c)? as usize;
         let mut table = Vec::with_capacity(table_length);
         for _ in 0..table_length {
@@ -141,7 +507,7 @@ impl Attribute {
         Ok(Self::LineNumberTable { table })
     }
 
-    fn code(c: &mut Cursor<&Vec<u8>>, constant_pool: &ConstantPool) -> Result<Self> {
+    fn code(c: &mut Cursor<&[u8]>, constant_pool: &ConstantPool) -> Result<Self> {
         let max_stacks = parse_u16(c)?;
         let max_locals = parse_u16(c)?;
 
@@ -153,37 +519,27 @@ impl Attribute {
 
         let exception_table_length = parse_u16(c)?;
         let mut exceptions = Vec::new();
-        for _ in 0..exception_table_length {
-            exceptions.push(Exception::new(c)?);
+        for i in 0..exception_table_length {
+            exceptions
+                .push(Exception::new(c).with_context(|| format!("exception table entry {i}"))?);
         }
 
         Ok(Self::Code {
             max_stacks,
             max_locals,
             code,
-            attributes: Self::attributes(c, constant_pool)?,
+            attributes: Attributes::new(c, constant_pool)?,
             exceptions,
         })
     }
 
-    fn constant_value(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    fn constant_value(c: &mut Cursor<&[u8]>) -> Result<Self> {
         Ok(Self::ConstantValue {
             constant_value_index: Index::new(parse_u16(c)?),
         })
     }
 
-    fn runtime_visible_annotations(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
-        let num_annotations = parse_u16(c)?;
-
-        let mut annotations = Vec::new();
-        for _ in 0..num_annotations {
-            annotations.push(Annotation::new(c)?);
-        }
-
-        Ok(Self::RuntimeVisibleAnnotations { annotations })
-    }
-
-    fn local_variable_table(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    fn local_variable_table(c: &mut Cursor<&[u8]>) -> Result<Self> {
         let local_variable_table_length = parse_u16(c)? as usize;
 
         let mut local_variable_table = Vec::with_capacity(local_variable_table_length);
@@ -196,18 +552,18 @@ impl Attribute {
         })
     }
 
-    fn stack_map_table(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    fn stack_map_table(c: &mut Cursor<&[u8]>) -> Result<Self> {
         let number_of_entries = parse_u16(c)?;
 
         let mut entries = Vec::new();
-        for _ in 0..number_of_entries {
-            entries.push(StackMapFrame::new(c)?);
+        for i in 0..number_of_entries {
+            entries.push(StackMapFrame::new(c).with_context(|| format!("stack map frame {i}"))?);
         }
 
         Ok(Self::StackMapTable { entries })
     }
 
-    fn exceptions(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    fn exceptions(c: &mut Cursor<&[u8]>) -> Result<Self> {
         let number_of_exceptions = parse_u16(c)?;
 
         let mut exception_index_table = Vec::new();
@@ -220,7 +576,7 @@ impl Attribute {
         })
     }
 
-    fn local_variable_type_table(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    fn local_variable_type_table(c: &mut Cursor<&[u8]>) -> Result<Self> {
         let mut local_variable_type_table = Vec::new();
         for _ in 0..parse_u16(c)? {
             local_variable_type_table.push(LocalVariableType::new(c)?);
@@ -231,13 +587,13 @@ impl Attribute {
         })
     }
 
-    fn signature(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    fn signature(c: &mut Cursor<&[u8]>) -> Result<Self> {
         Ok(Self::Signature {
             signature_index: Index::new(parse_u16(c)?),
         })
     }
 
-    fn nest_members(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    fn nest_members(c: &mut Cursor<&[u8]>) -> Result<Self> {
         let number_of_classes = parse_u16(c)? as usize;
         let mut classes = Vec::with_capacity(number_of_classes);
         for _ in 0..number_of_classes {
@@ -247,7 +603,47 @@ impl Attribute {
         Ok(Self::NestMembers { classes })
     }
 
-    fn bootstrap_methods(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    fn module_packages(c: &mut Cursor<&[u8]>) -> Result<Self> {
+        let package_count = parse_u16(c)? as usize;
+        let mut package_index = Vec::with_capacity(package_count);
+        for _ in 0..package_count {
+            package_index.push(Index::new(parse_u16(c)?));
+        }
+
+        Ok(Self::ModulePackages { package_index })
+    }
+
+    fn record(c: &mut Cursor<&[u8]>, constant_pool: &ConstantPool) -> Result<Self> {
+        let components_count = parse_u16(c)? as usize;
+        let mut components = Vec::with_capacity(components_count);
+        for _ in 0..components_count {
+            components.push(RecordComponent::new(c, constant_pool)?);
+        }
+
+        Ok(Self::Record { components })
+    }
+
+    fn permitted_subclasses(c: &mut Cursor<&[u8]>) -> Result<Self> {
+        let number_of_classes = parse_u16(c)? as usize;
+        let mut classes = Vec::with_capacity(number_of_classes);
+        for _ in 0..number_of_classes {
+            classes.push(Index::new(parse_u16(c)?));
+        }
+
+        Ok(Self::PermittedSubclasses { classes })
+    }
+
+    fn method_parameters(c: &mut Cursor<&[u8]>) -> Result<Self> {
+        let parameters_count = parse_u8(c)? as usize;
+        let mut parameters = Vec::with_capacity(parameters_count);
+        for _ in 0..parameters_count {
+            parameters.push(MethodParameter::new(c)?);
+        }
+
+        Ok(Self::MethodParameters { parameters })
+    }
+
+    fn bootstrap_methods(c: &mut Cursor<&[u8]>) -> Result<Self> {
         let num_bootstrap_methods = parse_u16(c)? as usize;
         let mut bootstrap_methods = Vec::with_capacity(num_bootstrap_methods);
         for _ in 0..num_bootstrap_methods {
@@ -257,7 +653,7 @@ impl Attribute {
         Ok(Self::BootstrapMethods { bootstrap_methods })
     }
 
-    fn inner_classes(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    fn inner_classes(c: &mut Cursor<&[u8]>) -> Result<Self> {
         let number_of_classes = parse_u16(c)? as usize;
         let mut classes = Vec::with_capacity(number_of_classes);
         for _ in 0..number_of_classes {
@@ -267,16 +663,107 @@ impl Attribute {
         Ok(Self::InnerClasses { classes })
     }
 
-    fn enclosing_method(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    fn enclosing_method(c: &mut Cursor<&[u8]>) -> Result<Self> {
         Ok(Self::EnclosingMethod {
             class_index: Index::new(parse_u16(c)?),
             method_index: Index::new(parse_u16(c)?),
         })
     }
 
-    fn nest_host(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    fn nest_host(c: &mut Cursor<&[u8]>) -> Result<Self> {
         Ok(Self::NestHost {
             host_class_index: Index::new(parse_u16(c)?),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::parser::constant_pool::ConstantPool;
+
+    use super::{Attribute, Attributes};
+
+    #[test]
+    fn test_unknown_attribute() {
+        let mut cp_bytes = vec![1, 0, 6];
+        cp_bytes.extend(b"Custom");
+        let constant_pool = ConstantPool::new(&mut Cursor::new(&cp_bytes), 2).unwrap();
+
+        let bytes = vec![0, 1, 0, 0, 0, 3, 0xA, 0xB, 0xC, 0xFF];
+        let mut c = Cursor::new(bytes.as_slice());
+        let (_, attribute) = Attribute::new(&mut c, &constant_pool).unwrap();
+
+        assert_eq!(
+            attribute,
+            Attribute::Unknown {
+                name: "Custom".to_string(),
+                bytes: vec![0xA, 0xB, 0xC],
+            }
+        );
+        assert_eq!(c.position(), 9);
+    }
+
+    #[test]
+    fn test_source_debug_extension() {
+        let mut cp_bytes = vec![1, 0, 20];
+        cp_bytes.extend(b"SourceDebugExtension");
+        let constant_pool = ConstantPool::new(&mut Cursor::new(&cp_bytes), 2).unwrap();
+
+        let mut bytes = vec![0, 1, 0, 0, 0, 4];
+        bytes.extend(b"SMAP");
+        let (_, attribute) = Attribute::new(&mut Cursor::new(&bytes), &constant_pool).unwrap();
+
+        assert_eq!(
+            attribute,
+            Attribute::SourceDebugExtension {
+                debug_extension: b"SMAP".to_vec(),
+            }
+        );
+
+        // the length is checked against the bytes left before allocating
+        bytes[2..6].copy_from_slice(&u32::MAX.to_be_bytes());
+        let err = Attribute::new(&mut Cursor::new(&bytes), &constant_pool).unwrap_err();
+        let err = err.root_cause().downcast_ref::<std::io::Error>().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_huge_length() {
+        let mut cp_bytes = vec![1, 0, 6];
+        cp_bytes.extend(b"Custom");
+        let constant_pool = ConstantPool::new(&mut Cursor::new(&cp_bytes), 2).unwrap();
+
+        let mut bytes = vec![0, 1];
+        bytes.extend(u32::MAX.to_be_bytes());
+        bytes.extend(b"SMAP");
+        let err = Attribute::new(&mut Cursor::new(&bytes), &constant_pool).unwrap_err();
+        let err = err.root_cause().downcast_ref::<std::io::Error>().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_duplicate_name() {
+        // two Utf8 entries holding "Deprecated", the attributes use the second one
+        let mut cp_bytes = vec![1, 0, 10];
+        cp_bytes.extend(b"Deprecated");
+        cp_bytes.extend([1, 0, 10]);
+        cp_bytes.extend(b"Deprecated");
+        let constant_pool = ConstantPool::new(&mut Cursor::new(&cp_bytes), 3).unwrap();
+
+        let bytes = vec![0, 2, 0, 2, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0];
+        let mut attributes = Attributes::new(&mut Cursor::new(&bytes), &constant_pool).unwrap();
+
+        let mut written = Vec::new();
+        attributes.write(&mut written, &constant_pool).unwrap();
+        assert_eq!(written, bytes);
+
+        // attributes added later use the first entry
+        attributes.push(Attribute::Deprecated);
+        let mut written = Vec::new();
+        attributes.write(&mut written, &constant_pool).unwrap();
+        assert_eq!(written[..2], [0, 3]);
+        assert_eq!(written[14..], [0, 1, 0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,542 @@
+use anyhow::{bail, Context, Result};
+use std::io::Cursor;
+
+use crate::{
+    parser::{
+        constant_pool::{ConstantPool, ConstantPoolItem, Index},
+        parse_u16, write_count, write_index, write_u16,
+    },
+    ClassIdentifier,
+};
+
+use super::Attribute;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Module {
+    pub module_name_index: Index,
+    pub module_flags: Vec<ModuleFlag>,
+    pub module_version_index: Index,
+    pub requires: Vec<Requires>,
+    pub exports: Vec<Exports>,
+    pub opens: Vec<Opens>,
+    pub uses_index: Vec<Index>,
+    pub provides: Vec<Provides>,
+}
+
+impl Module {
+    pub fn new(c: &mut Cursor<&[u8]>) -> Result<Self> {
+        let module_name_index = Index::new(parse_u16(c)?);
+        let module_flags = ModuleFlag::flags(parse_u16(c)?);
+        let module_version_index = Index::new(parse_u16(c)?);
+
+        let requires_count = parse_u16(c)? as usize;
+        let mut requires = Vec::with_capacity(requires_count);
+        for _ in 0..requires_count {
+            requires.push(Requires::new(c)?);
+        }
+
+        let exports_count = parse_u16(c)? as usize;
+        let mut exports = Vec::with_capacity(exports_count);
+        for _ in 0..exports_count {
+            exports.push(Exports::new(c)?);
+        }
+
+        let opens_count = parse_u16(c)? as usize;
+        let mut opens = Vec::with_capacity(opens_count);
+        for _ in 0..opens_count {
+            opens.push(Opens::new(c)?);
+        }
+
+        let uses_index = parse_indices(c)?;
+
+        let provides_count = parse_u16(c)? as usize;
+        let mut provides = Vec::with_capacity(provides_count);
+        for _ in 0..provides_count {
+            provides.push(Provides::new(c)?);
+        }
+
+        Ok(Self {
+            module_name_index,
+            module_flags,
+            module_version_index,
+            requires,
+            exports,
+            opens,
+            uses_index,
+            provides,
+        })
+    }
+
+    pub fn write(&self, w: &mut Vec<u8>) -> Result<()> {
+        write_index(w, &self.module_name_index)?;
+        write_u16(w, ModuleFlag::bits(&self.module_flags));
+        write_index(w, &self.module_version_index)?;
+
+        write_count(w, self.requires.len())?;
+        for requires in &self.requires {
+            write_index(w, &requires.requires_index)?;
+            write_u16(w, RequiresFlag::bits(&requires.requires_flags));
+            write_index(w, &requires.requires_version_index)?;
+        }
+
+        write_count(w, self.exports.len())?;
+        for exports in &self.exports {
+            write_index(w, &exports.exports_index)?;
+            write_u16(w, ExportsFlag::bits(&exports.exports_flags));
+            write_indices(w, &exports.exports_to_index)?;
+        }
+
+        write_count(w, self.opens.len())?;
+        for opens in &self.opens {
+            write_index(w, &opens.opens_index)?;
+            write_u16(w, ExportsFlag::bits(&opens.opens_flags));
+            write_indices(w, &opens.opens_to_index)?;
+        }
+
+        write_indices(w, &self.uses_index)?;
+
+        write_count(w, self.provides.len())?;
+        for provides in &self.provides {
+            write_index(w, &provides.provides_index)?;
+            write_indices(w, &provides.provides_with_index)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Requires {
+    pub requires_index: Index,
+    pub requires_flags: Vec<RequiresFlag>,
+    pub requires_version_index: Index,
+}
+
+impl Requires {
+    fn new(c: &mut Cursor<&[u8]>) -> Result<Self> {
+        Ok(Self {
+            requires_index: Index::new(parse_u16(c)?),
+            requires_flags: RequiresFlag::flags(parse_u16(c)?),
+            requires_version_index: Index::new(parse_u16(c)?),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Exports {
+    pub exports_index: Index,
+    pub exports_flags: Vec<ExportsFlag>,
+    pub exports_to_index: Vec<Index>,
+}
+
+impl Exports {
+    fn new(c: &mut Cursor<&[u8]>) -> Result<Self> {
+        Ok(Self {
+            exports_index: Index::new(parse_u16(c)?),
+            exports_flags: ExportsFlag::flags(parse_u16(c)?),
+            exports_to_index: parse_indices(c)?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Opens {
+    pub opens_index: Index,
+    pub opens_flags: Vec<ExportsFlag>,
+    pub opens_to_index: Vec<Index>,
+}
+
+impl Opens {
+    fn new(c: &mut Cursor<&[u8]>) -> Result<Self> {
+        Ok(Self {
+            opens_index: Index::new(parse_u16(c)?),
+            opens_flags: ExportsFlag::flags(parse_u16(c)?),
+            opens_to_index: parse_indices(c)?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Provides {
+    pub provides_index: Index,
+    pub provides_with_index: Vec<Index>,
+}
+
+impl Provides {
+    fn new(c: &mut Cursor<&[u8]>) -> Result<Self> {
+        Ok(Self {
+            provides_index: Index::new(parse_u16(c)?),
+            provides_with_index: parse_indices(c)?,
+        })
+    }
+}
+
+fn parse_indices(c: &mut Cursor<&[u8]>) -> Result<Vec<Index>> {
+    let count = parse_u16(c)? as usize;
+    let mut indices = Vec::with_capacity(count);
+    for _ in 0..count {
+        indices.push(Index::new(parse_u16(c)?));
+    }
+
+    Ok(indices)
+}
+
+fn write_indices(w: &mut Vec<u8>, indices: &[Index]) -> Result<()> {
+    write_count(w, indices.len())?;
+    for index in indices {
+        write_index(w, index)?;
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ModuleFlag {
+    Open,
+    Synthetic,
+    Mandated,
+}
+
+impl ModuleFlag {
+    fn mask(&self) -> u16 {
+        match self {
+            Self::Open => 0x0020,
+            Self::Synthetic => 0x1000,
+            Self::Mandated => 0x8000,
+        }
+    }
+
+    fn bits(flags: &[Self]) -> u16 {
+        flags.iter().fold(0, |bits, flag| bits | flag.mask())
+    }
+
+    fn flags(val: u16) -> Vec<Self> {
+        let mut flags = Vec::new();
+
+        if (val & 0x0020) != 0 {
+            flags.push(Self::Open);
+        }
+
+        if (val & 0x1000) != 0 {
+            flags.push(Self::Synthetic);
+        }
+
+        if (val & 0x8000) != 0 {
+            flags.push(Self::Mandated);
+        }
+
+        flags
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RequiresFlag {
+    Transitive,
+    StaticPhase,
+    Synthetic,
+    Mandated,
+}
+
+impl RequiresFlag {
+    fn mask(&self) -> u16 {
+        match self {
+            Self::Transitive => 0x0020,
+            Self::StaticPhase => 0x0040,
+            Self::Synthetic => 0x1000,
+            Self::Mandated => 0x8000,
+        }
+    }
+
+    fn bits(flags: &[Self]) -> u16 {
+        flags.iter().fold(0, |bits, flag| bits | flag.mask())
+    }
+
+    fn flags(val: u16) -> Vec<Self> {
+        let mut flags = Vec::new();
+
+        if (val & 0x0020) != 0 {
+            flags.push(Self::Transitive);
+        }
+
+        if (val & 0x0040) != 0 {
+            flags.push(Self::StaticPhase);
+        }
+
+        if (val & 0x1000) != 0 {
+            flags.push(Self::Synthetic);
+        }
+
+        if (val & 0x8000) != 0 {
+            flags.push(Self::Mandated);
+        }
+
+        flags
+    }
+}
+
+/// Flags of an `exports` or `opens` directive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExportsFlag {
+    Synthetic,
+    Mandated,
+}
+
+impl ExportsFlag {
+    fn mask(&self) -> u16 {
+        match self {
+            Self::Synthetic => 0x1000,
+            Self::Mandated => 0x8000,
+        }
+    }
+
+    fn bits(flags: &[Self]) -> u16 {
+        flags.iter().fold(0, |bits, flag| bits | flag.mask())
+    }
+
+    fn flags(val: u16) -> Vec<Self> {
+        let mut flags = Vec::new();
+
+        if (val & 0x1000) != 0 {
+            flags.push(Self::Synthetic);
+        }
+
+        if (val & 0x8000) != 0 {
+            flags.push(Self::Mandated);
+        }
+
+        flags
+    }
+}
+
+/// A module declaration with all constant pool references resolved, built from the
+/// `Module`, `ModulePackages` and `ModuleMainClass` attributes of a `module-info.class`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModuleDescriptor {
+    pub name: String,
+    pub flags: Vec<ModuleFlag>,
+    pub version: Option<String>,
+    pub requires: Vec<RequiresDirective>,
+    pub exports: Vec<ExportsDirective>,
+    pub opens: Vec<ExportsDirective>,
+    pub uses: Vec<ClassIdentifier>,
+    pub provides: Vec<ProvidesDirective>,
+    /// Packages in binary form, e.g. `java/lang`.
+    pub packages: Vec<String>,
+    pub main_class: Option<ClassIdentifier>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequiresDirective {
+    pub module: String,
+    pub flags: Vec<RequiresFlag>,
+    pub version: Option<String>,
+}
+
+/// An `exports` or `opens` directive; `to` is empty for unqualified directives.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportsDirective {
+    pub package: String,
+    pub flags: Vec<ExportsFlag>,
+    pub to: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProvidesDirective {
+    pub service: ClassIdentifier,
+    pub with: Vec<ClassIdentifier>,
+}
+
+impl ModuleDescriptor {
+    /// Returns `None` if there is no `Module` attribute, i.e. the class is not a module declaration.
+    pub fn new(attributes: &[Attribute], constant_pool: &ConstantPool) -> Result<Option<Self>> {
+        let mut module = None;
+        let mut packages = Vec::new();
+        let mut main_class = None;
+
+        for attribute in attributes {
+            match attribute {
+                Attribute::Module { module: m } => module = Some(m),
+                Attribute::ModulePackages { package_index } => {
+                    packages = package_index
+                        .iter()
+                        .map(|i| package(constant_pool, i))
+                        .collect::<Result<_>>()?
+                }
+                Attribute::ModuleMainClass { main_class_index } => {
+                    main_class = Some(class(constant_pool, main_class_index)?)
+                }
+                _ => {}
+            }
+        }
+
+        let module = match module {
+            Some(module) => module,
+            None => return Ok(None),
+        };
+
+        let mut requires = Vec::with_capacity(module.requires.len());
+        for r in &module.requires {
+            requires.push(RequiresDirective {
+                module: module_name(constant_pool, &r.requires_index)?,
+                flags: r.requires_flags.clone(),
+                version: optional_utf8(constant_pool, &r.requires_version_index)?,
+            });
+        }
+
+        let mut exports = Vec::with_capacity(module.exports.len());
+        for e in &module.exports {
+            exports.push(ExportsDirective {
+                package: package(constant_pool, &e.exports_index)?,
+                flags: e.exports_flags.clone(),
+                to: module_names(constant_pool, &e.exports_to_index)?,
+            });
+        }
+
+        let mut opens = Vec::with_capacity(module.opens.len());
+        for o in &module.opens {
+            opens.push(ExportsDirective {
+                package: package(constant_pool, &o.opens_index)?,
+                flags: o.opens_flags.clone(),
+                to: module_names(constant_pool, &o.opens_to_index)?,
+            });
+        }
+
+        let mut provides = Vec::with_capacity(module.provides.len());
+        for p in &module.provides {
+            provides.push(ProvidesDirective {
+                service: class(constant_pool, &p.provides_index)?,
+                with: p
+                    .provides_with_index
+                    .iter()
+                    .map(|i| class(constant_pool, i))
+                    .collect::<Result<_>>()?,
+            });
+        }
+
+        Ok(Some(Self {
+            name: module_name(constant_pool, &module.module_name_index)?,
+            flags: module.module_flags.clone(),
+            version: optional_utf8(constant_pool, &module.module_version_index)?,
+            requires,
+            exports,
+            opens,
+            uses: module
+                .uses_index
+                .iter()
+                .map(|i| class(constant_pool, i))
+                .collect::<Result<_>>()?,
+            provides,
+            packages,
+            main_class,
+        }))
+    }
+}
+
+fn module_name(constant_pool: &ConstantPool, index: &Index) -> Result<String> {
+    match constant_pool.resolve(index) {
+        Some(ConstantPoolItem::Module { name }) => Ok(name),
+        item => bail!("expected module constant at {index:?}, got {item:?}"),
+    }
+}
+
+fn module_names(constant_pool: &ConstantPool, indices: &[Index]) -> Result<Vec<String>> {
+    indices
+        .iter()
+        .map(|i| module_name(constant_pool, i))
+        .collect()
+}
+
+fn package(constant_pool: &ConstantPool, index: &Index) -> Result<String> {
+    match constant_pool.resolve(index) {
+        Some(ConstantPoolItem::Package { name }) => Ok(name),
+        item => bail!("expected package constant at {index:?}, got {item:?}"),
+    }
+}
+
+fn class(constant_pool: &ConstantPool, index: &Index) -> Result<ClassIdentifier> {
+    constant_pool
+        .class_identifier(index)
+        .context(format!("no class entry at {index:?} in constant pool"))
+}
+
+fn optional_utf8(constant_pool: &ConstantPool, index: &Index) -> Result<Option<String>> {
+    if index.is_zero() {
+        return Ok(None);
+    }
+
+    Ok(Some(constant_pool.utf8(index).context(format!(
+        "no constant pool utf8 entry found for {index:?}"
+    ))?))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::class::ClassFile, ClassIdentifier};
+
+    use super::{ExportsDirective, ProvidesDirective, RequiresDirective, RequiresFlag};
+
+    #[test]
+    fn test_module_descriptor() {
+        let data = std::fs::read("testdata/modular/module-info.class").unwrap();
+        let class_file = ClassFile::new(
+            &data,
+            ClassIdentifier::from_utf8("module-info".to_string()).unwrap(),
+        )
+        .unwrap();
+        let module = class_file.module().unwrap().unwrap();
+
+        assert_eq!(module.name, "com.example");
+        assert_eq!(module.version.as_deref(), Some("1.0"));
+        assert_eq!(module.requires.len(), 3);
+        assert_eq!(
+            module.requires[1],
+            RequiresDirective {
+                module: "java.logging".to_string(),
+                flags: vec![RequiresFlag::Transitive],
+                version: Some("17.0.15".to_string()),
+            }
+        );
+        assert_eq!(module.requires[2].flags, vec![RequiresFlag::StaticPhase]);
+        assert_eq!(
+            module.exports[1],
+            ExportsDirective {
+                package: "com/example/internal".to_string(),
+                flags: vec![],
+                to: vec!["java.base".to_string()],
+            }
+        );
+        assert_eq!(module.opens[0].package, "com/example/internal");
+        assert!(module.opens[0].to.is_empty());
+        assert_eq!(
+            module.uses,
+            vec![ClassIdentifier::from(
+                "java.lang".to_string(),
+                "Runnable".to_string()
+            )]
+        );
+        assert_eq!(
+            module.provides,
+            vec![ProvidesDirective {
+                service: ClassIdentifier::from("java.lang".to_string(), "Runnable".to_string()),
+                with: vec![ClassIdentifier::from(
+                    "com.example".to_string(),
+                    "Main".to_string()
+                )],
+            }]
+        );
+        assert_eq!(
+            module.packages,
+            vec![
+                "com/example".to_string(),
+                "com/example/internal".to_string()
+            ]
+        );
+        assert_eq!(
+            module.main_class,
+            Some(ClassIdentifier::from(
+                "com.example".to_string(),
+                "Main".to_string()
+            ))
+        );
+    }
+}
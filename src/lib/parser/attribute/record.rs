@@ -0,0 +1,56 @@
+use anyhow::{Context, Result};
+use std::io::Cursor;
+
+use crate::parser::{
+    constant_pool::{ConstantPool, Index},
+    descriptor::FieldType,
+    parse_u16, write_index,
+};
+
+use super::Attributes;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordComponent {
+    pub name_index: Index,
+    pub descriptor_index: Index,
+    pub attributes: Attributes,
+}
+
+impl RecordComponent {
+    pub fn new(c: &mut Cursor<&[u8]>, constant_pool: &ConstantPool) -> Result<Self> {
+        Ok(Self {
+            name_index: Index::new(parse_u16(c)?),
+            descriptor_index: Index::new(parse_u16(c)?),
+            attributes: Attributes::new(c, constant_pool)?,
+        })
+    }
+
+    pub fn write(&self, w: &mut Vec<u8>, constant_pool: &ConstantPool) -> Result<()> {
+        write_index(w, &self.name_index)?;
+        write_index(w, &self.descriptor_index)?;
+        self.attributes.write(w, constant_pool)
+    }
+
+    pub fn resolve(&self, constant_pool: &ConstantPool) -> Result<RecordComponentItem> {
+        Ok(RecordComponentItem {
+            name: constant_pool
+                .utf8(&self.name_index)
+                .context(format!("no utf8 entry at {:?}", self.name_index))?,
+            descriptor: FieldType::new(
+                &constant_pool
+                    .utf8(&self.descriptor_index)
+                    .context(format!("no utf8 entry at {:?}", self.descriptor_index))?,
+            )?,
+            attributes: self.attributes.clone(),
+        })
+    }
+}
+
+/// A record component with its name and descriptor resolved. The attributes may carry
+/// `Signature` and annotation attributes of the component.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordComponentItem {
+    pub name: String,
+    pub descriptor: FieldType,
+    pub attributes: Attributes,
+}
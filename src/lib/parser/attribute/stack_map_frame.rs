@@ -1,7 +1,9 @@
 use anyhow::{bail, Result};
 use std::io::Cursor;
 
-use crate::parser::{constant_pool::Index, parse_u16, parse_u8};
+use crate::parser::{
+    constant_pool::Index, parse_u16, parse_u8, write_count, write_index, write_u16, write_u8,
+};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum StackMapFrame {
@@ -17,6 +19,8 @@ pub enum StackMapFrame {
         verification_type: VerificationType,
     },
     Chop {
+        /// Number of trailing locals that are absent, between 1 and 3.
+        k: u8,
         offset_delta: u16,
     },
     SameExtended {
@@ -34,13 +38,13 @@ pub enum StackMapFrame {
 }
 
 impl StackMapFrame {
-    pub fn new(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    pub fn new(c: &mut Cursor<&[u8]>) -> Result<Self> {
         let tag = parse_u8(c)?;
         match tag {
             0..=63 => Ok(Self::SameFrame { offset_delta: tag }),
             64..=127 => Self::same_locals(c, tag),
             247 => Self::same_locals_extended(c),
-            248..=250 => Self::chop(c),
+            248..=250 => Self::chop(c, tag),
             251 => Self::same_extended(c),
             252..=254 => Self::append(c, tag),
             255 => Self::full(c),
@@ -48,26 +52,84 @@ impl StackMapFrame {
         }
     }
 
-    fn same_locals(c: &mut Cursor<&Vec<u8>>, tag: u8) -> Result<StackMapFrame> {
+    pub fn write(&self, w: &mut Vec<u8>) -> Result<()> {
+        match self {
+            Self::SameFrame { offset_delta } => write_u8(w, *offset_delta),
+            Self::SameLocals {
+                offset_delta,
+                verification_type,
+            } => {
+                write_u8(w, offset_delta + 64);
+                verification_type.write(w)?;
+            }
+            Self::SameLocalsExtended {
+                offset_delta,
+                verification_type,
+            } => {
+                write_u8(w, 247);
+                write_u16(w, *offset_delta);
+                verification_type.write(w)?;
+            }
+            Self::Chop { k, offset_delta } => {
+                write_u8(w, 251 - k);
+                write_u16(w, *offset_delta);
+            }
+            Self::SameExtended { offset_delta } => {
+                write_u8(w, 251);
+                write_u16(w, *offset_delta);
+            }
+            Self::Append {
+                offset_delta,
+                locals,
+            } => {
+                write_u8(w, 251 + u8::try_from(locals.len())?);
+                write_u16(w, *offset_delta);
+                for local in locals {
+                    local.write(w)?;
+                }
+            }
+            Self::Full {
+                offset_delta,
+                locals,
+                stack_items,
+            } => {
+                write_u8(w, 255);
+                write_u16(w, *offset_delta);
+                write_count(w, locals.len())?;
+                for local in locals {
+                    local.write(w)?;
+                }
+                write_count(w, stack_items.len())?;
+                for stack_item in stack_items {
+                    stack_item.write(w)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn same_locals(c: &mut Cursor<&[u8]>, tag: u8) -> Result<StackMapFrame> {
         Ok(Self::SameLocals {
             offset_delta: tag - 64,
             verification_type: VerificationType::new(c)?,
         })
     }
-    fn same_locals_extended(c: &mut Cursor<&Vec<u8>>) -> Result<StackMapFrame> {
+    fn same_locals_extended(c: &mut Cursor<&[u8]>) -> Result<StackMapFrame> {
         Ok(Self::SameLocalsExtended {
             offset_delta: parse_u16(c)?,
             verification_type: VerificationType::new(c)?,
         })
     }
 
-    fn chop(c: &mut Cursor<&Vec<u8>>) -> Result<StackMapFrame> {
+    fn chop(c: &mut Cursor<&[u8]>, tag: u8) -> Result<StackMapFrame> {
         Ok(Self::Chop {
+            k: 251 - tag,
             offset_delta: parse_u16(c)?,
         })
     }
 
-    fn append(c: &mut Cursor<&Vec<u8>>, tag: u8) -> Result<StackMapFrame> {
+    fn append(c: &mut Cursor<&[u8]>, tag: u8) -> Result<StackMapFrame> {
         let offset_delta = parse_u16(c)?;
         let mut locals = Vec::new();
         for _ in 0..tag - 251 {
@@ -80,7 +142,7 @@ impl StackMapFrame {
         })
     }
 
-    fn full(c: &mut Cursor<&Vec<u8>>) -> Result<StackMapFrame> {
+    fn full(c: &mut Cursor<&[u8]>) -> Result<StackMapFrame> {
         let offset_delta = parse_u16(c)?;
 
         let number_of_locals = parse_u16(c)? as usize;
@@ -102,7 +164,7 @@ impl StackMapFrame {
         })
     }
 
-    fn same_extended(c: &mut Cursor<&Vec<u8>>) -> Result<StackMapFrame> {
+    fn same_extended(c: &mut Cursor<&[u8]>) -> Result<StackMapFrame> {
         Ok(Self::SameExtended {
             offset_delta: parse_u16(c)?,
         })
@@ -113,21 +175,55 @@ impl StackMapFrame {
 pub enum VerificationType {
     Top,
     Integer,
+    Float,
+    Double,
     Long,
-    ConstantClass { cpoll_index: Index },
+    Null,
+    UninitializedThis,
+    Object { cpool_index: Index },
+    Uninitialized { offset: u16 },
 }
 
 impl VerificationType {
-    fn new(c: &mut Cursor<&Vec<u8>>) -> Result<Self> {
+    fn new(c: &mut Cursor<&[u8]>) -> Result<Self> {
         let tag = parse_u8(c)?;
         Ok(match tag {
             0 => VerificationType::Top,
             1 => VerificationType::Integer,
+            2 => VerificationType::Float,
+            3 => VerificationType::Double,
             4 => VerificationType::Long,
-            7 => VerificationType::ConstantClass {
-                cpoll_index: Index::new(parse_u16(c)?),
+            5 => VerificationType::Null,
+            6 => VerificationType::UninitializedThis,
+            7 => VerificationType::Object {
+                cpool_index: Index::new(parse_u16(c)?),
+            },
+            8 => VerificationType::Uninitialized {
+                offset: parse_u16(c)?,
             },
             _ => bail!("invalid verification type tag {tag}"),
         })
     }
+
+    fn write(&self, w: &mut Vec<u8>) -> Result<()> {
+        match self {
+            Self::Top => write_u8(w, 0),
+            Self::Integer => write_u8(w, 1),
+            Self::Float => write_u8(w, 2),
+            Self::Double => write_u8(w, 3),
+            Self::Long => write_u8(w, 4),
+            Self::Null => write_u8(w, 5),
+            Self::UninitializedThis => write_u8(w, 6),
+            Self::Object { cpool_index } => {
+                write_u8(w, 7);
+                write_index(w, cpool_index)?;
+            }
+            Self::Uninitialized { offset } => {
+                write_u8(w, 8);
+                write_u16(w, *offset);
+            }
+        }
+
+        Ok(())
+    }
 }
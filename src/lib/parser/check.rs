@@ -0,0 +1,1008 @@
+use anyhow::{Error, Result};
+use std::collections::HashSet;
+
+use super::{
+    attribute::Attribute,
+    class::{AccessFlag, ClassFile},
+    constant_pool::{ConstantPool, ConstantPoolInfo, Index, MethodHandleKind},
+    descriptor::{FieldType, MethodDescriptor, ReturnDescriptor},
+    error::ClassFormatError,
+    field::{Field, FieldFlag},
+    instruction::Instruction,
+    method::{Method, MethodFlag},
+};
+
+const MAX_ARRAY_DIMENSIONS: usize = 255;
+const MAX_PARAMETER_SLOTS: usize = 255;
+
+impl ClassFile {
+    /// Performs the format checks of JVMS 4.8 that [`ClassFile::new`] leaves out: constant
+    /// pool references point at entries of the right kind, access flags are legal, names and
+    /// descriptors are well formed and no member is declared twice.
+    pub fn check(&self) -> Result<(), ClassFormatError> {
+        self.check_format()
+            .map_err(|err| match err.downcast::<ClassFormatError>() {
+                Ok(err) => err,
+                Err(err) => ClassFormatError::Malformed {
+                    class: self.this_class.clone(),
+                    offset: None,
+                    context: "class file".to_string(),
+                    cause: format!("{err:#}"),
+                },
+            })
+    }
+
+    fn check_format(&self) -> Result<()> {
+        let checker = Checker {
+            class_file: self,
+            cp: &self.constant_pool,
+        };
+
+        checker.constant_pool()?;
+        checker.class()?;
+
+        let mut fields = HashSet::new();
+        for (i, field) in self.fields.iter().enumerate() {
+            let location = format!("field {i}");
+            let (name, descriptor) = checker.field(field, &location)?;
+            if !fields.insert((name.clone(), descriptor.clone())) {
+                return Err(checker.error(
+                    &format!("{location} ({name})"),
+                    format!("duplicate field {name} {descriptor}"),
+                ));
+            }
+        }
+
+        let mut methods = HashSet::new();
+        for (i, method) in self.methods.iter().enumerate() {
+            let location = format!("method {i}");
+            let (name, descriptor) = checker.method(method, &location)?;
+            if !methods.insert((name.clone(), descriptor.clone())) {
+                return Err(checker.error(
+                    &format!("{location} ({name})"),
+                    format!("duplicate method {name}{descriptor}"),
+                ));
+            }
+        }
+
+        checker.attributes(&self.attributes, "class")
+    }
+}
+
+struct Checker<'a> {
+    class_file: &'a ClassFile,
+    cp: &'a ConstantPool,
+}
+
+impl<'a> Checker<'a> {
+    fn error(&self, location: &str, message: String) -> Error {
+        ClassFormatError::Malformed {
+            class: self.class_file.this_class.clone(),
+            offset: None,
+            context: location.to_string(),
+            cause: message,
+        }
+        .into()
+    }
+
+    fn major(&self) -> u16 {
+        self.class_file.version.major
+    }
+
+    fn is_interface(&self) -> bool {
+        self.class_file
+            .access_flags
+            .contains(&AccessFlag::Interface)
+    }
+
+    fn is_module(&self) -> bool {
+        self.class_file.access_flags.contains(&AccessFlag::Module)
+    }
+
+    fn entry(&self, index: &Index, location: &str) -> Result<&'a ConstantPoolInfo> {
+        match self.cp.get(index) {
+            Some(ConstantPoolInfo::Reserved) | None => Err(self.error(
+                location,
+                format!("#{} is not a valid constant pool index", index.value()),
+            )),
+            Some(info) => Ok(info),
+        }
+    }
+
+    fn utf8(&self, index: &Index, location: &str) -> Result<String> {
+        match self.entry(index, location)? {
+            ConstantPoolInfo::Utf { text } => Ok(text.to_string()),
+            info => Err(self.unexpected(index, "Utf8", info, location)),
+        }
+    }
+
+    fn class_name(&self, index: &Index, location: &str) -> Result<String> {
+        match self.entry(index, location)? {
+            ConstantPoolInfo::ClassInfo { name_index } => self.utf8(name_index, location),
+            info => Err(self.unexpected(index, "Class", info, location)),
+        }
+    }
+
+    /// A class that may be extended or implemented, which excludes array types.
+    fn non_array_class(&self, index: &Index, location: &str) -> Result<String> {
+        let name = self.class_name(index, location)?;
+        if name.starts_with('[') {
+            return Err(self.error(location, format!("{name} is an array type")));
+        }
+
+        Ok(name)
+    }
+
+    fn name_and_type(&self, index: &Index, location: &str) -> Result<(String, String)> {
+        match self.entry(index, location)? {
+            ConstantPoolInfo::NameAndType {
+                name_index,
+                descriptor_index,
+            } => Ok((
+                self.utf8(name_index, location)?,
+                self.utf8(descriptor_index, location)?,
+            )),
+            info => Err(self.unexpected(index, "NameAndType", info, location)),
+        }
+    }
+
+    fn unexpected(
+        &self,
+        index: &Index,
+        expected: &str,
+        info: &ConstantPoolInfo,
+        location: &str,
+    ) -> Error {
+        self.error(
+            location,
+            format!(
+                "#{} must be a {expected} entry, is {}",
+                index.value(),
+                info.tag_name()
+            ),
+        )
+    }
+
+    fn constant_pool(&self) -> Result<()> {
+        for (index, info) in self.cp.iter() {
+            let location = format!("constant pool entry #{}", index.value());
+            self.constant_pool_entry(info, &location)?;
+        }
+
+        Ok(())
+    }
+
+    fn constant_pool_entry(&self, info: &ConstantPoolInfo, location: &str) -> Result<()> {
+        let since = match info {
+            ConstantPoolInfo::MethodHandle { .. }
+            | ConstantPoolInfo::MethodType { .. }
+            | ConstantPoolInfo::InvokeDynamic { .. } => 51,
+            ConstantPoolInfo::Module { .. } | ConstantPoolInfo::Package { .. } => 53,
+            ConstantPoolInfo::Dynamic { .. } => 55,
+            _ => 45,
+        };
+        if self.major() < since {
+            return Err(self.error(
+                location,
+                format!(
+                    "{} entries require class file version {since}",
+                    info.tag_name()
+                ),
+            ));
+        }
+
+        match info {
+            ConstantPoolInfo::FieldRef {
+                class_index,
+                name_and_type_index,
+            } => {
+                self.class_name(class_index, location)?;
+                let (name, descriptor) = self.name_and_type(name_and_type_index, location)?;
+                self.unqualified_name(&name, location)?;
+                self.field_descriptor(&descriptor, location)?;
+            }
+            ConstantPoolInfo::MethodRef {
+                class_index,
+                name_and_type_index,
+            }
+            | ConstantPoolInfo::InterfaceMethodRef {
+                class_index,
+                name_and_type_index,
+            } => {
+                self.class_name(class_index, location)?;
+                let (name, descriptor) = self.name_and_type(name_and_type_index, location)?;
+                let descriptor = self.method_descriptor(&descriptor, location)?;
+                if name.starts_with('<') {
+                    let is_method_ref = matches!(info, ConstantPoolInfo::MethodRef { .. });
+                    if name != "<init>" || !is_method_ref {
+                        return Err(self.error(location, format!("cannot reference {name}")));
+                    }
+                    if descriptor.return_descriptor != ReturnDescriptor::Void {
+                        return Err(self.error(location, "<init> must return void".to_string()));
+                    }
+                } else {
+                    self.method_name(&name, location)?;
+                }
+            }
+            ConstantPoolInfo::String { string_index } => {
+                self.utf8(string_index, location)?;
+            }
+            ConstantPoolInfo::ClassInfo { name_index } => {
+                let name = self.utf8(name_index, location)?;
+                self.class_or_array_name(&name, location)?;
+            }
+            ConstantPoolInfo::NameAndType {
+                name_index,
+                descriptor_index,
+            } => {
+                self.utf8(name_index, location)?;
+                self.utf8(descriptor_index, location)?;
+            }
+            ConstantPoolInfo::InvokeDynamic {
+                bootstrap_method_attr_index,
+                name_and_type_index,
+            }
+            | ConstantPoolInfo::Dynamic {
+                bootstrap_method_attr_index,
+                name_and_type_index,
+            } => {
+                let (name, descriptor) = self.name_and_type(name_and_type_index, location)?;
+                self.method_name(&name, location)?;
+                if matches!(info, ConstantPoolInfo::Dynamic { .. }) {
+                    self.field_descriptor(&descriptor, location)?;
+                } else {
+                    self.method_descriptor(&descriptor, location)?;
+                }
+
+                let bootstrap_methods = self.bootstrap_method_count();
+                if *bootstrap_method_attr_index as usize >= bootstrap_methods {
+                    return Err(self.error(
+                        location,
+                        format!(
+                            "bootstrap method {bootstrap_method_attr_index} does not exist, the class has {bootstrap_methods}"
+                        ),
+                    ));
+                }
+            }
+            ConstantPoolInfo::MethodHandle {
+                reference_kind,
+                reference_index,
+            } => self.method_handle(*reference_kind, reference_index, location)?,
+            ConstantPoolInfo::MethodType { descriptor_index } => {
+                let descriptor = self.utf8(descriptor_index, location)?;
+                self.method_descriptor(&descriptor, location)?;
+            }
+            ConstantPoolInfo::Module { name_index } | ConstantPoolInfo::Package { name_index } => {
+                if !self.is_module() {
+                    return Err(self.error(
+                        location,
+                        format!("{} entries are only allowed in modules", info.tag_name()),
+                    ));
+                }
+                self.utf8(name_index, location)?;
+            }
+            ConstantPoolInfo::Reserved
+            | ConstantPoolInfo::Utf { .. }
+            | ConstantPoolInfo::Integer(_)
+            | ConstantPoolInfo::Long(_)
+            | ConstantPoolInfo::Float(_)
+            | ConstantPoolInfo::Double(_) => {}
+        }
+
+        Ok(())
+    }
+
+    fn bootstrap_method_count(&self) -> usize {
+        self.class_file
+            .attributes
+            .iter()
+            .find_map(|a| match a {
+                Attribute::BootstrapMethods { bootstrap_methods } => Some(bootstrap_methods.len()),
+                _ => None,
+            })
+            .unwrap_or(0)
+    }
+
+    fn method_handle(
+        &self,
+        reference_kind: u8,
+        reference_index: &Index,
+        location: &str,
+    ) -> Result<()> {
+        let kind = MethodHandleKind::new(reference_kind).ok_or_else(|| {
+            self.error(location, format!("invalid reference kind {reference_kind}"))
+        })?;
+        let reference = self.entry(reference_index, location)?;
+
+        let (expected, valid) = match kind {
+            MethodHandleKind::GetField
+            | MethodHandleKind::GetStatic
+            | MethodHandleKind::PutField
+            | MethodHandleKind::PutStatic => (
+                "Fieldref",
+                matches!(reference, ConstantPoolInfo::FieldRef { .. }),
+            ),
+            MethodHandleKind::InvokeVirtual | MethodHandleKind::NewInvokeSpecial => (
+                "Methodref",
+                matches!(reference, ConstantPoolInfo::MethodRef { .. }),
+            ),
+            MethodHandleKind::InvokeStatic | MethodHandleKind::InvokeSpecial => (
+                "Methodref or InterfaceMethodref",
+                matches!(reference, ConstantPoolInfo::MethodRef { .. })
+                    || (self.major() >= 52
+                        && matches!(reference, ConstantPoolInfo::InterfaceMethodRef { .. })),
+            ),
+            MethodHandleKind::InvokeInterface => (
+                "InterfaceMethodref",
+                matches!(reference, ConstantPoolInfo::InterfaceMethodRef { .. }),
+            ),
+        };
+        if !valid {
+            return Err(self.unexpected(reference_index, expected, reference, location));
+        }
+
+        if let ConstantPoolInfo::MethodRef {
+            name_and_type_index,
+            ..
+        }
+        | ConstantPoolInfo::InterfaceMethodRef {
+            name_and_type_index,
+            ..
+        } = reference
+        {
+            let (name, _) = self.name_and_type(name_and_type_index, location)?;
+            let is_init = name == "<init>";
+            if (kind == MethodHandleKind::NewInvokeSpecial) != is_init || name == "<clinit>" {
+                return Err(self.error(
+                    location,
+                    format!("{kind:?} method handle cannot reference {name}"),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn class(&self) -> Result<()> {
+        let flags = &self.class_file.access_flags;
+        let location = "access flags";
+
+        if self.is_module() {
+            if flags.len() != 1 {
+                return Err(self.error(location, "a module has no other flags".to_string()));
+            }
+            if self.major() < 53 {
+                return Err(self.error(
+                    location,
+                    "modules require class file version 53".to_string(),
+                ));
+            }
+            let name = self.class_name(&self.class_file.this_class_index, "this_class")?;
+            if name != "module-info" {
+                return Err(self.error("this_class", format!("module is named {name}")));
+            }
+            if !self.class_file.super_class_index.is_zero()
+                || !self.class_file.interface_indices.is_empty()
+                || !self.class_file.fields.is_empty()
+                || !self.class_file.methods.is_empty()
+            {
+                return Err(self.error(
+                    location,
+                    "a module has no super class, interfaces, fields or methods".to_string(),
+                ));
+            }
+
+            return Ok(());
+        }
+
+        if self.is_interface() {
+            if !flags.contains(&AccessFlag::Abstract) {
+                return Err(self.error(location, "interface is not abstract".to_string()));
+            }
+            for flag in [AccessFlag::Final, AccessFlag::Super, AccessFlag::Enum] {
+                if flags.contains(&flag) {
+                    return Err(self.error(location, format!("interface is {flag:?}")));
+                }
+            }
+        } else {
+            if flags.contains(&AccessFlag::Annotation) {
+                return Err(self.error(location, "annotation is not an interface".to_string()));
+            }
+            if flags.contains(&AccessFlag::Final) && flags.contains(&AccessFlag::Abstract) {
+                return Err(self.error(location, "class is both final and abstract".to_string()));
+            }
+        }
+
+        let name = self.non_array_class(&self.class_file.this_class_index, "this_class")?;
+        if self.class_file.super_class_index.is_zero() {
+            if name != "java/lang/Object" {
+                return Err(self.error("super_class", "missing super class".to_string()));
+            }
+        } else {
+            let super_class =
+                self.non_array_class(&self.class_file.super_class_index, "super_class")?;
+            if name == "java/lang/Object" {
+                return Err(self.error(
+                    "super_class",
+                    "java/lang/Object has no super class".to_string(),
+                ));
+            }
+            if self.is_interface() && super_class != "java/lang/Object" {
+                return Err(self.error(
+                    "super_class",
+                    format!(
+                        "super class of an interface must be java/lang/Object, is {super_class}"
+                    ),
+                ));
+            }
+        }
+
+        for (i, index) in self.class_file.interface_indices.iter().enumerate() {
+            self.non_array_class(index, &format!("interface {i}"))?;
+        }
+
+        Ok(())
+    }
+
+    fn field(&self, field: &Field, location: &str) -> Result<(String, String)> {
+        let name = self.utf8(&field.name_index, location)?;
+        let location = &format!("{location} ({name})");
+        self.unqualified_name(&name, location)?;
+        let descriptor = self.utf8(&field.descriptor_index, location)?;
+        let field_type = self.field_descriptor(&descriptor, location)?;
+
+        let flags = &field.access_flags;
+        let visibility = [FieldFlag::Public, FieldFlag::Private, FieldFlag::Protected]
+            .iter()
+            .filter(|f| flags.contains(f))
+            .count();
+        if visibility > 1 {
+            return Err(self.error(location, format!("conflicting access flags {flags:?}")));
+        }
+        if flags.contains(&FieldFlag::Final) && flags.contains(&FieldFlag::Volatile) {
+            return Err(self.error(location, "field is both final and volatile".to_string()));
+        }
+        if self.is_interface() {
+            let required = [FieldFlag::Public, FieldFlag::Static, FieldFlag::Final];
+            if !required.iter().all(|f| flags.contains(f))
+                || flags
+                    .iter()
+                    .any(|f| !required.contains(f) && *f != FieldFlag::Synthetic)
+            {
+                return Err(self.error(
+                    location,
+                    format!("interface field must be public static final, is {flags:?}"),
+                ));
+            }
+        }
+
+        for (i, attribute) in field.attributes.iter().enumerate() {
+            if let Attribute::ConstantValue {
+                constant_value_index,
+            } = attribute
+            {
+                let location = &format!("{location}, attribute {i} (ConstantValue)");
+                self.constant_value(&field_type, constant_value_index, location)?;
+            }
+        }
+        self.attributes(&field.attributes, location)?;
+
+        Ok((name, descriptor))
+    }
+
+    fn constant_value(&self, field_type: &FieldType, index: &Index, location: &str) -> Result<()> {
+        let info = self.entry(index, location)?;
+        let valid = match field_type {
+            FieldType::Long => matches!(info, ConstantPoolInfo::Long(_)),
+            FieldType::Float => matches!(info, ConstantPoolInfo::Float(_)),
+            FieldType::Double => matches!(info, ConstantPoolInfo::Double(_)),
+            FieldType::Int
+            | FieldType::Short
+            | FieldType::Char
+            | FieldType::Byte
+            | FieldType::Boolean => matches!(info, ConstantPoolInfo::Integer(_)),
+            FieldType::Class(name) => {
+                name == "java/lang/String" && matches!(info, ConstantPoolInfo::String { .. })
+            }
+            FieldType::Array(_) => false,
+        };
+
+        if !valid {
+            return Err(self.error(
+                location,
+                format!(
+                    "{} constant cannot initialize a field of type {field_type}",
+                    info.tag_name()
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn method(&self, method: &Method, location: &str) -> Result<(String, String)> {
+        let name = self.utf8(&method.name_index, location)?;
+        let location = &format!("{location} ({name})");
+        let descriptor = self.utf8(&method.descriptor_index, location)?;
+        let method_descriptor = self.method_descriptor(&descriptor, location)?;
+        let flags = &method.access_flags;
+
+        let slots: usize = method_descriptor
+            .parameters
+            .iter()
+            .map(|p| match p {
+                FieldType::Long | FieldType::Double => 2,
+                _ => 1,
+            })
+            .sum::<usize>()
+            + usize::from(!flags.contains(&MethodFlag::Static));
+        if slots > MAX_PARAMETER_SLOTS {
+            return Err(self.error(location, format!("{slots} parameter slots")));
+        }
+
+        if name == "<clinit>" {
+            if self.major() >= 51 && (!flags.contains(&MethodFlag::Static) || descriptor != "()V") {
+                return Err(self.error(
+                    location,
+                    "class initializer must be static and take no arguments".to_string(),
+                ));
+            }
+        } else {
+            if name == "<init>" {
+                if self.is_interface() {
+                    return Err(self.error(location, "interface declares <init>".to_string()));
+                }
+                if method_descriptor.return_descriptor != ReturnDescriptor::Void {
+                    return Err(self.error(location, "<init> must return void".to_string()));
+                }
+            } else {
+                self.method_name(&name, location)?;
+            }
+            self.method_flags(&name, flags, location)?;
+        }
+
+        let code_attributes = method
+            .attributes
+            .iter()
+            .filter(|a| matches!(a, Attribute::Code { .. }))
+            .count();
+        let has_body =
+            !flags.contains(&MethodFlag::Abstract) && !flags.contains(&MethodFlag::Native);
+        if has_body && code_attributes != 1 {
+            return Err(self.error(location, "method has no Code attribute".to_string()));
+        }
+        if !has_body && code_attributes != 0 {
+            return Err(self.error(
+                location,
+                "abstract or native method has a Code attribute".to_string(),
+            ));
+        }
+
+        self.attributes(&method.attributes, location)?;
+        Ok((name, descriptor))
+    }
+
+    fn method_flags(&self, name: &str, flags: &[MethodFlag], location: &str) -> Result<()> {
+        let visibility = [
+            MethodFlag::Public,
+            MethodFlag::Private,
+            MethodFlag::Protected,
+        ]
+        .iter()
+        .filter(|f| flags.contains(f))
+        .count();
+        if visibility > 1 {
+            return Err(self.error(location, format!("conflicting access flags {flags:?}")));
+        }
+
+        let illegal: &[MethodFlag] = if name == "<init>" {
+            &[
+                MethodFlag::Static,
+                MethodFlag::Final,
+                MethodFlag::Synchronized,
+                MethodFlag::Bridge,
+                MethodFlag::Native,
+                MethodFlag::Abstract,
+            ]
+        } else if self.is_interface() && self.major() < 52 {
+            if !flags.contains(&MethodFlag::Public) || !flags.contains(&MethodFlag::Abstract) {
+                return Err(self.error(
+                    location,
+                    "interface method must be public abstract".to_string(),
+                ));
+            }
+            &[
+                MethodFlag::Static,
+                MethodFlag::Final,
+                MethodFlag::Synchronized,
+                MethodFlag::Native,
+                MethodFlag::Strict,
+            ]
+        } else if self.is_interface() {
+            if visibility != 1 || flags.contains(&MethodFlag::Protected) {
+                return Err(self.error(
+                    location,
+                    "interface method must be either public or private".to_string(),
+                ));
+            }
+            &[
+                MethodFlag::Protected,
+                MethodFlag::Final,
+                MethodFlag::Synchronized,
+                MethodFlag::Native,
+            ]
+        } else {
+            &[]
+        };
+
+        if let Some(flag) = illegal.iter().find(|f| flags.contains(f)) {
+            return Err(self.error(location, format!("method cannot be {flag:?}")));
+        }
+
+        if flags.contains(&MethodFlag::Abstract) {
+            // strictfp became implicit in Java 17 and with it the restriction on abstract
+            let strict = (46..61).contains(&self.major()) && flags.contains(&MethodFlag::Strict);
+            if strict
+                || [
+                    MethodFlag::Private,
+                    MethodFlag::Static,
+                    MethodFlag::Final,
+                    MethodFlag::Synchronized,
+                    MethodFlag::Native,
+                ]
+                .iter()
+                .any(|f| flags.contains(f))
+            {
+                return Err(self.error(
+                    location,
+                    format!("abstract method has conflicting flags {flags:?}"),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn attributes(&self, attributes: &[Attribute], location: &str) -> Result<()> {
+        let mut seen = HashSet::new();
+        for (i, attribute) in attributes.iter().enumerate() {
+            let location = &format!("{location}, attribute {i} ({})", attribute.name());
+            let repeatable = matches!(
+                attribute,
+                Attribute::LineNumberTable { .. }
+                    | Attribute::LocalVariableTable { .. }
+                    | Attribute::LocalVariableTypeTable { .. }
+                    | Attribute::Deprecated
+                    | Attribute::Unknown { .. }
+            );
+            if !repeatable && !seen.insert(attribute.name()) {
+                return Err(self.error(location, "attribute appears more than once".to_string()));
+            }
+
+            self.attribute(attribute, location)?;
+        }
+
+        if seen.contains("NestHost") && seen.contains("NestMembers") {
+            return Err(self.error(
+                location,
+                "class has both NestHost and NestMembers attributes".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn attribute(&self, attribute: &Attribute, location: &str) -> Result<()> {
+        match attribute {
+            Attribute::Code {
+                code,
+                exceptions,
+                attributes,
+                ..
+            } => {
+                if code.is_empty() || code.len() > u16::MAX as usize {
+                    return Err(self.error(location, format!("code length {}", code.len())));
+                }
+                if let Err(err) = Instruction::decode(code) {
+                    return Err(self.error(location, err.to_string()));
+                }
+
+                for (i, exception) in exceptions.iter().enumerate() {
+                    let location = &format!("{location}, exception table entry {i}");
+                    if exception.start_pc >= exception.end_pc
+                        || exception.end_pc as usize > code.len()
+                        || exception.handler_pc as usize >= code.len()
+                    {
+                        return Err(self.error(
+                            location,
+                            format!(
+                                "range {}..{} with handler {} does not fit code of length {}",
+                                exception.start_pc,
+                                exception.end_pc,
+                                exception.handler_pc,
+                                code.len()
+                            ),
+                        ));
+                    }
+                    if exception.catch_type != 0 {
+                        self.non_array_class(&Index::new(exception.catch_type), location)?;
+                    }
+                }
+
+                for attribute in attributes {
+                    if let Attribute::LocalVariableTable {
+                        local_variable_table,
+                    } = attribute
+                    {
+                        for (i, variable) in local_variable_table.iter().enumerate() {
+                            let location = &format!("{location}, local variable {i}");
+                            let name = self.utf8(&variable.name_index, location)?;
+                            self.unqualified_name(&name, location)?;
+                            let descriptor = self.utf8(&variable.descriptor_index, location)?;
+                            self.field_descriptor(&descriptor, location)?;
+                            if variable.start_pc as usize + variable.length as usize > code.len() {
+                                return Err(self
+                                    .error(location, "scope exceeds the code array".to_string()));
+                            }
+                        }
+                    }
+                }
+
+                self.attributes(attributes, location)?;
+            }
+            Attribute::SourceFile {
+                source_file_index: index,
+            }
+            | Attribute::Signature {
+                signature_index: index,
+            } => {
+                self.utf8(index, location)?;
+            }
+            Attribute::Exceptions {
+                exception_index_table: classes,
+            }
+            | Attribute::NestMembers { classes }
+            | Attribute::PermittedSubclasses { classes } => {
+                for index in classes {
+                    self.non_array_class(index, location)?;
+                }
+            }
+            Attribute::NestHost {
+                host_class_index: index,
+            }
+            | Attribute::ModuleMainClass {
+                main_class_index: index,
+            } => {
+                self.non_array_class(index, location)?;
+            }
+            Attribute::InnerClasses { classes } => {
+                for class in classes {
+                    self.non_array_class(&class.inner_class_info_index, location)?;
+                    if !class.outer_class_info_index.is_zero() {
+                        self.non_array_class(&class.outer_class_info_index, location)?;
+                    }
+                    if !class.inner_name_index.is_zero() {
+                        self.utf8(&class.inner_name_index, location)?;
+                    }
+                }
+            }
+            Attribute::EnclosingMethod {
+                class_index,
+                method_index,
+            } => {
+                self.non_array_class(class_index, location)?;
+                if !method_index.is_zero() {
+                    self.name_and_type(method_index, location)?;
+                }
+            }
+            Attribute::ModulePackages { package_index } => {
+                for index in package_index {
+                    match self.entry(index, location)? {
+                        ConstantPoolInfo::Package { .. } => {}
+                        info => return Err(self.unexpected(index, "Package", info, location)),
+                    }
+                }
+            }
+            Attribute::BootstrapMethods { bootstrap_methods } => {
+                for (i, method) in bootstrap_methods.iter().enumerate() {
+                    let location = &format!("{location}, bootstrap method {i}");
+                    let index = Index::new(method.bootstrap_method_ref);
+                    match self.entry(&index, location)? {
+                        ConstantPoolInfo::MethodHandle { .. } => {}
+                        info => {
+                            return Err(self.unexpected(&index, "MethodHandle", info, location))
+                        }
+                    }
+                    for argument in &method.bootstrap_arguments {
+                        let index = Index::new(*argument);
+                        let info = self.entry(&index, location)?;
+                        if !info.is_loadable() {
+                            return Err(self.unexpected(&index, "loadable", info, location));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// JVMS 4.2.2
+    fn unqualified_name(&self, name: &str, location: &str) -> Result<()> {
+        if name.is_empty() || name.contains(['.', ';', '[', '/']) {
+            return Err(self.error(location, format!("invalid name \"{name}\"")));
+        }
+
+        Ok(())
+    }
+
+    fn method_name(&self, name: &str, location: &str) -> Result<()> {
+        self.unqualified_name(name, location)?;
+        if name.contains(['<', '>']) {
+            return Err(self.error(location, format!("invalid method name \"{name}\"")));
+        }
+
+        Ok(())
+    }
+
+    /// JVMS 4.2.1, binary names in their internal form or array descriptors.
+    fn class_or_array_name(&self, name: &str, location: &str) -> Result<()> {
+        if name.starts_with('[') {
+            self.field_descriptor(name, location)?;
+            return Ok(());
+        }
+
+        for part in name.split('/') {
+            self.unqualified_name(part, location)
+                .map_err(|_| self.error(location, format!("invalid class name \"{name}\"")))?;
+        }
+
+        Ok(())
+    }
+
+    fn field_descriptor(&self, descriptor: &str, location: &str) -> Result<FieldType> {
+        let field_type = FieldType::new(descriptor)
+            .ok()
+            .filter(|t| t.to_string() == descriptor)
+            .ok_or_else(|| {
+                self.error(location, format!("invalid field descriptor {descriptor}"))
+            })?;
+        self.field_type(&field_type, location)?;
+
+        Ok(field_type)
+    }
+
+    fn method_descriptor(&self, descriptor: &str, location: &str) -> Result<MethodDescriptor> {
+        let method_descriptor = MethodDescriptor::new(descriptor)
+            .ok()
+            .filter(|d| descriptor.starts_with('(') && d.to_string() == descriptor)
+            .ok_or_else(|| {
+                self.error(location, format!("invalid method descriptor {descriptor}"))
+            })?;
+
+        for parameter in &method_descriptor.parameters {
+            self.field_type(parameter, location)?;
+        }
+        if let ReturnDescriptor::Type(field_type) = &method_descriptor.return_descriptor {
+            self.field_type(field_type, location)?;
+        }
+
+        Ok(method_descriptor)
+    }
+
+    fn field_type(&self, field_type: &FieldType, location: &str) -> Result<()> {
+        let mut dimensions = 0;
+        let mut component = field_type;
+        while let FieldType::Array(inner) = component {
+            dimensions += 1;
+            component = inner;
+        }
+        if dimensions > MAX_ARRAY_DIMENSIONS {
+            return Err(self.error(location, format!("{dimensions} array dimensions")));
+        }
+
+        if let FieldType::Class(name) = component {
+            if name.starts_with('[') {
+                return Err(self.error(location, format!("invalid class name \"{name}\"")));
+            }
+            self.class_or_array_name(name, location)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parser::{class::ClassFile, error::ClassFormatError, method::MethodFlag},
+        ClassIdentifier,
+    };
+
+    /// The location and message of a failed check.
+    fn malformed(class_file: &ClassFile) -> (String, String) {
+        match class_file.check().unwrap_err() {
+            ClassFormatError::Malformed {
+                class,
+                offset: None,
+                context,
+                cause,
+            } => {
+                assert_eq!(class, class_file.this_class);
+                (context, cause)
+            }
+            err => panic!("unexpected error {err}"),
+        }
+    }
+
+    fn class_file(name: &str) -> ClassFile {
+        let data = std::fs::read(format!("testdata/{name}.class")).unwrap();
+        ClassFile::new(&data, ClassIdentifier::from_utf8(name.to_string()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_valid() {
+        for name in [
+            "Main",
+            "Annotated",
+            "Annotated$Info",
+            "Shape",
+            "Shape$Circle",
+        ] {
+            class_file(name).check().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_conflicting_method_flags() {
+        let mut class_file = class_file("Main");
+        class_file.methods[1].access_flags.push(MethodFlag::Private);
+
+        let (context, cause) = malformed(&class_file);
+        assert_eq!(context, "method 1 (main)");
+        assert!(cause.contains("conflicting access flags"));
+        assert!(class_file
+            .check()
+            .unwrap_err()
+            .to_string()
+            .starts_with("ClassFormatError: <default>.Main (method 1 (main))"));
+    }
+
+    #[test]
+    fn test_duplicate_method() {
+        let mut class_file = class_file("Main");
+        let main = class_file.methods[1].clone();
+        class_file.methods.push(main);
+
+        let (context, cause) = malformed(&class_file);
+        assert_eq!(context, "method 2 (main)");
+        assert_eq!(cause, "duplicate method main([Ljava/lang/String;)V");
+    }
+
+    #[test]
+    fn test_wrong_index_kind() {
+        let mut class_file = class_file("Main");
+        class_file.methods[1].descriptor_index = class_file.this_class_index.clone();
+
+        let (context, cause) = malformed(&class_file);
+        assert_eq!(context, "method 1 (main)");
+        assert_eq!(cause, "#21 must be a Utf8 entry, is Class");
+    }
+
+    #[test]
+    fn test_trailing_data() {
+        let mut data = std::fs::read("testdata/Main.class").unwrap();
+        data.push(0);
+
+        let err = ClassFile::new(
+            &data,
+            ClassIdentifier::from_utf8("Main".to_string()).unwrap(),
+        )
+        .unwrap_err();
+        let ClassFormatError::Malformed { cause, .. } = err else {
+            panic!("unexpected error {err}");
+        };
+        assert!(cause.contains("1 extra bytes"));
+    }
+}
@@ -1,89 +1,368 @@
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use std::{
     fmt::Display,
     io::{Cursor, Read},
 };
 
-use crate::{parser::parse_u16, ClassIdentifier};
+use crate::{
+    parser::{parse_u16, write_count, write_index, write_u16},
+    ClassIdentifier,
+};
 
 use super::{
-    attribute::Attribute,
-    constant_pool::{ConstantPool, Index},
+    attribute::{
+        annotation::Annotations, inner_class::InnerClassItem, module::ModuleDescriptor,
+        record::RecordComponentItem, Attribute, Attributes,
+    },
+    constant_pool::{ConstantPool, Index, NameAndType},
+    error::ClassFormatError,
     field::Field,
     method::Method,
+    signature::{signature, ClassSignature},
 };
 
+/// Highest class file major version this VM can load (Java 17).
+pub const MAX_SUPPORTED_MAJOR_VERSION: u16 = 61;
+const MIN_SUPPORTED_MAJOR_VERSION: u16 = 45;
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl Version {
+    pub fn new(major: u16, minor: u16) -> Self {
+        Self { major, minor }
+    }
+
+    fn check(&self, class_identifier: &ClassIdentifier) -> Result<(), ClassFormatError> {
+        if !(MIN_SUPPORTED_MAJOR_VERSION..=MAX_SUPPORTED_MAJOR_VERSION).contains(&self.major) {
+            return Err(ClassFormatError::UnsupportedVersion {
+                class: class_identifier.clone(),
+                version: self.clone(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// A parsed class file, which keeps every structure needed to write it back unchanged.
 #[derive(Clone, Debug)]
 pub struct ClassFile {
-    pub class_identifier: ClassIdentifier,
+    pub version: Version,
+    pub this_class: ClassIdentifier,
+    pub super_class: Option<ClassIdentifier>,
+    pub interfaces: Vec<ClassIdentifier>,
+    pub this_class_index: Index,
+    /// Zero for `java/lang/Object` and module declarations, which have no super class.
+    pub super_class_index: Index,
+    pub interface_indices: Vec<Index>,
     pub constant_pool: ConstantPool,
-    pub _interfaces: Vec<Index>,
     pub methods: Vec<Method>,
     pub fields: Vec<Field>,
     pub access_flags: Vec<AccessFlag>,
+    pub attributes: Attributes,
 }
 
 impl ClassFile {
-    pub fn new(data: &Vec<u8>, class_identifier: ClassIdentifier) -> Result<ClassFile> {
+    /// Parses the class file `data`, which has to declare the class `class_identifier`.
+    pub fn new(
+        data: &[u8],
+        class_identifier: ClassIdentifier,
+    ) -> Result<ClassFile, ClassFormatError> {
+        Self::read(data, Some(class_identifier))
+    }
+
+    /// Parses a class file whose class is not known up front, such as a file on disk, taking
+    /// the class from its `this_class` entry. Errors before `this_class` is read name the
+    /// default class.
+    pub fn from_bytes(data: &[u8]) -> Result<ClassFile, ClassFormatError> {
+        Self::read(data, None)
+    }
+
+    fn read(data: &[u8], expected: Option<ClassIdentifier>) -> Result<ClassFile, ClassFormatError> {
         let mut c = Cursor::new(data);
+        let mut class_identifier = expected.clone().unwrap_or_default();
+        let class_file = Self::parse(&mut c, expected.as_ref(), &mut class_identifier);
+        class_file.map_err(|err| ClassFormatError::new(class_identifier, c.position(), err))
+    }
 
+    /// Parses the structures of a class file, with the one being parsed as the context of
+    /// any error. Without an `expected` class, `class_identifier` is set from `this_class`.
+    fn parse(
+        c: &mut Cursor<&[u8]>,
+        expected: Option<&ClassIdentifier>,
+        class_identifier: &mut ClassIdentifier,
+    ) -> Result<ClassFile> {
         let mut magic = [0u8; 4];
         c.read_exact(&mut magic)?;
         if magic != [0xCA, 0xFE, 0xBA, 0xBE] {
-            bail!("invalid magic bytes {magic:?}");
+            bail!("incompatible magic value {:#x}", u32::from_be_bytes(magic));
         }
 
-        let _minor_version = parse_u16(&mut c);
-        let _major_version = parse_u16(&mut c);
+        let minor_version = parse_u16(c)?;
+        let major_version = parse_u16(c)?;
+        let version = Version::new(major_version, minor_version);
+        if let Some(expected) = expected {
+            version.check(expected)?;
+        }
 
-        let constant_pool_count = parse_u16(&mut c)? as usize;
+        let constant_pool_count = parse_u16(c)? as usize;
         if constant_pool_count == 0 {
             bail!("constant pool count is empty");
         }
 
-        let constant_pool = ConstantPool::new(&mut c, constant_pool_count)?;
-        let access_flags = AccessFlag::flags(parse_u16(&mut c)?);
-        let _this_class = parse_u16(&mut c)?;
-        let _super_class = parse_u16(&mut c)?;
-        let interfaces_count = parse_u16(&mut c)?;
+        let constant_pool = match ConstantPool::new(c, constant_pool_count) {
+            Ok(constant_pool) => constant_pool,
+            // a class file of an unsupported version may use constants unknown to the parser
+            Err(err) => {
+                version.check(class_identifier)?;
+                return Err(err);
+            }
+        };
+        let access_flags = AccessFlag::flags(parse_u16(c)?);
+
+        let this_class_index = Index::new(parse_u16(c)?);
+        let this_class = Self::class(&constant_pool, &this_class_index).context("this class")?;
+        match expected {
+            Some(expected) if this_class != *expected => {
+                return Err(ClassFormatError::WrongName {
+                    class: expected.clone(),
+                    found: this_class,
+                }
+                .into())
+            }
+            Some(_) => {}
+            None => {
+                *class_identifier = this_class.clone();
+                version.check(class_identifier)?;
+            }
+        }
+
+        let super_class_index = Index::new(parse_u16(c)?);
+        let super_class = if super_class_index.is_zero() {
+            None
+        } else {
+            Some(Self::class(&constant_pool, &super_class_index).context("super class")?)
+        };
 
+        let interfaces_count = parse_u16(c)?;
         let mut interfaces = Vec::new();
-        for _ in 0..interfaces_count {
-            let index = parse_u16(&mut c)?;
-            interfaces.push(Index::new(index));
+        let mut interface_indices = Vec::new();
+        for i in 0..interfaces_count {
+            let index = Index::new(parse_u16(c)?);
+            interfaces.push(
+                Self::class(&constant_pool, &index).with_context(|| format!("interface {i}"))?,
+            );
+            interface_indices.push(index);
         }
 
-        let fields_count = parse_u16(&mut c)?;
+        let fields_count = parse_u16(c)?;
         let mut fields = Vec::new();
-        for _ in 0..fields_count {
-            let field = Field::new(&mut c, &constant_pool)?;
+        for i in 0..fields_count {
+            let field = Field::new(c, &constant_pool).with_context(|| format!("field {i}"))?;
             fields.push(field);
         }
 
-        let methods_count = parse_u16(&mut c)?;
+        let methods_count = parse_u16(c)?;
 
         let mut methods = Vec::new();
-        for _ in 0..methods_count {
-            let method = Method::new(&mut c, &constant_pool)?;
+        for i in 0..methods_count {
+            let method = Method::new(c, &constant_pool).with_context(|| format!("method {i}"))?;
             methods.push(method);
         }
 
-        let _attributes = Attribute::attributes(&mut c, &constant_pool);
+        let attributes = Attributes::new(c, &constant_pool)?;
+
+        let extra = c.get_ref().len() - c.position() as usize;
+        if extra > 0 {
+            return Err(anyhow!("{extra} extra bytes").context("end of class file"));
+        }
 
         Ok(ClassFile {
-            class_identifier,
+            version,
+            this_class,
+            super_class,
+            interfaces,
+            this_class_index,
+            super_class_index,
+            interface_indices,
             constant_pool,
-            _interfaces: interfaces,
             methods,
             fields,
             access_flags,
+            attributes,
         })
     }
+
+    /// Serializes the class file, the inverse of [`ClassFile::new`].
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut w = Vec::new();
+        w.extend([0xCA, 0xFE, 0xBA, 0xBE]);
+        write_u16(&mut w, self.version.minor);
+        write_u16(&mut w, self.version.major);
+        self.constant_pool.write(&mut w)?;
+        write_u16(&mut w, AccessFlag::bits(&self.access_flags));
+        write_index(&mut w, &self.this_class_index)?;
+        write_index(&mut w, &self.super_class_index)?;
+
+        write_count(&mut w, self.interface_indices.len())?;
+        for index in &self.interface_indices {
+            write_index(&mut w, index)?;
+        }
+
+        write_count(&mut w, self.fields.len())?;
+        for field in &self.fields {
+            field.write(&mut w, &self.constant_pool)?;
+        }
+
+        write_count(&mut w, self.methods.len())?;
+        for method in &self.methods {
+            method.write(&mut w, &self.constant_pool)?;
+        }
+
+        self.attributes.write(&mut w, &self.constant_pool)?;
+        Ok(w)
+    }
+
+    pub fn annotations(&self) -> Result<Annotations> {
+        Annotations::new(&self.attributes, &self.constant_pool)
+    }
+
+    /// Type parameters and generic super types from the `Signature` attribute, if any.
+    pub fn signature(&self) -> Result<Option<ClassSignature>> {
+        signature(&self.attributes, &self.constant_pool)?
+            .map(|text| ClassSignature::new(&text))
+            .transpose()
+    }
+
+    /// The module declaration of a `module-info.class`, `None` for ordinary classes.
+    pub fn module(&self) -> Result<Option<ModuleDescriptor>> {
+        ModuleDescriptor::new(&self.attributes, &self.constant_pool)
+    }
+
+    /// The components of a record class, `None` if the class has no `Record` attribute.
+    pub fn record_components(&self) -> Result<Option<Vec<RecordComponentItem>>> {
+        for attribute in &self.attributes {
+            if let Attribute::Record { components } = attribute {
+                return Ok(Some(
+                    components
+                        .iter()
+                        .map(|c| c.resolve(&self.constant_pool))
+                        .collect::<Result<_>>()?,
+                ));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The classes permitted to extend a sealed class, empty if the class is not sealed.
+    pub fn permitted_subclasses(&self) -> Result<Vec<ClassIdentifier>> {
+        for attribute in &self.attributes {
+            if let Attribute::PermittedSubclasses { classes } = attribute {
+                return classes
+                    .iter()
+                    .map(|i| Self::class(&self.constant_pool, i))
+                    .collect();
+            }
+        }
+
+        Ok(Vec::new())
+    }
+
+    /// The host of the nest this class claims to belong to, from the `NestHost` attribute.
+    pub fn nest_host(&self) -> Result<Option<ClassIdentifier>> {
+        for attribute in &self.attributes {
+            if let Attribute::NestHost { host_class_index } = attribute {
+                return Self::class(&self.constant_pool, host_class_index).map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The classes this class claims as members of its nest, from the `NestMembers` attribute.
+    pub fn nest_members(&self) -> Result<Vec<ClassIdentifier>> {
+        for attribute in &self.attributes {
+            if let Attribute::NestMembers { classes } = attribute {
+                return classes
+                    .iter()
+                    .map(|i| Self::class(&self.constant_pool, i))
+                    .collect();
+            }
+        }
+
+        Ok(Vec::new())
+    }
+
+    /// The entries of the `InnerClasses` attribute, empty if absent.
+    pub fn inner_classes(&self) -> Result<Vec<InnerClassItem>> {
+        for attribute in &self.attributes {
+            if let Attribute::InnerClasses { classes } = attribute {
+                return classes
+                    .iter()
+                    .map(|c| c.resolve(&self.constant_pool))
+                    .collect();
+            }
+        }
+
+        Ok(Vec::new())
+    }
+
+    /// The class, and method if any, enclosing a local or anonymous class.
+    pub fn enclosing_method(&self) -> Result<Option<EnclosingMethodItem>> {
+        for attribute in &self.attributes {
+            if let Attribute::EnclosingMethod {
+                class_index,
+                method_index,
+            } = attribute
+            {
+                let method = if method_index.is_zero() {
+                    None
+                } else {
+                    Some(
+                        self.constant_pool
+                            .name_and_type_method(method_index)
+                            .context(format!("no method NameAndType entry at {method_index:?}"))?,
+                    )
+                };
+
+                return Ok(Some(EnclosingMethodItem {
+                    class: Self::class(&self.constant_pool, class_index)?,
+                    method,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn class(constant_pool: &ConstantPool, index: &Index) -> Result<ClassIdentifier> {
+        constant_pool
+            .class_identifier(index)
+            .context(format!("no class entry at {index:?} in constant pool"))
+    }
+}
+
+/// The `EnclosingMethod` attribute; `method` is `None` for classes enclosed by an initializer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnclosingMethodItem {
+    pub class: ClassIdentifier,
+    pub method: Option<NameAndType>,
 }
 
 impl Display for ClassFile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.class_identifier)
+        write!(f, "{}", self.this_class)
     }
 }
 
@@ -94,9 +373,31 @@ pub enum AccessFlag {
     Super,
     Interface,
     Abstract,
+    Synthetic,
+    Annotation,
+    Enum,
+    Module,
 }
 
 impl AccessFlag {
+    fn mask(&self) -> u16 {
+        match self {
+            AccessFlag::Public => 0x0001,
+            AccessFlag::Final => 0x0010,
+            AccessFlag::Super => 0x0020,
+            AccessFlag::Interface => 0x0200,
+            AccessFlag::Abstract => 0x0400,
+            AccessFlag::Synthetic => 0x1000,
+            AccessFlag::Annotation => 0x2000,
+            AccessFlag::Enum => 0x4000,
+            AccessFlag::Module => 0x8000,
+        }
+    }
+
+    pub fn bits(flags: &[AccessFlag]) -> u16 {
+        flags.iter().fold(0, |bits, flag| bits | flag.mask())
+    }
+
     fn flags(val: u16) -> Vec<AccessFlag> {
         let mut flags = Vec::new();
 
@@ -120,6 +421,207 @@ impl AccessFlag {
             flags.push(AccessFlag::Abstract);
         }
 
+        if (val & 0x1000) != 0 {
+            flags.push(AccessFlag::Synthetic);
+        }
+
+        if (val & 0x2000) != 0 {
+            flags.push(AccessFlag::Annotation);
+        }
+
+        if (val & 0x4000) != 0 {
+            flags.push(AccessFlag::Enum);
+        }
+
+        if (val & 0x8000) != 0 {
+            flags.push(AccessFlag::Module);
+        }
+
         flags
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parser::{attribute::method_parameter::AccessFlag, descriptor::FieldType},
+        ClassIdentifier, ClassName, Package,
+    };
+
+    use super::{ClassFile, ClassFormatError, Version};
+
+    fn main_identifier() -> ClassIdentifier {
+        ClassIdentifier::new(Package::default(), ClassName::new("Main".to_string()))
+    }
+
+    #[test]
+    fn test_header() {
+        let data = std::fs::read("testdata/Main.class").unwrap();
+        let class_file = ClassFile::new(&data, main_identifier()).unwrap();
+
+        assert_eq!(class_file.version, Version::new(61, 0));
+        assert_eq!(class_file.this_class, main_identifier());
+        assert_eq!(
+            class_file.super_class,
+            Some(ClassIdentifier::from(
+                "java.lang".to_string(),
+                "Object".to_string()
+            ))
+        );
+        assert!(class_file.interfaces.is_empty());
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let data = std::fs::read("testdata/Main.class").unwrap();
+        let class_file = ClassFile::from_bytes(&data).unwrap();
+        assert_eq!(class_file.this_class, main_identifier());
+
+        let err = ClassFile::from_bytes(&data[..6]).unwrap_err();
+        assert!(matches!(err, ClassFormatError::Truncated { offset: 6, .. }));
+
+        // errors after this_class name the class it declares
+        let err = ClassFile::from_bytes(&data[..data.len() - 30]).unwrap_err();
+        let ClassFormatError::Truncated { class, .. } = err else {
+            panic!("{err}");
+        };
+        assert_eq!(class, main_identifier());
+
+        let mut data = data;
+        data[6..8].copy_from_slice(&62u16.to_be_bytes());
+        let err = ClassFile::from_bytes(&data).unwrap_err();
+        let ClassFormatError::UnsupportedVersion { class, .. } = err else {
+            panic!("{err}");
+        };
+        assert_eq!(class, main_identifier());
+    }
+
+    #[test]
+    fn test_wrong_name() {
+        let data = std::fs::read("testdata/Main.class").unwrap();
+        let identifier =
+            ClassIdentifier::new(Package::default(), ClassName::new("Other".to_string()));
+        let err = ClassFile::new(&data, identifier).unwrap_err();
+        assert!(err.to_string().starts_with("NoClassDefFoundError"));
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let mut data = std::fs::read("testdata/Main.class").unwrap();
+        data[6..8].copy_from_slice(&62u16.to_be_bytes());
+        let err = ClassFile::new(&data, main_identifier()).unwrap_err();
+        assert!(err.to_string().starts_with("UnsupportedClassVersionError"));
+    }
+
+    #[test]
+    fn test_truncated() {
+        let data = std::fs::read("testdata/Main.class").unwrap();
+        let err = ClassFile::new(&data[..data.len() - 30], main_identifier()).unwrap_err();
+
+        assert_eq!(err.exception(), "ClassFormatError");
+        let ClassFormatError::Truncated {
+            offset, context, ..
+        } = err
+        else {
+            panic!("{err}");
+        };
+        assert_eq!(offset, 384);
+        assert_eq!(context, "method 1, attribute Code");
+    }
+
+    #[test]
+    fn test_huge_attribute() {
+        let mut data = vec![0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 61, 0, 4];
+        data.extend([1, 0, 1, b'A', 7, 0, 1, 1, 0, 6]);
+        data.extend(b"Custom");
+        // flags, this, super, no interfaces, fields or methods, one attribute of 4 GiB
+        data.extend([0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 3]);
+        data.extend(u32::MAX.to_be_bytes());
+
+        let err = ClassFile::from_bytes(&data).unwrap_err();
+        assert!(matches!(err, ClassFormatError::Truncated { .. }), "{err}");
+    }
+
+    #[test]
+    fn test_bad_magic() {
+        let mut data = std::fs::read("testdata/Main.class").unwrap();
+        data[3] = 0xbf;
+        let err = ClassFile::new(&data, main_identifier()).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "ClassFormatError: <default>.Main (class file at offset 4): incompatible magic value 0xcafebabf"
+        );
+        assert_eq!(err.offset(), Some(4));
+    }
+
+    fn class_file(name: &str) -> ClassFile {
+        let data = std::fs::read(format!("testdata/{name}.class")).unwrap();
+        ClassFile::new(&data, ClassIdentifier::from_utf8(name.to_string()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_permitted_subclasses() {
+        assert_eq!(
+            class_file("Shape").permitted_subclasses().unwrap(),
+            vec![
+                ClassIdentifier::from_utf8("Shape$Circle".to_string()).unwrap(),
+                ClassIdentifier::from_utf8("Shape$Square".to_string()).unwrap(),
+            ]
+        );
+        assert!(class_file("Main")
+            .permitted_subclasses()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_record_components() {
+        let components = class_file("Shape$Circle")
+            .record_components()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0].name, "radius");
+        assert_eq!(components[0].descriptor, FieldType::Double);
+        assert_eq!(components[1].name, "label");
+        assert_eq!(
+            components[1].descriptor,
+            FieldType::Class("java/lang/String".to_string())
+        );
+        assert!(class_file("Shape$Square")
+            .record_components()
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_method_parameters() {
+        let class_file = class_file("Shape$Circle");
+        let scale = class_file
+            .methods
+            .iter()
+            .find(|m| class_file.constant_pool.utf8(&m.name_index).unwrap() == "scale")
+            .unwrap();
+        let parameters = scale.parameters(&class_file.constant_pool).unwrap();
+
+        assert_eq!(parameters.len(), 1);
+        assert_eq!(parameters[0].name.as_deref(), Some("factor"));
+        assert_eq!(parameters[0].access_flags, vec![AccessFlag::Final]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for name in [
+            "Main",
+            "Annotated",
+            "Annotated$Info",
+            "Shape",
+            "Shape$Circle",
+        ] {
+            let data = std::fs::read(format!("testdata/{name}.class")).unwrap();
+            assert_eq!(class_file(name).to_bytes().unwrap(), data, "{name}");
+        }
+    }
+}
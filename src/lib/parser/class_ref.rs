@@ -0,0 +1,550 @@
+use anyhow::{bail, Context, Result};
+use std::{borrow::Cow, cell::OnceCell, io::Cursor};
+
+use crate::ClassIdentifier;
+
+use super::{
+    class::{ClassFile, Version},
+    constant_pool::ConstantPoolInfo,
+    error::ClassFormatError,
+    mutf8::JavaString,
+};
+
+/// A class file borrowed from its bytes, for tools that scan many classes.
+///
+/// [`ClassFileRef::new`] only locates the constant pool entries and checks the class header,
+/// the fields, methods and attributes tables are checked the first time they are asked for,
+/// and the constants when they are decoded. Names are borrowed from the class file unless
+/// their modified UTF-8 differs from standard UTF-8.
+#[derive(Clone, Debug)]
+pub struct ClassFileRef<'a> {
+    data: &'a [u8],
+    class: ClassIdentifier,
+    /// Offset of the tag of each constant pool entry, zero for index 0 and the unusable slots
+    /// behind Long and Double.
+    constant_offsets: Vec<usize>,
+    /// Offset of access_flags, which follows the constant pool.
+    class_offset: usize,
+    fields_offset: usize,
+    /// Found by walking the tables before them, once they are needed.
+    methods_offset: OnceCell<usize>,
+    attributes_offset: OnceCell<usize>,
+    /// Set once the class attributes are checked to end the class file.
+    end_checked: OnceCell<()>,
+}
+
+impl<'a> ClassFileRef<'a> {
+    pub fn new(data: &'a [u8]) -> Result<Self, ClassFormatError> {
+        let bounds = Bounds {
+            data,
+            class: ClassIdentifier::default(),
+        };
+
+        let magic = bounds.u32(0, "class file")?;
+        if magic != 0xCAFE_BABE {
+            return Err(bounds.malformed(
+                4,
+                "class file",
+                format!("incompatible magic value {magic:#x}"),
+            ));
+        }
+
+        let count = bounds.u16(8, "constant pool")? as usize;
+        let mut constant_offsets = vec![0; count];
+        let mut offset = 10;
+        let mut i = 1;
+        while i < count {
+            let context = || format!("constant pool entry {i}");
+            constant_offsets[i] = offset;
+            let tag = bounds.u8(offset, &context())?;
+            offset += match tag {
+                1 => 3 + bounds.u16(offset + 1, &context())? as usize,
+                3 | 4 | 9 | 10 | 11 | 12 | 17 | 18 => 5,
+                5 | 6 => 9,
+                7 | 8 | 16 | 19 | 20 => 3,
+                15 => 4,
+                t => {
+                    return Err(bounds.malformed(
+                        offset + 1,
+                        &context(),
+                        format!("invalid constant pool tag {t}"),
+                    ))
+                }
+            };
+            i += if matches!(tag, 5 | 6) { 2 } else { 1 };
+        }
+        bounds.check(offset, "constant pool")?;
+
+        let class_offset = offset;
+        let interfaces_count = bounds.u16(class_offset + 6, "class")? as usize;
+        let fields_offset = class_offset + 8 + 2 * interfaces_count;
+        bounds.check(fields_offset, "interfaces")?;
+
+        let mut class_ref = Self {
+            data,
+            class: ClassIdentifier::default(),
+            constant_offsets,
+            class_offset,
+            fields_offset,
+            methods_offset: OnceCell::new(),
+            attributes_offset: OnceCell::new(),
+            end_checked: OnceCell::new(),
+        };
+
+        // errors from here on name the class
+        let this_class = class_ref
+            .this_class()
+            .ok()
+            .and_then(|name| ClassIdentifier::from_utf8(name.into_owned()))
+            .ok_or_else(|| {
+                bounds.malformed(
+                    class_offset + 2,
+                    "this class",
+                    "this_class is not a Class entry".to_string(),
+                )
+            })?;
+        class_ref.class = this_class;
+
+        Ok(class_ref)
+    }
+
+    /// Parses the whole class file into the owned model.
+    pub fn to_class_file(&self) -> Result<ClassFile, ClassFormatError> {
+        ClassFile::from_bytes(self.data)
+    }
+
+    pub fn version(&self) -> Version {
+        Version::new(self.u16(6), self.u16(4))
+    }
+
+    pub fn access_flags(&self) -> u16 {
+        self.u16(self.class_offset)
+    }
+
+    /// Name of the class in internal form.
+    pub fn this_class(&self) -> Result<Cow<'a, str>> {
+        self.class_name(self.u16(self.class_offset + 2))
+    }
+
+    /// Name of the super class in internal form, `None` for `java/lang/Object` and modules.
+    pub fn super_class(&self) -> Result<Option<Cow<'a, str>>> {
+        match self.u16(self.class_offset + 4) {
+            0 => Ok(None),
+            index => self.class_name(index).map(Some),
+        }
+    }
+
+    /// Names of the direct super interfaces in internal form.
+    pub fn interfaces(&self) -> impl Iterator<Item = Result<Cow<'a, str>>> + '_ {
+        let count = self.u16(self.class_offset + 6) as usize;
+        (0..count).map(|i| self.class_name(self.u16(self.class_offset + 8 + 2 * i)))
+    }
+
+    /// The fields, checking the fields table on first use.
+    pub fn fields(&self) -> Result<Members<'a>, ClassFormatError> {
+        self.methods_offset()?;
+        Ok(Members::new(self.data, self.fields_offset))
+    }
+
+    /// The methods, checking the fields and methods tables on first use.
+    pub fn methods(&self) -> Result<Members<'a>, ClassFormatError> {
+        self.attributes_offset()?;
+        Ok(Members::new(self.data, self.methods_offset()?))
+    }
+
+    /// The class attributes, checking them and that they end the class file on first use.
+    pub fn attributes(&self) -> Result<Attributes<'a>, ClassFormatError> {
+        let offset = self.attributes_offset()?;
+        if self.end_checked.get().is_none() {
+            let end = self.bounds().attributes(offset, "class attributes")?;
+            if end != self.data.len() {
+                return Err(self.bounds().malformed(
+                    end,
+                    "end of class file",
+                    format!("{} extra bytes", self.data.len() - end),
+                ));
+            }
+            self.end_checked.get_or_init(|| ());
+        }
+
+        Ok(Attributes::new(self.data, offset))
+    }
+
+    fn methods_offset(&self) -> Result<usize, ClassFormatError> {
+        if let Some(offset) = self.methods_offset.get() {
+            return Ok(*offset);
+        }
+
+        let offset = self.bounds().members(self.fields_offset, "field")?;
+        Ok(*self.methods_offset.get_or_init(|| offset))
+    }
+
+    fn attributes_offset(&self) -> Result<usize, ClassFormatError> {
+        if let Some(offset) = self.attributes_offset.get() {
+            return Ok(*offset);
+        }
+
+        let offset = self.bounds().members(self.methods_offset()?, "method")?;
+        Ok(*self.attributes_offset.get_or_init(|| offset))
+    }
+
+    fn bounds(&self) -> Bounds<'a> {
+        Bounds {
+            data: self.data,
+            class: self.class.clone(),
+        }
+    }
+
+    /// Number of constant pool slots, one more than the highest index.
+    pub fn constant_pool_count(&self) -> usize {
+        self.constant_offsets.len()
+    }
+
+    /// Decodes the constant pool entry at `index`.
+    pub fn constant(&self, index: u16) -> Result<ConstantPoolInfo> {
+        let mut c = Cursor::new(self.data);
+        c.set_position(self.constant_offset(index)? as u64);
+        ConstantPoolInfo::new(&mut c)
+    }
+
+    /// The text of the Utf8 entry at `index`, with unpaired surrogates escaped as by
+    /// [`JavaString`]'s `Display`.
+    pub fn utf8(&self, index: u16) -> Result<Cow<'a, str>> {
+        let offset = self.constant_offset(index)?;
+        if self.data[offset] != 1 {
+            bail!("#{index} is not a Utf8 entry");
+        }
+
+        let length = self.u16(offset + 1) as usize;
+        let bytes = &self.data[offset + 3..offset + 3 + length];
+        // modified UTF-8 without NUL and supplementary characters is standard UTF-8
+        if !bytes.iter().any(|b| *b == 0 || *b >= 0xf0) {
+            if let Ok(text) = std::str::from_utf8(bytes) {
+                return Ok(Cow::Borrowed(text));
+            }
+        }
+
+        Ok(Cow::Owned(
+            JavaString::from_modified_utf8(bytes)
+                .with_context(|| format!("#{index}"))?
+                .to_string(),
+        ))
+    }
+
+    /// The name of the Class entry at `index` in internal form.
+    pub fn class_name(&self, index: u16) -> Result<Cow<'a, str>> {
+        let offset = self.constant_offset(index)?;
+        if self.data[offset] != 7 {
+            bail!("#{index} is not a Class entry");
+        }
+
+        self.utf8(self.u16(offset + 1))
+    }
+
+    fn constant_offset(&self, index: u16) -> Result<usize> {
+        match self.constant_offsets.get(index as usize) {
+            Some(offset) if *offset != 0 => Ok(*offset),
+            _ => bail!("#{index} is not a valid constant pool index"),
+        }
+    }
+
+    fn u16(&self, offset: usize) -> u16 {
+        u16_at(self.data, offset)
+    }
+}
+
+/// A field_info or method_info structure borrowed from a [`ClassFileRef`].
+#[derive(Clone, Copy, Debug)]
+pub struct MemberRef<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> MemberRef<'a> {
+    pub fn access_flags(&self) -> u16 {
+        u16_at(self.data, self.offset)
+    }
+
+    pub fn name_index(&self) -> u16 {
+        u16_at(self.data, self.offset + 2)
+    }
+
+    pub fn descriptor_index(&self) -> u16 {
+        u16_at(self.data, self.offset + 4)
+    }
+
+    pub fn attributes(&self) -> Attributes<'a> {
+        Attributes::new(self.data, self.offset + 6)
+    }
+}
+
+/// Iterator over the fields or methods of a [`ClassFileRef`].
+#[derive(Clone, Debug)]
+pub struct Members<'a> {
+    data: &'a [u8],
+    offset: usize,
+    remaining: u16,
+}
+
+impl<'a> Members<'a> {
+    fn new(data: &'a [u8], offset: usize) -> Self {
+        Self {
+            data,
+            offset: offset + 2,
+            remaining: u16_at(data, offset),
+        }
+    }
+}
+
+impl<'a> Iterator for Members<'a> {
+    type Item = MemberRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let member = MemberRef {
+            data: self.data,
+            offset: self.offset,
+        };
+        let mut attributes = member.attributes();
+        attributes.by_ref().for_each(drop);
+        self.offset = attributes.offset;
+        self.remaining -= 1;
+        Some(member)
+    }
+}
+
+/// An attribute borrowed from a [`ClassFileRef`], its `info` undecoded.
+#[derive(Clone, Copy, Debug)]
+pub struct AttributeRef<'a> {
+    pub name_index: u16,
+    pub info: &'a [u8],
+}
+
+/// Iterator over the attributes of a class, member or Code attribute.
+#[derive(Clone, Debug)]
+pub struct Attributes<'a> {
+    data: &'a [u8],
+    offset: usize,
+    remaining: u16,
+}
+
+impl<'a> Attributes<'a> {
+    fn new(data: &'a [u8], offset: usize) -> Self {
+        Self {
+            data,
+            offset: offset + 2,
+            remaining: u16_at(data, offset),
+        }
+    }
+}
+
+impl<'a> Iterator for Attributes<'a> {
+    type Item = AttributeRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let name_index = u16_at(self.data, self.offset);
+        let length = u32_at(self.data, self.offset + 2) as usize;
+        let start = self.offset + 6;
+        self.offset = start + length;
+        self.remaining -= 1;
+        Some(AttributeRef {
+            name_index,
+            info: &self.data[start..start + length],
+        })
+    }
+}
+
+/// Reads a u16 at an offset [`ClassFileRef`] has checked.
+fn u16_at(data: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([data[offset], data[offset + 1]])
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+/// Bounds checked reads of a class file, failing with a [`ClassFormatError`] for `class`.
+struct Bounds<'a> {
+    data: &'a [u8],
+    class: ClassIdentifier,
+}
+
+impl Bounds<'_> {
+    fn malformed(&self, offset: usize, context: &str, cause: String) -> ClassFormatError {
+        ClassFormatError::Malformed {
+            class: self.class.clone(),
+            offset: Some(offset as u64),
+            context: context.to_string(),
+            cause,
+        }
+    }
+
+    fn check(&self, end: usize, context: &str) -> Result<(), ClassFormatError> {
+        if end > self.data.len() {
+            return Err(ClassFormatError::Truncated {
+                class: self.class.clone(),
+                offset: self.data.len() as u64,
+                context: context.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn u8(&self, offset: usize, context: &str) -> Result<u8, ClassFormatError> {
+        self.check(offset + 1, context)?;
+        Ok(self.data[offset])
+    }
+
+    fn u16(&self, offset: usize, context: &str) -> Result<u16, ClassFormatError> {
+        self.check(offset + 2, context)?;
+        Ok(u16_at(self.data, offset))
+    }
+
+    fn u32(&self, offset: usize, context: &str) -> Result<u32, ClassFormatError> {
+        self.check(offset + 4, context)?;
+        Ok(u32_at(self.data, offset))
+    }
+
+    /// The offset following a fields or methods table.
+    fn members(&self, offset: usize, kind: &str) -> Result<usize, ClassFormatError> {
+        let count = self.u16(offset, &format!("{kind}s"))?;
+        let mut offset = offset + 2;
+        for i in 0..count {
+            let context = format!("{kind} {i}");
+            self.check(offset + 6, &context)?;
+            offset = self.attributes(offset + 6, &context)?;
+        }
+
+        Ok(offset)
+    }
+
+    /// The offset following an attributes table.
+    fn attributes(&self, offset: usize, context: &str) -> Result<usize, ClassFormatError> {
+        let count = self.u16(offset, context)?;
+        let mut offset = offset + 2;
+        for i in 0..count {
+            let context = format!("{context}, attribute {i}");
+            offset += 6 + self.u32(offset + 2, &context)? as usize;
+            self.check(offset, &context)?;
+        }
+
+        Ok(offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::class::ClassFile;
+
+    use super::{ClassFileRef, ClassFormatError};
+
+    #[test]
+    fn test_matches_class_file() {
+        for name in ["Main", "Annotated", "Shape", "Shape$Circle"] {
+            let data = std::fs::read(format!("testdata/{name}.class")).unwrap();
+            let class_ref = ClassFileRef::new(&data).unwrap();
+            let class_file = ClassFile::from_bytes(&data).unwrap();
+            let cp = &class_file.constant_pool;
+
+            assert_eq!(class_ref.version(), class_file.version);
+            assert_eq!(class_ref.this_class().unwrap(), name);
+            assert_eq!(
+                class_ref.super_class().unwrap().as_deref(),
+                cp.class_name(&class_file.super_class_index).as_deref()
+            );
+            assert_eq!(class_ref.interfaces().count(), class_file.interfaces.len());
+
+            let methods: Vec<_> = class_ref
+                .methods()
+                .unwrap()
+                .map(|m| {
+                    (
+                        class_ref.utf8(m.name_index()).unwrap(),
+                        class_ref.utf8(m.descriptor_index()).unwrap(),
+                        m.attributes().count(),
+                    )
+                })
+                .collect();
+            let expected: Vec<_> = class_file
+                .methods
+                .iter()
+                .map(|m| {
+                    (
+                        cp.utf8(&m.name_index).unwrap(),
+                        cp.utf8(&m.descriptor_index).unwrap(),
+                        m.attributes.len(),
+                    )
+                })
+                .collect();
+            assert_eq!(methods.len(), expected.len());
+            for (method, expected) in methods.iter().zip(&expected) {
+                assert_eq!(method.0, expected.0);
+                assert_eq!(method.1, expected.1);
+                assert_eq!(method.2, expected.2);
+            }
+
+            assert_eq!(class_ref.fields().unwrap().count(), class_file.fields.len());
+            assert_eq!(
+                class_ref.attributes().unwrap().count(),
+                class_file.attributes.len()
+            );
+            for (index, info) in cp.iter() {
+                assert_eq!(class_ref.constant(index.value() as u16).unwrap(), *info);
+            }
+        }
+    }
+
+    #[test]
+    fn test_borrowed_names() {
+        let data = std::fs::read("testdata/Main.class").unwrap();
+        let class_ref = ClassFileRef::new(&data).unwrap();
+        assert!(matches!(
+            class_ref.this_class().unwrap(),
+            std::borrow::Cow::Borrowed("Main")
+        ));
+    }
+
+    #[test]
+    fn test_truncated() {
+        let data = std::fs::read("testdata/Main.class").unwrap();
+        assert!(matches!(
+            ClassFileRef::new(&data[..100]),
+            Err(ClassFormatError::Truncated { offset: 100, .. })
+        ));
+
+        // the tables after the class header are only checked when asked for
+        let class_ref = ClassFileRef::new(&data[..data.len() - 1]).unwrap();
+        assert!(class_ref.fields().is_ok());
+        match class_ref.attributes() {
+            Err(ClassFormatError::Truncated { class, .. }) => {
+                assert_eq!(class.to_string(), "<default>.Main")
+            }
+            result => panic!("expected a truncated class file, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_trailing_data() {
+        let mut data = std::fs::read("testdata/Main.class").unwrap();
+        data.push(0);
+        let class_ref = ClassFileRef::new(&data).unwrap();
+        assert!(class_ref.methods().is_ok());
+        assert!(matches!(
+            class_ref.attributes(),
+            Err(ClassFormatError::Malformed { context, .. }) if context == "end of class file"
+        ));
+    }
+}
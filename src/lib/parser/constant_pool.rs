@@ -1,11 +1,13 @@
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use std::{fmt::Display, io::Cursor};
 
 use crate::ClassIdentifier;
 
 use super::{
     descriptor::{Descriptor, FieldType, MethodDescriptor},
-    parse_f32, parse_i32, parse_u16, parse_u32, parse_u8, parse_vec,
+    mutf8::JavaString,
+    parse_f32, parse_f64, parse_i32, parse_u16, parse_u32, parse_u8, parse_vec, write_count,
+    write_index, write_u16, write_u8,
 };
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -20,6 +22,7 @@ impl Display for NameAndType {
     }
 }
 
+/// A constant pool entry with the entries it refers to resolved, see [`ConstantPool::resolve`].
 #[derive(Clone, Debug, PartialEq)]
 pub enum ConstantPoolItem {
     Reserved,
@@ -42,10 +45,10 @@ pub enum ConstantPoolItem {
         descriptor: String,
     },
     String {
-        value: String,
+        value: JavaString,
     },
     Utf {
-        text: String,
+        text: JavaString,
     },
     Integer {
         val: i32,
@@ -56,6 +59,13 @@ pub enum ConstantPoolItem {
     Float {
         val: f32,
     },
+    Double {
+        val: f64,
+    },
+    Dynamic {
+        bootstrap_method_attr_index: u16,
+        name_and_type: NameAndType,
+    },
     InvokeDynamic {
         bootstrap_method_attr_index: u16,
         name_and_type: NameAndType,
@@ -64,13 +74,68 @@ pub enum ConstantPoolItem {
         reference_kind: u8,
         reference: Box<ConstantPoolItem>,
     },
+    Module {
+        name: String,
+    },
+    Package {
+        name: String,
+    },
+}
+
+/// The `reference_kind` of a MethodHandle entry, see JVMS 5.4.3.5.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MethodHandleKind {
+    GetField,
+    GetStatic,
+    PutField,
+    PutStatic,
+    InvokeVirtual,
+    InvokeStatic,
+    InvokeSpecial,
+    NewInvokeSpecial,
+    InvokeInterface,
+}
+
+impl MethodHandleKind {
+    pub fn new(reference_kind: u8) -> Option<Self> {
+        Some(match reference_kind {
+            1 => Self::GetField,
+            2 => Self::GetStatic,
+            3 => Self::PutField,
+            4 => Self::PutStatic,
+            5 => Self::InvokeVirtual,
+            6 => Self::InvokeStatic,
+            7 => Self::InvokeSpecial,
+            8 => Self::NewInvokeSpecial,
+            9 => Self::InvokeInterface,
+            _ => return None,
+        })
+    }
+}
+
+impl Display for MethodHandleKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::GetField => write!(f, "REF_getField"),
+            Self::GetStatic => write!(f, "REF_getStatic"),
+            Self::PutField => write!(f, "REF_putField"),
+            Self::PutStatic => write!(f, "REF_putStatic"),
+            Self::InvokeVirtual => write!(f, "REF_invokeVirtual"),
+            Self::InvokeStatic => write!(f, "REF_invokeStatic"),
+            Self::InvokeSpecial => write!(f, "REF_invokeSpecial"),
+            Self::NewInvokeSpecial => write!(f, "REF_newInvokeSpecial"),
+            Self::InvokeInterface => write!(f, "REF_invokeInterface"),
+        }
+    }
 }
 
+/// The constant pool of a class file, indexed from 1 as in the class file.
 #[derive(Clone, Debug, PartialEq)]
 pub struct ConstantPool {
     infos: Vec<ConstantPoolInfo>,
 }
 
+/// A constant pool index as it appears in the class file.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Index {
     index: usize,
@@ -82,49 +147,114 @@ impl Index {
             index: index.into(),
         }
     }
+
+    pub fn value(&self) -> usize {
+        self.index
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.index == 0
+    }
+}
+
+/// An empty pool, holding only the unusable entry at index 0.
+impl Default for ConstantPool {
+    fn default() -> Self {
+        Self {
+            infos: vec![ConstantPoolInfo::Reserved],
+        }
+    }
 }
 
 impl ConstantPool {
-    pub fn new(c: &mut Cursor<&Vec<u8>>, count: usize) -> Result<ConstantPool> {
+    pub fn new(c: &mut Cursor<&[u8]>, count: usize) -> Result<ConstantPool> {
         let mut infos = vec![ConstantPoolInfo::Reserved; count];
 
-        let mut i = 0;
-        loop {
-            let info = ConstantPoolInfo::new(c)?;
-            if matches!(info, ConstantPoolInfo::Long(..)) {
-                i += 2;
-            } else {
-                i += 1;
-            }
-
+        // index 0 is never valid, and the entry following a Long or Double is unusable
+        let mut i = 1;
+        while i < count {
+            let info =
+                ConstantPoolInfo::new(c).with_context(|| format!("constant pool entry #{i}"))?;
+            let slots = if info.is_category_2() { 2 } else { 1 };
             infos[i] = info;
+            i += slots;
+        }
 
-            if i == count - 1 {
-                break;
+        Ok(ConstantPool { infos })
+    }
+
+    /// Index of an entry equal to `info`, appending it if the pool has none.
+    pub fn add(&mut self, info: ConstantPoolInfo) -> Result<Index> {
+        if let Some(i) = self.infos.iter().position(|i| *i == info) {
+            return Ok(Index::new(i));
+        }
+
+        let slots = if info.is_category_2() { 2 } else { 1 };
+        if self.infos.len() + slots > u16::MAX as usize {
+            bail!("constant pool is full");
+        }
+
+        let index = Index::new(self.infos.len());
+        self.infos.push(info);
+        if slots == 2 {
+            self.infos.push(ConstantPoolInfo::Reserved);
+        }
+
+        Ok(index)
+    }
+
+    pub fn write(&self, w: &mut Vec<u8>) -> Result<()> {
+        write_count(w, self.infos.len())?;
+
+        // the unusable entries behind Long and Double are implied by the tag
+        for info in self.infos.iter().skip(1) {
+            if *info != ConstantPoolInfo::Reserved {
+                info.write(w)?;
             }
         }
 
-        Ok(ConstantPool { infos })
+        Ok(())
+    }
+
+    /// Index of the first Utf8 entry with the given text, used to find attribute names.
+    pub fn utf8_index(&self, text: &str) -> Option<Index> {
+        let text = JavaString::from(text);
+        self.infos
+            .iter()
+            .position(|info| matches!(info, ConstantPoolInfo::Utf { text: t } if *t == text))
+            .map(Index::new)
     }
 
-    fn get(&self, index: &Index) -> Option<ConstantPoolInfo> {
-        self.infos.get(index.index).cloned()
+    /// The raw entry at `index`.
+    pub fn get(&self, index: &Index) -> Option<&ConstantPoolInfo> {
+        self.infos.get(index.index)
     }
 
+    /// Usable entries with their indices, skipping index 0 and the slots behind Long and Double.
+    pub fn iter(&self) -> impl Iterator<Item = (Index, &ConstantPoolInfo)> {
+        self.infos
+            .iter()
+            .enumerate()
+            .filter(|(_, info)| **info != ConstantPoolInfo::Reserved)
+            .map(|(i, info)| (Index::new(i), info))
+    }
+
+    /// The entry at `index` with the entries it refers to looked up, `None` if one is missing
+    /// or of the wrong kind.
     pub fn resolve(&self, index: &Index) -> Option<ConstantPoolItem> {
         self.get(index).map(|i| self.resolve_cp_item(i))?
     }
 
-    fn resolve_cp_item(&self, cp_item: ConstantPoolInfo) -> Option<ConstantPoolItem> {
+    fn resolve_cp_item(&self, cp_item: &ConstantPoolInfo) -> Option<ConstantPoolItem> {
         match cp_item {
             ConstantPoolInfo::Reserved => Some(ConstantPoolItem::Reserved),
             ConstantPoolInfo::FieldRef {
                 class_index,
                 name_and_type_index,
             } => {
-                if let ConstantPoolInfo::ClassInfo { name_index } = self.get(&class_index)? {
-                    let class_identifier = ClassIdentifier::from_utf8(self.utf8(&name_index)?)?;
-                    let name_and_type = self.name_and_type_field(&name_and_type_index)?;
+                if let ConstantPoolInfo::ClassInfo { name_index } = self.get(class_index)? {
+                    let class_identifier = ClassIdentifier::from_utf8(self.utf8(name_index)?)?;
+                    let name_and_type = self.name_and_type_field(name_and_type_index)?;
 
                     Some(ConstantPoolItem::FieldRef {
                         class_identifier,
@@ -138,9 +268,9 @@ impl ConstantPool {
                 class_index,
                 name_and_type_index,
             } => {
-                if let ConstantPoolInfo::ClassInfo { name_index } = self.get(&class_index)? {
-                    let class_identifier = ClassIdentifier::from_utf8(self.utf8(&name_index)?)?;
-                    let name_and_type = self.name_and_type_method(&name_and_type_index)?;
+                if let ConstantPoolInfo::ClassInfo { name_index } = self.get(class_index)? {
+                    let class_identifier = ClassIdentifier::from_utf8(self.utf8(name_index)?)?;
+                    let name_and_type = self.name_and_type_method(name_and_type_index)?;
 
                     Some(ConstantPoolItem::MethodRef {
                         class_identifier,
@@ -154,9 +284,9 @@ impl ConstantPool {
                 class_index,
                 name_and_type_index,
             } => {
-                if let ConstantPoolInfo::ClassInfo { name_index } = self.get(&class_index)? {
-                    let class_identifier = ClassIdentifier::from_utf8(self.utf8(&name_index)?)?;
-                    let name_and_type = self.name_and_type_field(&name_and_type_index)?;
+                if let ConstantPoolInfo::ClassInfo { name_index } = self.get(class_index)? {
+                    let class_identifier = ClassIdentifier::from_utf8(self.utf8(name_index)?)?;
+                    let name_and_type = self.name_and_type_method(name_and_type_index)?;
 
                     Some(ConstantPoolItem::InterfaceMethodRef {
                         class_identifier,
@@ -167,60 +297,98 @@ impl ConstantPool {
                 }
             }
             ConstantPoolInfo::String { string_index } => Some(ConstantPoolItem::String {
-                value: self.utf8(&string_index)?,
+                value: self.java_string(string_index)?,
             }),
             ConstantPoolInfo::ClassInfo { name_index } => Some(ConstantPoolItem::ClassInfo {
-                identifier: ClassIdentifier::from_utf8(self.utf8(&name_index)?)?,
+                identifier: ClassIdentifier::from_utf8(self.utf8(name_index)?)?,
             }),
             ConstantPoolInfo::NameAndType {
                 name_index: _,
                 descriptor_index: _,
             } => None,
-            ConstantPoolInfo::Utf { text } => Some(ConstantPoolItem::Utf { text }),
+            ConstantPoolInfo::Utf { text } => Some(ConstantPoolItem::Utf { text: text.clone() }),
             ConstantPoolInfo::InvokeDynamic {
                 bootstrap_method_attr_index,
                 name_and_type_index,
             } => Some(ConstantPoolItem::InvokeDynamic {
-                bootstrap_method_attr_index,
-                name_and_type: self.name_and_type_method(&name_and_type_index)?,
+                bootstrap_method_attr_index: *bootstrap_method_attr_index,
+                name_and_type: self.name_and_type_method(name_and_type_index)?,
             }),
-            ConstantPoolInfo::Integer(val) => Some(ConstantPoolItem::Integer { val }),
+            ConstantPoolInfo::Integer(val) => Some(ConstantPoolItem::Integer { val: *val }),
             ConstantPoolInfo::MethodHandle {
                 reference_kind,
                 reference_index,
             } => Some(ConstantPoolItem::MethodHandle {
-                reference_kind,
-                reference: Box::new(self.resolve(&reference_index)?),
+                reference_kind: *reference_kind,
+                reference: Box::new(self.resolve(reference_index)?),
             }),
             ConstantPoolInfo::MethodType { descriptor_index } => {
                 Some(ConstantPoolItem::MethodType {
-                    descriptor: self.utf8(&descriptor_index)?,
+                    descriptor: self.utf8(descriptor_index)?,
                 })
             }
-            ConstantPoolInfo::Long(val) => Some(ConstantPoolItem::Long { val }),
-            ConstantPoolInfo::Float(val) => Some(ConstantPoolItem::Float { val }),
+            ConstantPoolInfo::Long(val) => Some(ConstantPoolItem::Long { val: *val }),
+            ConstantPoolInfo::Float(val) => Some(ConstantPoolItem::Float { val: *val }),
+            ConstantPoolInfo::Double(val) => Some(ConstantPoolItem::Double { val: *val }),
+            ConstantPoolInfo::Dynamic {
+                bootstrap_method_attr_index,
+                name_and_type_index,
+            } => Some(ConstantPoolItem::Dynamic {
+                bootstrap_method_attr_index: *bootstrap_method_attr_index,
+                name_and_type: self.name_and_type_field(name_and_type_index)?,
+            }),
+            ConstantPoolInfo::Module { name_index } => Some(ConstantPoolItem::Module {
+                name: self.utf8(name_index)?,
+            }),
+            ConstantPoolInfo::Package { name_index } => Some(ConstantPoolItem::Package {
+                name: self.utf8(name_index)?,
+            }),
         }
     }
 
     // TODO: return Result instead
+    /// The text of a Utf8 entry, `None` as well if it contains unpaired surrogates.
     pub fn utf8(&self, index: &Index) -> Option<String> {
+        self.java_string(index)?.as_string()
+    }
+
+    /// The text of a Utf8 entry as UTF-16, which keeps unpaired surrogates.
+    pub fn java_string(&self, index: &Index) -> Option<JavaString> {
         if let ConstantPoolInfo::Utf { text } = self.infos.get(index.index)? {
-            Some(text.to_string())
+            Some(text.clone())
+        } else {
+            None
+        }
+    }
+
+    /// The name of a Class entry in internal form, or the descriptor of an array class.
+    pub fn class_name(&self, index: &Index) -> Option<String> {
+        if let ConstantPoolInfo::ClassInfo { name_index } = self.infos.get(index.index)? {
+            self.utf8(name_index)
+        } else {
+            None
+        }
+    }
+
+    pub fn class_identifier(&self, index: &Index) -> Option<ClassIdentifier> {
+        if let ConstantPoolInfo::ClassInfo { name_index } = self.infos.get(index.index)? {
+            ClassIdentifier::from_utf8(self.utf8(name_index)?)
         } else {
             None
         }
     }
 
-    fn name_and_type_method(&self, index: &Index) -> Option<NameAndType> {
+    /// The NameAndType entry at `index` with a method descriptor.
+    pub fn name_and_type_method(&self, index: &Index) -> Option<NameAndType> {
         if let ConstantPoolInfo::NameAndType {
             name_index,
             descriptor_index,
         } = self.get(index)?
         {
             Some(NameAndType {
-                name: self.utf8(&name_index)?,
+                name: self.utf8(name_index)?,
                 descriptor: Descriptor::Method(
-                    MethodDescriptor::new(&self.utf8(&descriptor_index)?).ok()?,
+                    MethodDescriptor::new(&self.utf8(descriptor_index)?).ok()?,
                 ),
             })
         } else {
@@ -235,8 +403,8 @@ impl ConstantPool {
         } = self.get(index)?
         {
             Some(NameAndType {
-                name: self.utf8(&name_index)?,
-                descriptor: Descriptor::Field(FieldType::new(&self.utf8(&descriptor_index)?).ok()?),
+                name: self.utf8(name_index)?,
+                descriptor: Descriptor::Field(FieldType::new(&self.utf8(descriptor_index)?).ok()?),
             })
         } else {
             None
@@ -244,6 +412,7 @@ impl ConstantPool {
     }
 }
 
+/// A constant pool entry as stored in the class file, referring to other entries by index.
 #[derive(Clone, Debug, PartialEq)]
 pub enum ConstantPoolInfo {
     Reserved,
@@ -270,7 +439,7 @@ pub enum ConstantPoolInfo {
         descriptor_index: Index,
     },
     Utf {
-        text: String,
+        text: JavaString,
     },
     InvokeDynamic {
         bootstrap_method_attr_index: u16,
@@ -286,10 +455,61 @@ pub enum ConstantPoolInfo {
     },
     Long(i64),
     Float(f32),
+    Double(f64),
+    Dynamic {
+        bootstrap_method_attr_index: u16,
+        name_and_type_index: Index,
+    },
+    Module {
+        name_index: Index,
+    },
+    Package {
+        name_index: Index,
+    },
 }
 
 impl ConstantPoolInfo {
-    fn new(c: &mut Cursor<&Vec<u8>>) -> Result<ConstantPoolInfo> {
+    /// The name of the entry's tag as JVMS 4.4 spells it, without the `CONSTANT_` prefix.
+    pub fn tag_name(&self) -> &'static str {
+        match self {
+            Self::Reserved => "Reserved",
+            Self::FieldRef { .. } => "Fieldref",
+            Self::MethodRef { .. } => "Methodref",
+            Self::InterfaceMethodRef { .. } => "InterfaceMethodref",
+            Self::String { .. } => "String",
+            Self::ClassInfo { .. } => "Class",
+            Self::NameAndType { .. } => "NameAndType",
+            Self::Utf { .. } => "Utf8",
+            Self::InvokeDynamic { .. } => "InvokeDynamic",
+            Self::Integer(_) => "Integer",
+            Self::MethodHandle { .. } => "MethodHandle",
+            Self::MethodType { .. } => "MethodType",
+            Self::Long(_) => "Long",
+            Self::Float(_) => "Float",
+            Self::Double(_) => "Double",
+            Self::Dynamic { .. } => "Dynamic",
+            Self::Module { .. } => "Module",
+            Self::Package { .. } => "Package",
+        }
+    }
+
+    /// Whether `ldc` and bootstrap arguments may refer to this entry (JVMS 4.4, table 4.4-C).
+    pub fn is_loadable(&self) -> bool {
+        matches!(
+            self,
+            Self::Integer(_)
+                | Self::Float(_)
+                | Self::Long(_)
+                | Self::Double(_)
+                | Self::ClassInfo { .. }
+                | Self::String { .. }
+                | Self::MethodHandle { .. }
+                | Self::MethodType { .. }
+                | Self::Dynamic { .. }
+        )
+    }
+
+    pub(crate) fn new(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
         let tag = parse_u8(c)?;
 
         match tag {
@@ -297,6 +517,7 @@ impl ConstantPoolInfo {
             3 => ConstantPoolInfo::integer(c),
             4 => ConstantPoolInfo::float(c),
             5 => ConstantPoolInfo::long(c),
+            6 => ConstantPoolInfo::double(c),
             7 => ConstantPoolInfo::class_info(c),
             8 => ConstantPoolInfo::string(c),
             9 => ConstantPoolInfo::field_ref(c),
@@ -305,70 +526,193 @@ impl ConstantPoolInfo {
             12 => ConstantPoolInfo::name_and_type(c),
             15 => ConstantPoolInfo::method_handle(c),
             16 => ConstantPoolInfo::method_type(c),
+            17 => ConstantPoolInfo::dynamic(c),
             18 => ConstantPoolInfo::invoke_dynamic(c),
+            19 => ConstantPoolInfo::module(c),
+            20 => ConstantPoolInfo::package(c),
             t => bail!("invalid constant pool tag {t}"),
         }
     }
 
-    fn class_info(c: &mut Cursor<&Vec<u8>>) -> Result<ConstantPoolInfo> {
+    fn write(&self, w: &mut Vec<u8>) -> Result<()> {
+        match self {
+            ConstantPoolInfo::Reserved => bail!("reserved constant pool entries cannot be written"),
+            ConstantPoolInfo::Utf { text } => {
+                let bytes = text.to_modified_utf8();
+                write_u8(w, 1);
+                write_count(w, bytes.len())?;
+                w.extend(bytes);
+            }
+            ConstantPoolInfo::Integer(val) => {
+                write_u8(w, 3);
+                w.extend(val.to_be_bytes());
+            }
+            ConstantPoolInfo::Float(val) => {
+                write_u8(w, 4);
+                w.extend(val.to_be_bytes());
+            }
+            ConstantPoolInfo::Long(val) => {
+                write_u8(w, 5);
+                w.extend(val.to_be_bytes());
+            }
+            ConstantPoolInfo::Double(val) => {
+                write_u8(w, 6);
+                w.extend(val.to_be_bytes());
+            }
+            ConstantPoolInfo::ClassInfo { name_index } => {
+                write_u8(w, 7);
+                write_index(w, name_index)?;
+            }
+            ConstantPoolInfo::String { string_index } => {
+                write_u8(w, 8);
+                write_index(w, string_index)?;
+            }
+            ConstantPoolInfo::FieldRef {
+                class_index,
+                name_and_type_index,
+            } => {
+                write_u8(w, 9);
+                write_index(w, class_index)?;
+                write_index(w, name_and_type_index)?;
+            }
+            ConstantPoolInfo::MethodRef {
+                class_index,
+                name_and_type_index,
+            } => {
+                write_u8(w, 10);
+                write_index(w, class_index)?;
+                write_index(w, name_and_type_index)?;
+            }
+            ConstantPoolInfo::InterfaceMethodRef {
+                class_index,
+                name_and_type_index,
+            } => {
+                write_u8(w, 11);
+                write_index(w, class_index)?;
+                write_index(w, name_and_type_index)?;
+            }
+            ConstantPoolInfo::NameAndType {
+                name_index,
+                descriptor_index,
+            } => {
+                write_u8(w, 12);
+                write_index(w, name_index)?;
+                write_index(w, descriptor_index)?;
+            }
+            ConstantPoolInfo::MethodHandle {
+                reference_kind,
+                reference_index,
+            } => {
+                write_u8(w, 15);
+                write_u8(w, *reference_kind);
+                write_index(w, reference_index)?;
+            }
+            ConstantPoolInfo::MethodType { descriptor_index } => {
+                write_u8(w, 16);
+                write_index(w, descriptor_index)?;
+            }
+            ConstantPoolInfo::Dynamic {
+                bootstrap_method_attr_index,
+                name_and_type_index,
+            } => {
+                write_u8(w, 17);
+                write_u16(w, *bootstrap_method_attr_index);
+                write_index(w, name_and_type_index)?;
+            }
+            ConstantPoolInfo::InvokeDynamic {
+                bootstrap_method_attr_index,
+                name_and_type_index,
+            } => {
+                write_u8(w, 18);
+                write_u16(w, *bootstrap_method_attr_index);
+                write_index(w, name_and_type_index)?;
+            }
+            ConstantPoolInfo::Module { name_index } => {
+                write_u8(w, 19);
+                write_index(w, name_index)?;
+            }
+            ConstantPoolInfo::Package { name_index } => {
+                write_u8(w, 20);
+                write_index(w, name_index)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_category_2(&self) -> bool {
+        matches!(
+            self,
+            ConstantPoolInfo::Long(..) | ConstantPoolInfo::Double(..)
+        )
+    }
+
+    fn class_info(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
         Ok(ConstantPoolInfo::ClassInfo {
             name_index: Index::new(parse_u16(c)?),
         })
     }
 
-    fn method_ref(c: &mut Cursor<&Vec<u8>>) -> Result<ConstantPoolInfo> {
+    fn method_ref(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
         Ok(ConstantPoolInfo::MethodRef {
             class_index: Index::new(parse_u16(c)?),
             name_and_type_index: Index::new(parse_u16(c)?),
         })
     }
 
-    fn interface_method_ref(c: &mut Cursor<&Vec<u8>>) -> Result<ConstantPoolInfo> {
+    fn interface_method_ref(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
         Ok(ConstantPoolInfo::InterfaceMethodRef {
             class_index: Index::new(parse_u16(c)?),
             name_and_type_index: Index::new(parse_u16(c)?),
         })
     }
 
-    fn field_ref(c: &mut Cursor<&Vec<u8>>) -> Result<ConstantPoolInfo> {
+    fn field_ref(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
         Ok(ConstantPoolInfo::FieldRef {
             class_index: Index::new(parse_u16(c)?),
             name_and_type_index: Index::new(parse_u16(c)?),
         })
     }
 
-    fn string(c: &mut Cursor<&Vec<u8>>) -> Result<ConstantPoolInfo> {
+    fn string(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
         Ok(ConstantPoolInfo::String {
             string_index: Index::new(parse_u16(c)?),
         })
     }
 
-    fn name_and_type(c: &mut Cursor<&Vec<u8>>) -> Result<ConstantPoolInfo> {
+    fn name_and_type(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
         Ok(ConstantPoolInfo::NameAndType {
             name_index: Index::new(parse_u16(c)?),
             descriptor_index: Index::new(parse_u16(c)?),
         })
     }
 
-    fn utf8(c: &mut Cursor<&Vec<u8>>) -> Result<ConstantPoolInfo> {
+    fn utf8(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
         let length = parse_u16(c)? as usize;
-        let text = String::from_utf8(parse_vec(c, length)?)?;
+        let text = JavaString::from_modified_utf8(&parse_vec(c, length)?)?;
 
         Ok(ConstantPoolInfo::Utf { text })
     }
 
-    fn invoke_dynamic(c: &mut Cursor<&Vec<u8>>) -> Result<ConstantPoolInfo> {
+    fn invoke_dynamic(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
         Ok(ConstantPoolInfo::InvokeDynamic {
             bootstrap_method_attr_index: parse_u16(c)?,
             name_and_type_index: Index::new(parse_u16(c)?),
         })
     }
 
-    fn integer(c: &mut Cursor<&Vec<u8>>) -> Result<ConstantPoolInfo> {
+    fn dynamic(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
+        Ok(ConstantPoolInfo::Dynamic {
+            bootstrap_method_attr_index: parse_u16(c)?,
+            name_and_type_index: Index::new(parse_u16(c)?),
+        })
+    }
+
+    fn integer(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
         Ok(ConstantPoolInfo::Integer(parse_i32(c)?))
     }
 
-    fn method_handle(c: &mut Cursor<&Vec<u8>>) -> Result<ConstantPoolInfo> {
+    fn method_handle(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
         let reference_kind = parse_u8(c)?;
 
         if !(reference_kind > 0 && reference_kind < 10) {
@@ -381,19 +725,111 @@ impl ConstantPoolInfo {
         })
     }
 
-    fn method_type(c: &mut Cursor<&Vec<u8>>) -> Result<ConstantPoolInfo> {
+    fn method_type(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
         Ok(ConstantPoolInfo::MethodType {
             descriptor_index: Index::new(parse_u16(c)?),
         })
     }
 
-    fn long(c: &mut Cursor<&Vec<u8>>) -> Result<ConstantPoolInfo> {
+    fn long(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
         let high_bytes = parse_u32(c)? as i64;
         let low_bytes = parse_u32(c)? as i64;
-        Ok(ConstantPoolInfo::Long((high_bytes << 8) | low_bytes))
+        Ok(ConstantPoolInfo::Long((high_bytes << 32) | low_bytes))
     }
 
-    fn float(c: &mut Cursor<&Vec<u8>>) -> Result<ConstantPoolInfo> {
+    fn float(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
         Ok(ConstantPoolInfo::Float(parse_f32(c)?))
     }
+
+    fn double(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
+        Ok(ConstantPoolInfo::Double(parse_f64(c)?))
+    }
+
+    fn module(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
+        Ok(ConstantPoolInfo::Module {
+            name_index: Index::new(parse_u16(c)?),
+        })
+    }
+
+    fn package(c: &mut Cursor<&[u8]>) -> Result<ConstantPoolInfo> {
+        Ok(ConstantPoolInfo::Package {
+            name_index: Index::new(parse_u16(c)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::parser::descriptor::{Descriptor, FieldType};
+
+    use super::{ConstantPool, ConstantPoolItem, Index, NameAndType};
+
+    fn utf8(bytes: &mut Vec<u8>, text: &str) {
+        bytes.push(1);
+        bytes.extend((text.len() as u16).to_be_bytes());
+        bytes.extend(text.as_bytes());
+    }
+
+    #[test]
+    fn test_category_2_and_module_entries() {
+        let mut bytes = vec![5];
+        bytes.extend(0x0000_0001_0000_0002i64.to_be_bytes());
+        bytes.push(6);
+        bytes.extend(1.5f64.to_be_bytes());
+        utf8(&mut bytes, "java.base");
+        bytes.extend([19, 0, 5]);
+        utf8(&mut bytes, "java/lang");
+        bytes.extend([20, 0, 7]);
+        utf8(&mut bytes, "value");
+        utf8(&mut bytes, "I");
+        bytes.extend([12, 0, 9, 0, 10]);
+        bytes.extend([17, 0, 0, 0, 11]);
+
+        let mut c = Cursor::new(bytes.as_slice());
+        let cp = ConstantPool::new(&mut c, 13).unwrap();
+        assert_eq!(c.position() as usize, bytes.len());
+
+        assert_eq!(
+            cp.resolve(&Index::new(1u16)),
+            Some(ConstantPoolItem::Long {
+                val: 0x0000_0001_0000_0002
+            })
+        );
+        assert_eq!(
+            cp.resolve(&Index::new(2u16)),
+            Some(ConstantPoolItem::Reserved)
+        );
+        assert_eq!(
+            cp.resolve(&Index::new(3u16)),
+            Some(ConstantPoolItem::Double { val: 1.5 })
+        );
+        assert_eq!(
+            cp.resolve(&Index::new(4u16)),
+            Some(ConstantPoolItem::Reserved)
+        );
+        assert_eq!(
+            cp.resolve(&Index::new(6u16)),
+            Some(ConstantPoolItem::Module {
+                name: "java.base".to_string()
+            })
+        );
+        assert_eq!(
+            cp.resolve(&Index::new(8u16)),
+            Some(ConstantPoolItem::Package {
+                name: "java/lang".to_string()
+            })
+        );
+        assert_eq!(
+            cp.resolve(&Index::new(12u16)),
+            Some(ConstantPoolItem::Dynamic {
+                bootstrap_method_attr_index: 0,
+                name_and_type: NameAndType {
+                    name: "value".to_string(),
+                    descriptor: Descriptor::Field(FieldType::Int),
+                }
+            })
+        );
+    }
 }
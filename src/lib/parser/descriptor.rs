@@ -27,6 +27,7 @@ impl Descriptor {
     }
 }
 
+/// A field descriptor such as `I` or `[Ljava/lang/String;`, class names in internal form.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum FieldType {
     Byte,
@@ -116,7 +117,7 @@ impl Display for ReturnDescriptor {
 }
 
 impl ReturnDescriptor {
-    fn new(text: &str) -> Result<Self> {
+    pub fn new(text: &str) -> Result<Self> {
         Ok(if text == "V" {
             Self::Void
         } else {
@@ -125,6 +126,7 @@ impl ReturnDescriptor {
     }
 }
 
+/// A method descriptor such as `(IJ)V`.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct MethodDescriptor {
     pub parameters: Vec<FieldType>,
@@ -0,0 +1,129 @@
+use std::{fmt::Display, io::ErrorKind};
+
+use crate::ClassIdentifier;
+
+use super::class::{Version, MAX_SUPPORTED_MAJOR_VERSION};
+
+/// Why [`ClassFile::new`](super::class::ClassFile::new) rejected a class file, each kind
+/// corresponding to the Java exception it is reported as.
+#[derive(Debug)]
+pub enum ClassFormatError {
+    /// An `UnsupportedClassVersionError`.
+    UnsupportedVersion {
+        class: ClassIdentifier,
+        version: Version,
+    },
+    /// A `NoClassDefFoundError`, the class file declares another class than the one requested.
+    WrongName {
+        class: ClassIdentifier,
+        found: ClassIdentifier,
+    },
+    /// The class file ends inside a structure.
+    Truncated {
+        class: ClassIdentifier,
+        offset: u64,
+        context: String,
+    },
+    /// A structure holds a value the class file format does not allow. The offset is missing
+    /// for failures of the format checks of [`ClassFile::check`](super::class::ClassFile::check),
+    /// which run on the parsed class file.
+    Malformed {
+        class: ClassIdentifier,
+        offset: Option<u64>,
+        context: String,
+        cause: String,
+    },
+}
+
+impl ClassFormatError {
+    /// Builds the error for a failure at `offset`, where `err` carries the structures being
+    /// parsed as its context, outermost first.
+    pub(crate) fn new(class: ClassIdentifier, offset: u64, err: anyhow::Error) -> Self {
+        let err = match err.downcast::<ClassFormatError>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+
+        let chain: Vec<String> = err.chain().map(|cause| cause.to_string()).collect();
+        let context = match &chain[..chain.len() - 1] {
+            [] => "class file".to_string(),
+            structures => structures.join(", "),
+        };
+
+        let truncated = err
+            .root_cause()
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|err| err.kind() == ErrorKind::UnexpectedEof);
+        if truncated {
+            return Self::Truncated {
+                class,
+                offset,
+                context,
+            };
+        }
+
+        Self::Malformed {
+            class,
+            offset: Some(offset),
+            context,
+            cause: chain[chain.len() - 1].clone(),
+        }
+    }
+
+    /// The simple name of the Java exception this error is thrown as.
+    pub fn exception(&self) -> &'static str {
+        match self {
+            Self::UnsupportedVersion { .. } => "UnsupportedClassVersionError",
+            Self::WrongName { .. } => "NoClassDefFoundError",
+            Self::Truncated { .. } | Self::Malformed { .. } => "ClassFormatError",
+        }
+    }
+
+    /// Byte offset in the class file where parsing stopped, if the error is about its format.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Self::Truncated { offset, .. } => Some(*offset),
+            Self::Malformed { offset, .. } => *offset,
+            Self::UnsupportedVersion { .. } | Self::WrongName { .. } => None,
+        }
+    }
+}
+
+impl Display for ClassFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: ", self.exception())?;
+        match self {
+            Self::UnsupportedVersion { class, version }
+                if version.major > MAX_SUPPORTED_MAJOR_VERSION =>
+            {
+                write!(f, "{class} has been compiled by a more recent version of the Java Runtime (class file version {version}), this version of the Java Runtime only recognizes class file versions up to {MAX_SUPPORTED_MAJOR_VERSION}.0")
+            }
+            Self::UnsupportedVersion { class, version } => {
+                write!(f, "{class} has unsupported class file version {version}")
+            }
+            Self::WrongName { class, found } => write!(f, "{class} (wrong name: {found})"),
+            Self::Truncated {
+                class,
+                offset,
+                context,
+            } => write!(
+                f,
+                "{class} ({context} at offset {offset}): truncated class file"
+            ),
+            Self::Malformed {
+                class,
+                offset: Some(offset),
+                context,
+                cause,
+            } => write!(f, "{class} ({context} at offset {offset}): {cause}"),
+            Self::Malformed {
+                class,
+                offset: None,
+                context,
+                cause,
+            } => write!(f, "{class} ({context}): {cause}"),
+        }
+    }
+}
+
+impl std::error::Error for ClassFormatError {}
@@ -1,29 +1,65 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::io::Cursor;
 
 use super::{
-    attribute::Attribute,
+    attribute::{annotation::Annotations, Attributes},
     constant_pool::{ConstantPool, Index},
+    descriptor::FieldType,
     parse_u16,
+    signature::{signature, FieldSignature},
+    write_index, write_u16,
 };
 
+/// A field_info structure, whose name and descriptor are constant pool indices.
 #[derive(Clone, Debug)]
 pub struct Field {
-    _access_flags: Vec<FieldFlag>,
+    pub access_flags: Vec<FieldFlag>,
     pub name_index: Index,
     pub descriptor_index: Index,
-    _attributes: Vec<Attribute>,
+    pub attributes: Attributes,
 }
 
 impl Field {
-    pub fn new(c: &mut Cursor<&Vec<u8>>, constant_pool: &ConstantPool) -> Result<Self> {
+    pub fn new(c: &mut Cursor<&[u8]>, constant_pool: &ConstantPool) -> Result<Self> {
         Ok(Self {
-            _access_flags: FieldFlag::flags(parse_u16(c)?),
+            access_flags: FieldFlag::flags(parse_u16(c)?),
             name_index: Index::new(parse_u16(c)?),
             descriptor_index: Index::new(parse_u16(c)?),
-            _attributes: Attribute::attributes(c, constant_pool)?,
+            attributes: Attributes::new(c, constant_pool)?,
         })
     }
+
+    pub fn name(&self, constant_pool: &ConstantPool) -> Result<String> {
+        constant_pool
+            .utf8(&self.name_index)
+            .with_context(|| format!("no utf8 entry at {:?}", self.name_index))
+    }
+
+    pub fn descriptor(&self, constant_pool: &ConstantPool) -> Result<FieldType> {
+        FieldType::new(
+            &constant_pool
+                .utf8(&self.descriptor_index)
+                .with_context(|| format!("no utf8 entry at {:?}", self.descriptor_index))?,
+        )
+    }
+
+    pub fn write(&self, w: &mut Vec<u8>, constant_pool: &ConstantPool) -> Result<()> {
+        write_u16(w, FieldFlag::bits(&self.access_flags));
+        write_index(w, &self.name_index)?;
+        write_index(w, &self.descriptor_index)?;
+        self.attributes.write(w, constant_pool)
+    }
+
+    pub fn annotations(&self, constant_pool: &ConstantPool) -> Result<Annotations> {
+        Annotations::new(&self.attributes, constant_pool)
+    }
+
+    /// The generic type from the `Signature` attribute, if any.
+    pub fn signature(&self, constant_pool: &ConstantPool) -> Result<Option<FieldSignature>> {
+        signature(&self.attributes, constant_pool)?
+            .map(|text| FieldSignature::new(&text))
+            .transpose()
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -40,6 +76,24 @@ pub enum FieldFlag {
 }
 
 impl FieldFlag {
+    fn mask(&self) -> u16 {
+        match self {
+            Self::Public => 0x0001,
+            Self::Private => 0x0002,
+            Self::Protected => 0x0004,
+            Self::Static => 0x0008,
+            Self::Final => 0x0010,
+            Self::Volatile => 0x0040,
+            Self::Transient => 0x0080,
+            Self::Synthetic => 0x1000,
+            Self::Enum => 0x4000,
+        }
+    }
+
+    pub fn bits(flags: &[Self]) -> u16 {
+        flags.iter().fold(0, |bits, flag| bits | flag.mask())
+    }
+
     fn flags(val: u16) -> Vec<Self> {
         let mut flags = Vec::new();
 
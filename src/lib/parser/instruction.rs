@@ -0,0 +1,1467 @@
+use anyhow::{bail, Result};
+use std::{fmt::Display, io::Cursor};
+
+use super::{
+    constant_pool::Index, parse_i32, parse_u16, parse_u8, write_index, write_u16, write_u8,
+};
+
+/// A single JVM instruction with its operands decoded. Branch offsets are kept relative to
+/// the offset of the instruction, as they appear in the bytecode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    Nop,
+    AconstNull,
+    IconstM1,
+    Iconst0,
+    Iconst1,
+    Iconst2,
+    Iconst3,
+    Iconst4,
+    Iconst5,
+    Lconst0,
+    Lconst1,
+    Fconst0,
+    Fconst1,
+    Fconst2,
+    Dconst0,
+    Dconst1,
+    Bipush(i8),
+    Sipush(i16),
+    Ldc(Index),
+    LdcW(Index),
+    Ldc2W(Index),
+    Iload(u16),
+    Lload(u16),
+    Fload(u16),
+    Dload(u16),
+    Aload(u16),
+    Iload0,
+    Iload1,
+    Iload2,
+    Iload3,
+    Lload0,
+    Lload1,
+    Lload2,
+    Lload3,
+    Fload0,
+    Fload1,
+    Fload2,
+    Fload3,
+    Dload0,
+    Dload1,
+    Dload2,
+    Dload3,
+    Aload0,
+    Aload1,
+    Aload2,
+    Aload3,
+    Iaload,
+    Laload,
+    Faload,
+    Daload,
+    Aaload,
+    Baload,
+    Caload,
+    Saload,
+    Istore(u16),
+    Lstore(u16),
+    Fstore(u16),
+    Dstore(u16),
+    Astore(u16),
+    Istore0,
+    Istore1,
+    Istore2,
+    Istore3,
+    Lstore0,
+    Lstore1,
+    Lstore2,
+    Lstore3,
+    Fstore0,
+    Fstore1,
+    Fstore2,
+    Fstore3,
+    Dstore0,
+    Dstore1,
+    Dstore2,
+    Dstore3,
+    Astore0,
+    Astore1,
+    Astore2,
+    Astore3,
+    Iastore,
+    Lastore,
+    Fastore,
+    Dastore,
+    Aastore,
+    Bastore,
+    Castore,
+    Sastore,
+    Pop,
+    Pop2,
+    Dup,
+    DupX1,
+    DupX2,
+    Dup2,
+    Dup2X1,
+    Dup2X2,
+    Swap,
+    Iadd,
+    Ladd,
+    Fadd,
+    Dadd,
+    Isub,
+    Lsub,
+    Fsub,
+    Dsub,
+    Imul,
+    Lmul,
+    Fmul,
+    Dmul,
+    Idiv,
+    Ldiv,
+    Fdiv,
+    Ddiv,
+    Irem,
+    Lrem,
+    Frem,
+    Drem,
+    Ineg,
+    Lneg,
+    Fneg,
+    Dneg,
+    Ishl,
+    Lshl,
+    Ishr,
+    Lshr,
+    Iushr,
+    Lushr,
+    Iand,
+    Land,
+    Ior,
+    Lor,
+    Ixor,
+    Lxor,
+    Iinc {
+        index: u16,
+        constant: i16,
+    },
+    I2l,
+    I2f,
+    I2d,
+    L2i,
+    L2f,
+    L2d,
+    F2i,
+    F2l,
+    F2d,
+    D2i,
+    D2l,
+    D2f,
+    I2b,
+    I2c,
+    I2s,
+    Lcmp,
+    Fcmpl,
+    Fcmpg,
+    Dcmpl,
+    Dcmpg,
+    Ifeq(i16),
+    Ifne(i16),
+    Iflt(i16),
+    Ifge(i16),
+    Ifgt(i16),
+    Ifle(i16),
+    IfIcmpeq(i16),
+    IfIcmpne(i16),
+    IfIcmplt(i16),
+    IfIcmpge(i16),
+    IfIcmpgt(i16),
+    IfIcmple(i16),
+    IfAcmpeq(i16),
+    IfAcmpne(i16),
+    Goto(i16),
+    Jsr(i16),
+    Ret(u16),
+    TableSwitch {
+        default: i32,
+        low: i32,
+        high: i32,
+        offsets: Vec<i32>,
+    },
+    LookupSwitch {
+        default: i32,
+        pairs: Vec<(i32, i32)>,
+    },
+    Ireturn,
+    Lreturn,
+    Freturn,
+    Dreturn,
+    Areturn,
+    Return,
+    GetStatic(Index),
+    PutStatic(Index),
+    GetField(Index),
+    PutField(Index),
+    InvokeVirtual(Index),
+    InvokeSpecial(Index),
+    InvokeStatic(Index),
+    InvokeInterface {
+        index: Index,
+        count: u8,
+    },
+    InvokeDynamic(Index),
+    New(Index),
+    NewArray(ArrayType),
+    ANewArray(Index),
+    ArrayLength,
+    Athrow,
+    CheckCast(Index),
+    InstanceOf(Index),
+    MonitorEnter,
+    MonitorExit,
+    /// `wide` prefixed load, store, `ret` or `iinc` with 16 bit operands.
+    Wide(Box<Instruction>),
+    MultiANewArray {
+        index: Index,
+        dimensions: u8,
+    },
+    IfNull(i16),
+    IfNonNull(i16),
+    GotoW(i32),
+    JsrW(i32),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArrayType {
+    Boolean,
+    Char,
+    Float,
+    Double,
+    Byte,
+    Short,
+    Int,
+    Long,
+}
+
+impl ArrayType {
+    fn new(atype: u8) -> Result<Self> {
+        Ok(match atype {
+            4 => Self::Boolean,
+            5 => Self::Char,
+            6 => Self::Float,
+            7 => Self::Double,
+            8 => Self::Byte,
+            9 => Self::Short,
+            10 => Self::Int,
+            11 => Self::Long,
+            _ => bail!("invalid newarray type {atype}"),
+        })
+    }
+
+    fn atype(&self) -> u8 {
+        match self {
+            Self::Boolean => 4,
+            Self::Char => 5,
+            Self::Float => 6,
+            Self::Double => 7,
+            Self::Byte => 8,
+            Self::Short => 9,
+            Self::Int => 10,
+            Self::Long => 11,
+        }
+    }
+}
+
+impl Display for ArrayType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Boolean => write!(f, "boolean"),
+            Self::Char => write!(f, "char"),
+            Self::Float => write!(f, "float"),
+            Self::Double => write!(f, "double"),
+            Self::Byte => write!(f, "byte"),
+            Self::Short => write!(f, "short"),
+            Self::Int => write!(f, "int"),
+            Self::Long => write!(f, "long"),
+        }
+    }
+}
+
+impl Instruction {
+    /// Decodes the `code` array of a Code attribute into instructions paired with their offsets.
+    pub fn decode(code: &[u8]) -> Result<Vec<(usize, Instruction)>> {
+        let mut c = Cursor::new(code);
+        let mut instructions = Vec::new();
+
+        while (c.position() as usize) < code.len() {
+            let offset = c.position() as usize;
+            instructions.push((offset, Self::new(&mut c, offset)?));
+        }
+
+        Ok(instructions)
+    }
+
+    fn new(c: &mut Cursor<&[u8]>, offset: usize) -> Result<Self> {
+        let opcode = parse_u8(c)?;
+
+        Ok(match opcode {
+            0x00 => Self::Nop,
+            0x01 => Self::AconstNull,
+            0x02 => Self::IconstM1,
+            0x03 => Self::Iconst0,
+            0x04 => Self::Iconst1,
+            0x05 => Self::Iconst2,
+            0x06 => Self::Iconst3,
+            0x07 => Self::Iconst4,
+            0x08 => Self::Iconst5,
+            0x09 => Self::Lconst0,
+            0x0A => Self::Lconst1,
+            0x0B => Self::Fconst0,
+            0x0C => Self::Fconst1,
+            0x0D => Self::Fconst2,
+            0x0E => Self::Dconst0,
+            0x0F => Self::Dconst1,
+            0x10 => Self::Bipush(parse_u8(c)? as i8),
+            0x11 => Self::Sipush(parse_u16(c)? as i16),
+            0x12 => Self::Ldc(Index::new(parse_u8(c)?)),
+            0x13 => Self::LdcW(Index::new(parse_u16(c)?)),
+            0x14 => Self::Ldc2W(Index::new(parse_u16(c)?)),
+            0x15 => Self::Iload(parse_u8(c)? as u16),
+            0x16 => Self::Lload(parse_u8(c)? as u16),
+            0x17 => Self::Fload(parse_u8(c)? as u16),
+            0x18 => Self::Dload(parse_u8(c)? as u16),
+            0x19 => Self::Aload(parse_u8(c)? as u16),
+            0x1A => Self::Iload0,
+            0x1B => Self::Iload1,
+            0x1C => Self::Iload2,
+            0x1D => Self::Iload3,
+            0x1E => Self::Lload0,
+            0x1F => Self::Lload1,
+            0x20 => Self::Lload2,
+            0x21 => Self::Lload3,
+            0x22 => Self::Fload0,
+            0x23 => Self::Fload1,
+            0x24 => Self::Fload2,
+            0x25 => Self::Fload3,
+            0x26 => Self::Dload0,
+            0x27 => Self::Dload1,
+            0x28 => Self::Dload2,
+            0x29 => Self::Dload3,
+            0x2A => Self::Aload0,
+            0x2B => Self::Aload1,
+            0x2C => Self::Aload2,
+            0x2D => Self::Aload3,
+            0x2E => Self::Iaload,
+            0x2F => Self::Laload,
+            0x30 => Self::Faload,
+            0x31 => Self::Daload,
+            0x32 => Self::Aaload,
+            0x33 => Self::Baload,
+            0x34 => Self::Caload,
+            0x35 => Self::Saload,
+            0x36 => Self::Istore(parse_u8(c)? as u16),
+            0x37 => Self::Lstore(parse_u8(c)? as u16),
+            0x38 => Self::Fstore(parse_u8(c)? as u16),
+            0x39 => Self::Dstore(parse_u8(c)? as u16),
+            0x3A => Self::Astore(parse_u8(c)? as u16),
+            0x3B => Self::Istore0,
+            0x3C => Self::Istore1,
+            0x3D => Self::Istore2,
+            0x3E => Self::Istore3,
+            0x3F => Self::Lstore0,
+            0x40 => Self::Lstore1,
+            0x41 => Self::Lstore2,
+            0x42 => Self::Lstore3,
+            0x43 => Self::Fstore0,
+            0x44 => Self::Fstore1,
+            0x45 => Self::Fstore2,
+            0x46 => Self::Fstore3,
+            0x47 => Self::Dstore0,
+            0x48 => Self::Dstore1,
+            0x49 => Self::Dstore2,
+            0x4A => Self::Dstore3,
+            0x4B => Self::Astore0,
+            0x4C => Self::Astore1,
+            0x4D => Self::Astore2,
+            0x4E => Self::Astore3,
+            0x4F => Self::Iastore,
+            0x50 => Self::Lastore,
+            0x51 => Self::Fastore,
+            0x52 => Self::Dastore,
+            0x53 => Self::Aastore,
+            0x54 => Self::Bastore,
+            0x55 => Self::Castore,
+            0x56 => Self::Sastore,
+            0x57 => Self::Pop,
+            0x58 => Self::Pop2,
+            0x59 => Self::Dup,
+            0x5A => Self::DupX1,
+            0x5B => Self::DupX2,
+            0x5C => Self::Dup2,
+            0x5D => Self::Dup2X1,
+            0x5E => Self::Dup2X2,
+            0x5F => Self::Swap,
+            0x60 => Self::Iadd,
+            0x61 => Self::Ladd,
+            0x62 => Self::Fadd,
+            0x63 => Self::Dadd,
+            0x64 => Self::Isub,
+            0x65 => Self::Lsub,
+            0x66 => Self::Fsub,
+            0x67 => Self::Dsub,
+            0x68 => Self::Imul,
+            0x69 => Self::Lmul,
+            0x6A => Self::Fmul,
+            0x6B => Self::Dmul,
+            0x6C => Self::Idiv,
+            0x6D => Self::Ldiv,
+            0x6E => Self::Fdiv,
+            0x6F => Self::Ddiv,
+            0x70 => Self::Irem,
+            0x71 => Self::Lrem,
+            0x72 => Self::Frem,
+            0x73 => Self::Drem,
+            0x74 => Self::Ineg,
+            0x75 => Self::Lneg,
+            0x76 => Self::Fneg,
+            0x77 => Self::Dneg,
+            0x78 => Self::Ishl,
+            0x79 => Self::Lshl,
+            0x7A => Self::Ishr,
+            0x7B => Self::Lshr,
+            0x7C => Self::Iushr,
+            0x7D => Self::Lushr,
+            0x7E => Self::Iand,
+            0x7F => Self::Land,
+            0x80 => Self::Ior,
+            0x81 => Self::Lor,
+            0x82 => Self::Ixor,
+            0x83 => Self::Lxor,
+            0x84 => Self::Iinc {
+                index: parse_u8(c)? as u16,
+                constant: parse_u8(c)? as i8 as i16,
+            },
+            0x85 => Self::I2l,
+            0x86 => Self::I2f,
+            0x87 => Self::I2d,
+            0x88 => Self::L2i,
+            0x89 => Self::L2f,
+            0x8A => Self::L2d,
+            0x8B => Self::F2i,
+            0x8C => Self::F2l,
+            0x8D => Self::F2d,
+            0x8E => Self::D2i,
+            0x8F => Self::D2l,
+            0x90 => Self::D2f,
+            0x91 => Self::I2b,
+            0x92 => Self::I2c,
+            0x93 => Self::I2s,
+            0x94 => Self::Lcmp,
+            0x95 => Self::Fcmpl,
+            0x96 => Self::Fcmpg,
+            0x97 => Self::Dcmpl,
+            0x98 => Self::Dcmpg,
+            0x99 => Self::Ifeq(parse_u16(c)? as i16),
+            0x9A => Self::Ifne(parse_u16(c)? as i16),
+            0x9B => Self::Iflt(parse_u16(c)? as i16),
+            0x9C => Self::Ifge(parse_u16(c)? as i16),
+            0x9D => Self::Ifgt(parse_u16(c)? as i16),
+            0x9E => Self::Ifle(parse_u16(c)? as i16),
+            0x9F => Self::IfIcmpeq(parse_u16(c)? as i16),
+            0xA0 => Self::IfIcmpne(parse_u16(c)? as i16),
+            0xA1 => Self::IfIcmplt(parse_u16(c)? as i16),
+            0xA2 => Self::IfIcmpge(parse_u16(c)? as i16),
+            0xA3 => Self::IfIcmpgt(parse_u16(c)? as i16),
+            0xA4 => Self::IfIcmple(parse_u16(c)? as i16),
+            0xA5 => Self::IfAcmpeq(parse_u16(c)? as i16),
+            0xA6 => Self::IfAcmpne(parse_u16(c)? as i16),
+            0xA7 => Self::Goto(parse_u16(c)? as i16),
+            0xA8 => Self::Jsr(parse_u16(c)? as i16),
+            0xA9 => Self::Ret(parse_u8(c)? as u16),
+            0xAA => Self::table_switch(c, offset)?,
+            0xAB => Self::lookup_switch(c, offset)?,
+            0xAC => Self::Ireturn,
+            0xAD => Self::Lreturn,
+            0xAE => Self::Freturn,
+            0xAF => Self::Dreturn,
+            0xB0 => Self::Areturn,
+            0xB1 => Self::Return,
+            0xB2 => Self::GetStatic(Index::new(parse_u16(c)?)),
+            0xB3 => Self::PutStatic(Index::new(parse_u16(c)?)),
+            0xB4 => Self::GetField(Index::new(parse_u16(c)?)),
+            0xB5 => Self::PutField(Index::new(parse_u16(c)?)),
+            0xB6 => Self::InvokeVirtual(Index::new(parse_u16(c)?)),
+            0xB7 => Self::InvokeSpecial(Index::new(parse_u16(c)?)),
+            0xB8 => Self::InvokeStatic(Index::new(parse_u16(c)?)),
+            0xB9 => Self::invoke_interface(c)?,
+            0xBA => Self::invoke_dynamic(c)?,
+            0xBB => Self::New(Index::new(parse_u16(c)?)),
+            0xBC => Self::NewArray(ArrayType::new(parse_u8(c)?)?),
+            0xBD => Self::ANewArray(Index::new(parse_u16(c)?)),
+            0xBE => Self::ArrayLength,
+            0xBF => Self::Athrow,
+            0xC0 => Self::CheckCast(Index::new(parse_u16(c)?)),
+            0xC1 => Self::InstanceOf(Index::new(parse_u16(c)?)),
+            0xC2 => Self::MonitorEnter,
+            0xC3 => Self::MonitorExit,
+            0xC4 => Self::wide(c)?,
+            0xC5 => Self::MultiANewArray {
+                index: Index::new(parse_u16(c)?),
+                dimensions: parse_u8(c)?,
+            },
+            0xC6 => Self::IfNull(parse_u16(c)? as i16),
+            0xC7 => Self::IfNonNull(parse_u16(c)? as i16),
+            0xC8 => Self::GotoW(parse_i32(c)?),
+            0xC9 => Self::JsrW(parse_i32(c)?),
+            _ => bail!("invalid opcode 0x{opcode:X} at offset {offset}"),
+        })
+    }
+
+    fn invoke_interface(c: &mut Cursor<&[u8]>) -> Result<Self> {
+        let index = Index::new(parse_u16(c)?);
+        let count = parse_u8(c)?;
+        if parse_u8(c)? != 0 {
+            bail!("fourth operand byte of invokeinterface must be zero");
+        }
+
+        Ok(Self::InvokeInterface { index, count })
+    }
+
+    fn invoke_dynamic(c: &mut Cursor<&[u8]>) -> Result<Self> {
+        let index = Index::new(parse_u16(c)?);
+        if parse_u16(c)? != 0 {
+            bail!("third and fourth operand bytes of invokedynamic must be zero");
+        }
+
+        Ok(Self::InvokeDynamic(index))
+    }
+
+    /// Skips the 0-3 bytes of padding that align switch operands to a multiple of four
+    /// from the start of the code array.
+    fn skip_padding(c: &mut Cursor<&[u8]>, offset: usize) -> Result<()> {
+        for _ in 0..Self::padding(offset) {
+            parse_u8(c)?;
+        }
+
+        Ok(())
+    }
+
+    fn padding(offset: usize) -> usize {
+        (4 - (offset + 1) % 4) % 4
+    }
+
+    fn table_switch(c: &mut Cursor<&[u8]>, offset: usize) -> Result<Self> {
+        Self::skip_padding(c, offset)?;
+        let default = parse_i32(c)?;
+        let low = parse_i32(c)?;
+        let high = parse_i32(c)?;
+        if low > high {
+            bail!("tableswitch low {low} is greater than high {high}");
+        }
+
+        // the count comes from the bytecode, check it against the code left before allocating
+        let count = (high as i64 - low as i64 + 1) as usize;
+        Self::check_remaining(c, count, 4, "tableswitch")?;
+        let mut offsets = Vec::with_capacity(count);
+        for _ in low..=high {
+            offsets.push(parse_i32(c)?);
+        }
+
+        Ok(Self::TableSwitch {
+            default,
+            low,
+            high,
+            offsets,
+        })
+    }
+
+    fn lookup_switch(c: &mut Cursor<&[u8]>, offset: usize) -> Result<Self> {
+        Self::skip_padding(c, offset)?;
+        let default = parse_i32(c)?;
+        let npairs = parse_i32(c)?;
+        if npairs < 0 {
+            bail!("lookupswitch npairs {npairs} is negative");
+        }
+
+        Self::check_remaining(c, npairs as usize, 8, "lookupswitch")?;
+        let mut pairs = Vec::with_capacity(npairs as usize);
+        for _ in 0..npairs {
+            pairs.push((parse_i32(c)?, parse_i32(c)?));
+        }
+
+        Ok(Self::LookupSwitch { default, pairs })
+    }
+
+    fn check_remaining(
+        c: &Cursor<&[u8]>,
+        count: usize,
+        size: usize,
+        instruction: &str,
+    ) -> Result<()> {
+        let remaining = c.get_ref().len().saturating_sub(c.position() as usize);
+        if count.saturating_mul(size) > remaining {
+            bail!("{instruction} has {count} entries but only {remaining} bytes of code are left");
+        }
+
+        Ok(())
+    }
+
+    fn wide(c: &mut Cursor<&[u8]>) -> Result<Self> {
+        let opcode = parse_u8(c)?;
+        let index = parse_u16(c)?;
+
+        let instruction = match opcode {
+            0x15 => Self::Iload(index),
+            0x16 => Self::Lload(index),
+            0x17 => Self::Fload(index),
+            0x18 => Self::Dload(index),
+            0x19 => Self::Aload(index),
+            0x36 => Self::Istore(index),
+            0x37 => Self::Lstore(index),
+            0x38 => Self::Fstore(index),
+            0x39 => Self::Dstore(index),
+            0x3A => Self::Astore(index),
+            0xA9 => Self::Ret(index),
+            0x84 => Self::Iinc {
+                index,
+                constant: parse_u16(c)? as i16,
+            },
+            _ => bail!("opcode 0x{opcode:X} cannot be modified by wide"),
+        };
+
+        Ok(Self::Wide(Box::new(instruction)))
+    }
+
+    pub fn opcode(&self) -> u8 {
+        match self {
+            Self::Nop => 0x00,
+            Self::AconstNull => 0x01,
+            Self::IconstM1 => 0x02,
+            Self::Iconst0 => 0x03,
+            Self::Iconst1 => 0x04,
+            Self::Iconst2 => 0x05,
+            Self::Iconst3 => 0x06,
+            Self::Iconst4 => 0x07,
+            Self::Iconst5 => 0x08,
+            Self::Lconst0 => 0x09,
+            Self::Lconst1 => 0x0A,
+            Self::Fconst0 => 0x0B,
+            Self::Fconst1 => 0x0C,
+            Self::Fconst2 => 0x0D,
+            Self::Dconst0 => 0x0E,
+            Self::Dconst1 => 0x0F,
+            Self::Bipush(_) => 0x10,
+            Self::Sipush(_) => 0x11,
+            Self::Ldc(_) => 0x12,
+            Self::LdcW(_) => 0x13,
+            Self::Ldc2W(_) => 0x14,
+            Self::Iload(_) => 0x15,
+            Self::Lload(_) => 0x16,
+            Self::Fload(_) => 0x17,
+            Self::Dload(_) => 0x18,
+            Self::Aload(_) => 0x19,
+            Self::Iload0 => 0x1A,
+            Self::Iload1 => 0x1B,
+            Self::Iload2 => 0x1C,
+            Self::Iload3 => 0x1D,
+            Self::Lload0 => 0x1E,
+            Self::Lload1 => 0x1F,
+            Self::Lload2 => 0x20,
+            Self::Lload3 => 0x21,
+            Self::Fload0 => 0x22,
+            Self::Fload1 => 0x23,
+            Self::Fload2 => 0x24,
+            Self::Fload3 => 0x25,
+            Self::Dload0 => 0x26,
+            Self::Dload1 => 0x27,
+            Self::Dload2 => 0x28,
+            Self::Dload3 => 0x29,
+            Self::Aload0 => 0x2A,
+            Self::Aload1 => 0x2B,
+            Self::Aload2 => 0x2C,
+            Self::Aload3 => 0x2D,
+            Self::Iaload => 0x2E,
+            Self::Laload => 0x2F,
+            Self::Faload => 0x30,
+            Self::Daload => 0x31,
+            Self::Aaload => 0x32,
+            Self::Baload => 0x33,
+            Self::Caload => 0x34,
+            Self::Saload => 0x35,
+            Self::Istore(_) => 0x36,
+            Self::Lstore(_) => 0x37,
+            Self::Fstore(_) => 0x38,
+            Self::Dstore(_) => 0x39,
+            Self::Astore(_) => 0x3A,
+            Self::Istore0 => 0x3B,
+            Self::Istore1 => 0x3C,
+            Self::Istore2 => 0x3D,
+            Self::Istore3 => 0x3E,
+            Self::Lstore0 => 0x3F,
+            Self::Lstore1 => 0x40,
+            Self::Lstore2 => 0x41,
+            Self::Lstore3 => 0x42,
+            Self::Fstore0 => 0x43,
+            Self::Fstore1 => 0x44,
+            Self::Fstore2 => 0x45,
+            Self::Fstore3 => 0x46,
+            Self::Dstore0 => 0x47,
+            Self::Dstore1 => 0x48,
+            Self::Dstore2 => 0x49,
+            Self::Dstore3 => 0x4A,
+            Self::Astore0 => 0x4B,
+            Self::Astore1 => 0x4C,
+            Self::Astore2 => 0x4D,
+            Self::Astore3 => 0x4E,
+            Self::Iastore => 0x4F,
+            Self::Lastore => 0x50,
+            Self::Fastore => 0x51,
+            Self::Dastore => 0x52,
+            Self::Aastore => 0x53,
+            Self::Bastore => 0x54,
+            Self::Castore => 0x55,
+            Self::Sastore => 0x56,
+            Self::Pop => 0x57,
+            Self::Pop2 => 0x58,
+            Self::Dup => 0x59,
+            Self::DupX1 => 0x5A,
+            Self::DupX2 => 0x5B,
+            Self::Dup2 => 0x5C,
+            Self::Dup2X1 => 0x5D,
+            Self::Dup2X2 => 0x5E,
+            Self::Swap => 0x5F,
+            Self::Iadd => 0x60,
+            Self::Ladd => 0x61,
+            Self::Fadd => 0x62,
+            Self::Dadd => 0x63,
+            Self::Isub => 0x64,
+            Self::Lsub => 0x65,
+            Self::Fsub => 0x66,
+            Self::Dsub => 0x67,
+            Self::Imul => 0x68,
+            Self::Lmul => 0x69,
+            Self::Fmul => 0x6A,
+            Self::Dmul => 0x6B,
+            Self::Idiv => 0x6C,
+            Self::Ldiv => 0x6D,
+            Self::Fdiv => 0x6E,
+            Self::Ddiv => 0x6F,
+            Self::Irem => 0x70,
+            Self::Lrem => 0x71,
+            Self::Frem => 0x72,
+            Self::Drem => 0x73,
+            Self::Ineg => 0x74,
+            Self::Lneg => 0x75,
+            Self::Fneg => 0x76,
+            Self::Dneg => 0x77,
+            Self::Ishl => 0x78,
+            Self::Lshl => 0x79,
+            Self::Ishr => 0x7A,
+            Self::Lshr => 0x7B,
+            Self::Iushr => 0x7C,
+            Self::Lushr => 0x7D,
+            Self::Iand => 0x7E,
+            Self::Land => 0x7F,
+            Self::Ior => 0x80,
+            Self::Lor => 0x81,
+            Self::Ixor => 0x82,
+            Self::Lxor => 0x83,
+            Self::Iinc { .. } => 0x84,
+            Self::I2l => 0x85,
+            Self::I2f => 0x86,
+            Self::I2d => 0x87,
+            Self::L2i => 0x88,
+            Self::L2f => 0x89,
+            Self::L2d => 0x8A,
+            Self::F2i => 0x8B,
+            Self::F2l => 0x8C,
+            Self::F2d => 0x8D,
+            Self::D2i => 0x8E,
+            Self::D2l => 0x8F,
+            Self::D2f => 0x90,
+            Self::I2b => 0x91,
+            Self::I2c => 0x92,
+            Self::I2s => 0x93,
+            Self::Lcmp => 0x94,
+            Self::Fcmpl => 0x95,
+            Self::Fcmpg => 0x96,
+            Self::Dcmpl => 0x97,
+            Self::Dcmpg => 0x98,
+            Self::Ifeq(_) => 0x99,
+            Self::Ifne(_) => 0x9A,
+            Self::Iflt(_) => 0x9B,
+            Self::Ifge(_) => 0x9C,
+            Self::Ifgt(_) => 0x9D,
+            Self::Ifle(_) => 0x9E,
+            Self::IfIcmpeq(_) => 0x9F,
+            Self::IfIcmpne(_) => 0xA0,
+            Self::IfIcmplt(_) => 0xA1,
+            Self::IfIcmpge(_) => 0xA2,
+            Self::IfIcmpgt(_) => 0xA3,
+            Self::IfIcmple(_) => 0xA4,
+            Self::IfAcmpeq(_) => 0xA5,
+            Self::IfAcmpne(_) => 0xA6,
+            Self::Goto(_) => 0xA7,
+            Self::Jsr(_) => 0xA8,
+            Self::Ret(_) => 0xA9,
+            Self::TableSwitch { .. } => 0xAA,
+            Self::LookupSwitch { .. } => 0xAB,
+            Self::Ireturn => 0xAC,
+            Self::Lreturn => 0xAD,
+            Self::Freturn => 0xAE,
+            Self::Dreturn => 0xAF,
+            Self::Areturn => 0xB0,
+            Self::Return => 0xB1,
+            Self::GetStatic(_) => 0xB2,
+            Self::PutStatic(_) => 0xB3,
+            Self::GetField(_) => 0xB4,
+            Self::PutField(_) => 0xB5,
+            Self::InvokeVirtual(_) => 0xB6,
+            Self::InvokeSpecial(_) => 0xB7,
+            Self::InvokeStatic(_) => 0xB8,
+            Self::InvokeInterface { .. } => 0xB9,
+            Self::InvokeDynamic(_) => 0xBA,
+            Self::New(_) => 0xBB,
+            Self::NewArray(_) => 0xBC,
+            Self::ANewArray(_) => 0xBD,
+            Self::ArrayLength => 0xBE,
+            Self::Athrow => 0xBF,
+            Self::CheckCast(_) => 0xC0,
+            Self::InstanceOf(_) => 0xC1,
+            Self::MonitorEnter => 0xC2,
+            Self::MonitorExit => 0xC3,
+            Self::Wide(_) => 0xC4,
+            Self::MultiANewArray { .. } => 0xC5,
+            Self::IfNull(_) => 0xC6,
+            Self::IfNonNull(_) => 0xC7,
+            Self::GotoW(_) => 0xC8,
+            Self::JsrW(_) => 0xC9,
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Self::Nop => "nop",
+            Self::AconstNull => "aconst_null",
+            Self::IconstM1 => "iconst_m1",
+            Self::Iconst0 => "iconst_0",
+            Self::Iconst1 => "iconst_1",
+            Self::Iconst2 => "iconst_2",
+            Self::Iconst3 => "iconst_3",
+            Self::Iconst4 => "iconst_4",
+            Self::Iconst5 => "iconst_5",
+            Self::Lconst0 => "lconst_0",
+            Self::Lconst1 => "lconst_1",
+            Self::Fconst0 => "fconst_0",
+            Self::Fconst1 => "fconst_1",
+            Self::Fconst2 => "fconst_2",
+            Self::Dconst0 => "dconst_0",
+            Self::Dconst1 => "dconst_1",
+            Self::Bipush(_) => "bipush",
+            Self::Sipush(_) => "sipush",
+            Self::Ldc(_) => "ldc",
+            Self::LdcW(_) => "ldc_w",
+            Self::Ldc2W(_) => "ldc2_w",
+            Self::Iload(_) => "iload",
+            Self::Lload(_) => "lload",
+            Self::Fload(_) => "fload",
+            Self::Dload(_) => "dload",
+            Self::Aload(_) => "aload",
+            Self::Iload0 => "iload_0",
+            Self::Iload1 => "iload_1",
+            Self::Iload2 => "iload_2",
+            Self::Iload3 => "iload_3",
+            Self::Lload0 => "lload_0",
+            Self::Lload1 => "lload_1",
+            Self::Lload2 => "lload_2",
+            Self::Lload3 => "lload_3",
+            Self::Fload0 => "fload_0",
+            Self::Fload1 => "fload_1",
+            Self::Fload2 => "fload_2",
+            Self::Fload3 => "fload_3",
+            Self::Dload0 => "dload_0",
+            Self::Dload1 => "dload_1",
+            Self::Dload2 => "dload_2",
+            Self::Dload3 => "dload_3",
+            Self::Aload0 => "aload_0",
+            Self::Aload1 => "aload_1",
+            Self::Aload2 => "aload_2",
+            Self::Aload3 => "aload_3",
+            Self::Iaload => "iaload",
+            Self::Laload => "laload",
+            Self::Faload => "faload",
+            Self::Daload => "daload",
+            Self::Aaload => "aaload",
+            Self::Baload => "baload",
+            Self::Caload => "caload",
+            Self::Saload => "saload",
+            Self::Istore(_) => "istore",
+            Self::Lstore(_) => "lstore",
+            Self::Fstore(_) => "fstore",
+            Self::Dstore(_) => "dstore",
+            Self::Astore(_) => "astore",
+            Self::Istore0 => "istore_0",
+            Self::Istore1 => "istore_1",
+            Self::Istore2 => "istore_2",
+            Self::Istore3 => "istore_3",
+            Self::Lstore0 => "lstore_0",
+            Self::Lstore1 => "lstore_1",
+            Self::Lstore2 => "lstore_2",
+            Self::Lstore3 => "lstore_3",
+            Self::Fstore0 => "fstore_0",
+            Self::Fstore1 => "fstore_1",
+            Self::Fstore2 => "fstore_2",
+            Self::Fstore3 => "fstore_3",
+            Self::Dstore0 => "dstore_0",
+            Self::Dstore1 => "dstore_1",
+            Self::Dstore2 => "dstore_2",
+            Self::Dstore3 => "dstore_3",
+            Self::Astore0 => "astore_0",
+            Self::Astore1 => "astore_1",
+            Self::Astore2 => "astore_2",
+            Self::Astore3 => "astore_3",
+            Self::Iastore => "iastore",
+            Self::Lastore => "lastore",
+            Self::Fastore => "fastore",
+            Self::Dastore => "dastore",
+            Self::Aastore => "aastore",
+            Self::Bastore => "bastore",
+            Self::Castore => "castore",
+            Self::Sastore => "sastore",
+            Self::Pop => "pop",
+            Self::Pop2 => "pop2",
+            Self::Dup => "dup",
+            Self::DupX1 => "dup_x1",
+            Self::DupX2 => "dup_x2",
+            Self::Dup2 => "dup2",
+            Self::Dup2X1 => "dup2_x1",
+            Self::Dup2X2 => "dup2_x2",
+            Self::Swap => "swap",
+            Self::Iadd => "iadd",
+            Self::Ladd => "ladd",
+            Self::Fadd => "fadd",
+            Self::Dadd => "dadd",
+            Self::Isub => "isub",
+            Self::Lsub => "lsub",
+            Self::Fsub => "fsub",
+            Self::Dsub => "dsub",
+            Self::Imul => "imul",
+            Self::Lmul => "lmul",
+            Self::Fmul => "fmul",
+            Self::Dmul => "dmul",
+            Self::Idiv => "idiv",
+            Self::Ldiv => "ldiv",
+            Self::Fdiv => "fdiv",
+            Self::Ddiv => "ddiv",
+            Self::Irem => "irem",
+            Self::Lrem => "lrem",
+            Self::Frem => "frem",
+            Self::Drem => "drem",
+            Self::Ineg => "ineg",
+            Self::Lneg => "lneg",
+            Self::Fneg => "fneg",
+            Self::Dneg => "dneg",
+            Self::Ishl => "ishl",
+            Self::Lshl => "lshl",
+            Self::Ishr => "ishr",
+            Self::Lshr => "lshr",
+            Self::Iushr => "iushr",
+            Self::Lushr => "lushr",
+            Self::Iand => "iand",
+            Self::Land => "land",
+            Self::Ior => "ior",
+            Self::Lor => "lor",
+            Self::Ixor => "ixor",
+            Self::Lxor => "lxor",
+            Self::Iinc { .. } => "iinc",
+            Self::I2l => "i2l",
+            Self::I2f => "i2f",
+            Self::I2d => "i2d",
+            Self::L2i => "l2i",
+            Self::L2f => "l2f",
+            Self::L2d => "l2d",
+            Self::F2i => "f2i",
+            Self::F2l => "f2l",
+            Self::F2d => "f2d",
+            Self::D2i => "d2i",
+            Self::D2l => "d2l",
+            Self::D2f => "d2f",
+            Self::I2b => "i2b",
+            Self::I2c => "i2c",
+            Self::I2s => "i2s",
+            Self::Lcmp => "lcmp",
+            Self::Fcmpl => "fcmpl",
+            Self::Fcmpg => "fcmpg",
+            Self::Dcmpl => "dcmpl",
+            Self::Dcmpg => "dcmpg",
+            Self::Ifeq(_) => "ifeq",
+            Self::Ifne(_) => "ifne",
+            Self::Iflt(_) => "iflt",
+            Self::Ifge(_) => "ifge",
+            Self::Ifgt(_) => "ifgt",
+            Self::Ifle(_) => "ifle",
+            Self::IfIcmpeq(_) => "if_icmpeq",
+            Self::IfIcmpne(_) => "if_icmpne",
+            Self::IfIcmplt(_) => "if_icmplt",
+            Self::IfIcmpge(_) => "if_icmpge",
+            Self::IfIcmpgt(_) => "if_icmpgt",
+            Self::IfIcmple(_) => "if_icmple",
+            Self::IfAcmpeq(_) => "if_acmpeq",
+            Self::IfAcmpne(_) => "if_acmpne",
+            Self::Goto(_) => "goto",
+            Self::Jsr(_) => "jsr",
+            Self::Ret(_) => "ret",
+            Self::TableSwitch { .. } => "tableswitch",
+            Self::LookupSwitch { .. } => "lookupswitch",
+            Self::Ireturn => "ireturn",
+            Self::Lreturn => "lreturn",
+            Self::Freturn => "freturn",
+            Self::Dreturn => "dreturn",
+            Self::Areturn => "areturn",
+            Self::Return => "return",
+            Self::GetStatic(_) => "getstatic",
+            Self::PutStatic(_) => "putstatic",
+            Self::GetField(_) => "getfield",
+            Self::PutField(_) => "putfield",
+            Self::InvokeVirtual(_) => "invokevirtual",
+            Self::InvokeSpecial(_) => "invokespecial",
+            Self::InvokeStatic(_) => "invokestatic",
+            Self::InvokeInterface { .. } => "invokeinterface",
+            Self::InvokeDynamic(_) => "invokedynamic",
+            Self::New(_) => "new",
+            Self::NewArray(_) => "newarray",
+            Self::ANewArray(_) => "anewarray",
+            Self::ArrayLength => "arraylength",
+            Self::Athrow => "athrow",
+            Self::CheckCast(_) => "checkcast",
+            Self::InstanceOf(_) => "instanceof",
+            Self::MonitorEnter => "monitorenter",
+            Self::MonitorExit => "monitorexit",
+            Self::Wide(_) => "wide",
+            Self::MultiANewArray { .. } => "multianewarray",
+            Self::IfNull(_) => "ifnull",
+            Self::IfNonNull(_) => "ifnonnull",
+            Self::GotoW(_) => "goto_w",
+            Self::JsrW(_) => "jsr_w",
+        }
+    }
+
+    /// Encodes the instruction at the end of `w`, which must hold the code array written so
+    /// far so that switch padding is aligned from its start.
+    pub fn write(&self, w: &mut Vec<u8>) -> Result<()> {
+        let offset = w.len();
+        write_u8(w, self.opcode());
+
+        match self {
+            Self::Bipush(val) => write_u8(w, *val as u8),
+            Self::Sipush(val) => write_u16(w, *val as u16),
+            Self::Ldc(index) => write_u8(w, u8::try_from(index.value())?),
+            Self::LdcW(index) => write_index(w, index)?,
+            Self::Ldc2W(index) => write_index(w, index)?,
+            Self::Iload(index) => write_u8(w, u8::try_from(*index)?),
+            Self::Lload(index) => write_u8(w, u8::try_from(*index)?),
+            Self::Fload(index) => write_u8(w, u8::try_from(*index)?),
+            Self::Dload(index) => write_u8(w, u8::try_from(*index)?),
+            Self::Aload(index) => write_u8(w, u8::try_from(*index)?),
+            Self::Istore(index) => write_u8(w, u8::try_from(*index)?),
+            Self::Lstore(index) => write_u8(w, u8::try_from(*index)?),
+            Self::Fstore(index) => write_u8(w, u8::try_from(*index)?),
+            Self::Dstore(index) => write_u8(w, u8::try_from(*index)?),
+            Self::Astore(index) => write_u8(w, u8::try_from(*index)?),
+            Self::Ifeq(val) => write_u16(w, *val as u16),
+            Self::Ifne(val) => write_u16(w, *val as u16),
+            Self::Iflt(val) => write_u16(w, *val as u16),
+            Self::Ifge(val) => write_u16(w, *val as u16),
+            Self::Ifgt(val) => write_u16(w, *val as u16),
+            Self::Ifle(val) => write_u16(w, *val as u16),
+            Self::IfIcmpeq(val) => write_u16(w, *val as u16),
+            Self::IfIcmpne(val) => write_u16(w, *val as u16),
+            Self::IfIcmplt(val) => write_u16(w, *val as u16),
+            Self::IfIcmpge(val) => write_u16(w, *val as u16),
+            Self::IfIcmpgt(val) => write_u16(w, *val as u16),
+            Self::IfIcmple(val) => write_u16(w, *val as u16),
+            Self::IfAcmpeq(val) => write_u16(w, *val as u16),
+            Self::IfAcmpne(val) => write_u16(w, *val as u16),
+            Self::Goto(val) => write_u16(w, *val as u16),
+            Self::Jsr(val) => write_u16(w, *val as u16),
+            Self::Ret(index) => write_u8(w, u8::try_from(*index)?),
+            Self::GetStatic(index) => write_index(w, index)?,
+            Self::PutStatic(index) => write_index(w, index)?,
+            Self::GetField(index) => write_index(w, index)?,
+            Self::PutField(index) => write_index(w, index)?,
+            Self::InvokeVirtual(index) => write_index(w, index)?,
+            Self::InvokeSpecial(index) => write_index(w, index)?,
+            Self::InvokeStatic(index) => write_index(w, index)?,
+            Self::New(index) => write_index(w, index)?,
+            Self::ANewArray(index) => write_index(w, index)?,
+            Self::CheckCast(index) => write_index(w, index)?,
+            Self::InstanceOf(index) => write_index(w, index)?,
+            Self::IfNull(val) => write_u16(w, *val as u16),
+            Self::IfNonNull(val) => write_u16(w, *val as u16),
+            Self::GotoW(val) => w.extend_from_slice(&val.to_be_bytes()),
+            Self::JsrW(val) => w.extend_from_slice(&val.to_be_bytes()),
+            Self::Iinc { index, constant } => {
+                write_u8(w, u8::try_from(*index)?);
+                write_u8(w, i8::try_from(*constant)? as u8);
+            }
+            Self::NewArray(atype) => write_u8(w, atype.atype()),
+            Self::InvokeInterface { index, count } => {
+                write_index(w, index)?;
+                write_u8(w, *count);
+                write_u8(w, 0);
+            }
+            Self::InvokeDynamic(index) => {
+                write_index(w, index)?;
+                write_u16(w, 0);
+            }
+            Self::MultiANewArray { index, dimensions } => {
+                write_index(w, index)?;
+                write_u8(w, *dimensions);
+            }
+            Self::TableSwitch {
+                default,
+                low,
+                high,
+                offsets,
+            } => {
+                w.resize(w.len() + Self::padding(offset), 0);
+                for val in [default, low, high].into_iter().chain(offsets) {
+                    w.extend_from_slice(&val.to_be_bytes());
+                }
+            }
+            Self::LookupSwitch { default, pairs } => {
+                w.resize(w.len() + Self::padding(offset), 0);
+                w.extend_from_slice(&default.to_be_bytes());
+                w.extend_from_slice(&(pairs.len() as i32).to_be_bytes());
+                for (key, val) in pairs {
+                    w.extend_from_slice(&key.to_be_bytes());
+                    w.extend_from_slice(&val.to_be_bytes());
+                }
+            }
+            Self::Wide(instruction) => {
+                write_u8(w, instruction.opcode());
+                match instruction.as_ref() {
+                    Self::Iload(index)
+                    | Self::Lload(index)
+                    | Self::Fload(index)
+                    | Self::Dload(index)
+                    | Self::Aload(index)
+                    | Self::Istore(index)
+                    | Self::Lstore(index)
+                    | Self::Fstore(index)
+                    | Self::Dstore(index)
+                    | Self::Astore(index)
+                    | Self::Ret(index) => write_u16(w, *index),
+                    Self::Iinc { index, constant } => {
+                        write_u16(w, *index);
+                        write_u16(w, *constant as u16);
+                    }
+                    _ => bail!("{} cannot be modified by wide", instruction.mnemonic()),
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Constant pool entry referenced by the instruction, if any.
+    pub fn constant_pool_index(&self) -> Option<&Index> {
+        match self {
+            Self::Ldc(index)
+            | Self::LdcW(index)
+            | Self::Ldc2W(index)
+            | Self::GetStatic(index)
+            | Self::PutStatic(index)
+            | Self::GetField(index)
+            | Self::PutField(index)
+            | Self::InvokeVirtual(index)
+            | Self::InvokeSpecial(index)
+            | Self::InvokeStatic(index)
+            | Self::InvokeInterface { index, .. }
+            | Self::InvokeDynamic(index)
+            | Self::New(index)
+            | Self::ANewArray(index)
+            | Self::CheckCast(index)
+            | Self::InstanceOf(index)
+            | Self::MultiANewArray { index, .. } => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Absolute offsets this instruction may transfer control to, excluding the next instruction.
+    pub fn branch_targets(&self, offset: usize) -> Vec<usize> {
+        let target = |delta: i32| (offset as i64 + delta as i64) as usize;
+
+        match self {
+            Self::Ifeq(delta)
+            | Self::Ifne(delta)
+            | Self::Iflt(delta)
+            | Self::Ifge(delta)
+            | Self::Ifgt(delta)
+            | Self::Ifle(delta)
+            | Self::IfIcmpeq(delta)
+            | Self::IfIcmpne(delta)
+            | Self::IfIcmplt(delta)
+            | Self::IfIcmpge(delta)
+            | Self::IfIcmpgt(delta)
+            | Self::IfIcmple(delta)
+            | Self::IfAcmpeq(delta)
+            | Self::IfAcmpne(delta)
+            | Self::Goto(delta)
+            | Self::Jsr(delta)
+            | Self::IfNull(delta)
+            | Self::IfNonNull(delta) => vec![target(*delta as i32)],
+            Self::GotoW(delta) | Self::JsrW(delta) => vec![target(*delta)],
+            Self::TableSwitch {
+                default, offsets, ..
+            } => std::iter::once(default)
+                .chain(offsets)
+                .map(|delta| target(*delta))
+                .collect(),
+            Self::LookupSwitch { default, pairs } => std::iter::once(default)
+                .chain(pairs.iter().map(|(_, delta)| delta))
+                .map(|delta| target(*delta))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.mnemonic())?;
+
+        match self {
+            Self::Bipush(val) => write!(f, " {val}"),
+            Self::Sipush(val) => write!(f, " {val}"),
+            Self::Ldc(index) => write!(f, " #{}", index.value()),
+            Self::LdcW(index) => write!(f, " #{}", index.value()),
+            Self::Ldc2W(index) => write!(f, " #{}", index.value()),
+            Self::Iload(val) => write!(f, " {val}"),
+            Self::Lload(val) => write!(f, " {val}"),
+            Self::Fload(val) => write!(f, " {val}"),
+            Self::Dload(val) => write!(f, " {val}"),
+            Self::Aload(val) => write!(f, " {val}"),
+            Self::Istore(val) => write!(f, " {val}"),
+            Self::Lstore(val) => write!(f, " {val}"),
+            Self::Fstore(val) => write!(f, " {val}"),
+            Self::Dstore(val) => write!(f, " {val}"),
+            Self::Astore(val) => write!(f, " {val}"),
+            Self::Ifeq(val) => write!(f, " {val}"),
+            Self::Ifne(val) => write!(f, " {val}"),
+            Self::Iflt(val) => write!(f, " {val}"),
+            Self::Ifge(val) => write!(f, " {val}"),
+            Self::Ifgt(val) => write!(f, " {val}"),
+            Self::Ifle(val) => write!(f, " {val}"),
+            Self::IfIcmpeq(val) => write!(f, " {val}"),
+            Self::IfIcmpne(val) => write!(f, " {val}"),
+            Self::IfIcmplt(val) => write!(f, " {val}"),
+            Self::IfIcmpge(val) => write!(f, " {val}"),
+            Self::IfIcmpgt(val) => write!(f, " {val}"),
+            Self::IfIcmple(val) => write!(f, " {val}"),
+            Self::IfAcmpeq(val) => write!(f, " {val}"),
+            Self::IfAcmpne(val) => write!(f, " {val}"),
+            Self::Goto(val) => write!(f, " {val}"),
+            Self::Jsr(val) => write!(f, " {val}"),
+            Self::Ret(val) => write!(f, " {val}"),
+            Self::GetStatic(index) => write!(f, " #{}", index.value()),
+            Self::PutStatic(index) => write!(f, " #{}", index.value()),
+            Self::GetField(index) => write!(f, " #{}", index.value()),
+            Self::PutField(index) => write!(f, " #{}", index.value()),
+            Self::InvokeVirtual(index) => write!(f, " #{}", index.value()),
+            Self::InvokeSpecial(index) => write!(f, " #{}", index.value()),
+            Self::InvokeStatic(index) => write!(f, " #{}", index.value()),
+            Self::New(index) => write!(f, " #{}", index.value()),
+            Self::ANewArray(index) => write!(f, " #{}", index.value()),
+            Self::CheckCast(index) => write!(f, " #{}", index.value()),
+            Self::InstanceOf(index) => write!(f, " #{}", index.value()),
+            Self::IfNull(val) => write!(f, " {val}"),
+            Self::IfNonNull(val) => write!(f, " {val}"),
+            Self::GotoW(val) => write!(f, " {val}"),
+            Self::JsrW(val) => write!(f, " {val}"),
+            Self::Iinc { index, constant } => write!(f, " {index}, {constant}"),
+            Self::NewArray(atype) => write!(f, " {atype}"),
+            Self::InvokeInterface { index, count } => write!(f, " #{}, {count}", index.value()),
+            Self::InvokeDynamic(index) => write!(f, " #{}, 0", index.value()),
+            Self::MultiANewArray { index, dimensions } => {
+                write!(f, " #{}, {dimensions}", index.value())
+            }
+            Self::TableSwitch {
+                default, low, high, ..
+            } => write!(f, " {{ {low} to {high}, default: {default} }}"),
+            Self::LookupSwitch { default, pairs } => {
+                write!(f, " {{ {} pairs, default: {default} }}", pairs.len())
+            }
+            Self::Wide(instruction) => write!(f, " {instruction}"),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArrayType, Instruction};
+    use crate::parser::constant_pool::Index;
+
+    fn round_trip(code: Vec<u8>) -> Vec<(usize, Instruction)> {
+        let instructions = Instruction::decode(&code).unwrap();
+        let mut written = Vec::new();
+        for (offset, instruction) in &instructions {
+            assert_eq!(*offset, written.len());
+            instruction.write(&mut written).unwrap();
+        }
+        assert_eq!(written, code);
+
+        instructions
+    }
+
+    #[test]
+    fn test_operands() {
+        let instructions = round_trip(vec![
+            0x10, 0xFF, // bipush -1
+            0x11, 0x01, 0x00, // sipush 256
+            0x12, 0x07, // ldc #7
+            0x84, 0x02, 0xFE, // iinc 2, -2
+            0xBC, 0x0A, // newarray int
+            0xB9, 0x00, 0x05, 0x02, 0x00, // invokeinterface #5, 2
+            0xBA, 0x00, 0x06, 0x00, 0x00, // invokedynamic #6
+            0xC5, 0x00, 0x08, 0x03, // multianewarray #8, 3
+            0xA7, 0xFF, 0xE6, // goto -26
+            0xB1, // return
+        ]);
+
+        assert_eq!(
+            instructions,
+            vec![
+                (0, Instruction::Bipush(-1)),
+                (2, Instruction::Sipush(256)),
+                (5, Instruction::Ldc(Index::new(7u16))),
+                (
+                    7,
+                    Instruction::Iinc {
+                        index: 2,
+                        constant: -2
+                    }
+                ),
+                (10, Instruction::NewArray(ArrayType::Int)),
+                (
+                    12,
+                    Instruction::InvokeInterface {
+                        index: Index::new(5u16),
+                        count: 2
+                    }
+                ),
+                (17, Instruction::InvokeDynamic(Index::new(6u16))),
+                (
+                    22,
+                    Instruction::MultiANewArray {
+                        index: Index::new(8u16),
+                        dimensions: 3
+                    }
+                ),
+                (26, Instruction::Goto(-26)),
+                (29, Instruction::Return),
+            ]
+        );
+        assert_eq!(instructions[8].1.branch_targets(26), vec![0]);
+    }
+
+    #[test]
+    fn test_wide() {
+        let instructions = round_trip(vec![
+            0xC4, 0x15, 0x01, 0x00, // wide iload 256
+            0xC4, 0x84, 0x01, 0x00, 0x80, 0x00, // wide iinc 256, -32768
+        ]);
+
+        assert_eq!(
+            instructions[0].1,
+            Instruction::Wide(Box::new(Instruction::Iload(256)))
+        );
+        assert_eq!(
+            instructions[1],
+            (
+                4,
+                Instruction::Wide(Box::new(Instruction::Iinc {
+                    index: 256,
+                    constant: -32768
+                }))
+            )
+        );
+        assert_eq!(instructions[1].1.to_string(), "wide iinc 256, -32768");
+        assert!(Instruction::decode(&[0xC4, 0x10, 0x00, 0x01]).is_err());
+    }
+
+    #[test]
+    fn test_switch_padding() {
+        for nops in 0..4 {
+            let mut code = vec![0x00; nops];
+            code.push(0xAA);
+            code.resize(code.len() + (3 - nops % 4), 0);
+            for val in [20i32, 1, 2, 30, 40] {
+                code.extend_from_slice(&val.to_be_bytes());
+            }
+            let lookup = code.len();
+            code.push(0xAB);
+            code.resize(code.len() + (3 - lookup % 4) % 4, 0);
+            for val in [8i32, 1, -5, 12] {
+                code.extend_from_slice(&val.to_be_bytes());
+            }
+
+            let instructions = round_trip(code);
+            let (offset, table) = &instructions[nops];
+            assert_eq!(*offset, nops);
+            assert_eq!(
+                *table,
+                Instruction::TableSwitch {
+                    default: 20,
+                    low: 1,
+                    high: 2,
+                    offsets: vec![30, 40]
+                }
+            );
+            assert_eq!(
+                table.branch_targets(nops),
+                vec![nops + 20, nops + 30, nops + 40]
+            );
+            assert_eq!(
+                instructions[nops + 1],
+                (
+                    lookup,
+                    Instruction::LookupSwitch {
+                        default: 8,
+                        pairs: vec![(-5, 12)]
+                    }
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(Instruction::decode(&[0xCA]).is_err());
+        assert!(Instruction::decode(&[0x11, 0x00]).is_err());
+        assert!(Instruction::decode(&[0xBA, 0x00, 0x01, 0x00, 0x01]).is_err());
+    }
+
+    #[test]
+    fn test_huge_switch() {
+        // tableswitch with low i32::MIN and high i32::MAX, 3 bytes of padding
+        let mut table_switch = vec![0xAA, 0, 0, 0, 0, 0, 0, 0];
+        table_switch.extend(i32::MIN.to_be_bytes());
+        table_switch.extend(i32::MAX.to_be_bytes());
+        let err = Instruction::decode(&table_switch).unwrap_err();
+        assert!(format!("{err:#}").contains("tableswitch has 4294967296 entries"));
+
+        let mut high_below_low = vec![0xAA, 0, 0, 0, 0, 0, 0, 0];
+        high_below_low.extend(1i32.to_be_bytes());
+        high_below_low.extend(0i32.to_be_bytes());
+        assert!(Instruction::decode(&high_below_low).is_err());
+
+        // lookupswitch with 0x7fffffff pairs
+        let mut lookup_switch = vec![0xAB, 0, 0, 0, 0, 0, 0, 0];
+        lookup_switch.extend(i32::MAX.to_be_bytes());
+        let err = Instruction::decode(&lookup_switch).unwrap_err();
+        assert!(format!("{err:#}").contains("lookupswitch has 2147483647 entries"));
+    }
+}
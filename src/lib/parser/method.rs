@@ -1,29 +1,81 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::io::Cursor;
 
 use super::{
-    attribute::Attribute,
+    attribute::{
+        annotation::Annotations, method_parameter::MethodParameterItem, Attribute, Attributes,
+    },
     constant_pool::{ConstantPool, Index},
+    descriptor::MethodDescriptor,
     parse_u16,
+    signature::{signature, MethodSignature},
+    write_index, write_u16,
 };
 
+/// A method_info structure, whose name and descriptor are constant pool indices.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Method {
     pub access_flags: Vec<MethodFlag>,
     pub name_index: Index,
     pub descriptor_index: Index,
-    pub attributes: Vec<Attribute>,
+    pub attributes: Attributes,
 }
 
 impl Method {
-    pub fn new(c: &mut Cursor<&Vec<u8>>, constant_pool: &ConstantPool) -> Result<Method> {
+    pub fn new(c: &mut Cursor<&[u8]>, constant_pool: &ConstantPool) -> Result<Method> {
         Ok(Method {
             access_flags: MethodFlag::flags(parse_u16(c)?),
             name_index: Index::new(parse_u16(c)?),
             descriptor_index: Index::new(parse_u16(c)?),
-            attributes: Attribute::attributes(c, constant_pool)?,
+            attributes: Attributes::new(c, constant_pool)?,
         })
     }
+
+    pub fn name(&self, constant_pool: &ConstantPool) -> Result<String> {
+        constant_pool
+            .utf8(&self.name_index)
+            .with_context(|| format!("no utf8 entry at {:?}", self.name_index))
+    }
+
+    pub fn descriptor(&self, constant_pool: &ConstantPool) -> Result<MethodDescriptor> {
+        MethodDescriptor::new(
+            &constant_pool
+                .utf8(&self.descriptor_index)
+                .with_context(|| format!("no utf8 entry at {:?}", self.descriptor_index))?,
+        )
+    }
+
+    pub fn write(&self, w: &mut Vec<u8>, constant_pool: &ConstantPool) -> Result<()> {
+        write_u16(w, MethodFlag::bits(&self.access_flags));
+        write_index(w, &self.name_index)?;
+        write_index(w, &self.descriptor_index)?;
+        self.attributes.write(w, constant_pool)
+    }
+
+    pub fn annotations(&self, constant_pool: &ConstantPool) -> Result<Annotations> {
+        Annotations::new(&self.attributes, constant_pool)
+    }
+
+    /// The generic signature from the `Signature` attribute, if any.
+    pub fn signature(&self, constant_pool: &ConstantPool) -> Result<Option<MethodSignature>> {
+        signature(&self.attributes, constant_pool)?
+            .map(|text| MethodSignature::new(&text))
+            .transpose()
+    }
+
+    /// Parameter names and flags from the `MethodParameters` attribute, empty if absent.
+    pub fn parameters(&self, constant_pool: &ConstantPool) -> Result<Vec<MethodParameterItem>> {
+        for attribute in &self.attributes {
+            if let Attribute::MethodParameters { parameters } = attribute {
+                return parameters
+                    .iter()
+                    .map(|p| p.resolve(constant_pool))
+                    .collect();
+            }
+        }
+
+        Ok(Vec::new())
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -43,6 +95,27 @@ pub enum MethodFlag {
 }
 
 impl MethodFlag {
+    fn mask(&self) -> u16 {
+        match self {
+            MethodFlag::Public => 0x0001,
+            MethodFlag::Private => 0x0002,
+            MethodFlag::Protected => 0x0004,
+            MethodFlag::Static => 0x0008,
+            MethodFlag::Final => 0x0010,
+            MethodFlag::Synchronized => 0x0020,
+            MethodFlag::Bridge => 0x0040,
+            MethodFlag::Varargs => 0x0080,
+            MethodFlag::Native => 0x0100,
+            MethodFlag::Abstract => 0x0400,
+            MethodFlag::Strict => 0x0800,
+            MethodFlag::Synthetic => 0x1000,
+        }
+    }
+
+    pub fn bits(flags: &[MethodFlag]) -> u16 {
+        flags.iter().fold(0, |bits, flag| bits | flag.mask())
+    }
+
     fn flags(val: u16) -> Vec<MethodFlag> {
         let mut flags = Vec::new();
 
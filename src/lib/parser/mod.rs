@@ -1,45 +1,134 @@
-use anyhow::Result;
-use std::io::{Cursor, Read};
+//! Parser for the class file format of JVMS chapter 4, usable on its own to inspect classes.
+//!
+//! [`ClassFile::from_bytes`](class::ClassFile::from_bytes) parses a class file, whose members
+//! and attributes refer to the constant pool by [`Index`].
+//! [`ConstantPool::resolve`](constant_pool::ConstantPool::resolve) turns an index into the
+//! constant it refers to, and the [`descriptor`] types decode field and method descriptors.
+//! [`ClassFile::check`](class::ClassFile::check) performs the format checks a JVM does before
+//! loading a class.
+//!
+//! ```
+//! use jvm::parser::{
+//!     class::ClassFile,
+//!     constant_pool::ConstantPoolItem,
+//!     descriptor::{FieldType, ReturnDescriptor},
+//! };
+//!
+//! let data = std::fs::read("testdata/Main.class")?;
+//! let class_file = ClassFile::from_bytes(&data)?;
+//! let cp = &class_file.constant_pool;
+//!
+//! let main = class_file
+//!     .methods
+//!     .iter()
+//!     .find(|method| method.name(cp).is_ok_and(|name| name == "main"))
+//!     .unwrap();
+//! let descriptor = main.descriptor(cp)?;
+//! assert_eq!(descriptor.return_descriptor, ReturnDescriptor::Void);
+//! assert_eq!(
+//!     descriptor.parameters,
+//!     vec![FieldType::Array(Box::new(FieldType::Class("java/lang/String".to_string())))]
+//! );
+//!
+//! let Some(ConstantPoolItem::ClassInfo { identifier }) = cp.resolve(&class_file.super_class_index)
+//! else {
+//!     panic!("super class is not a class");
+//! };
+//! assert_eq!(identifier.to_string(), "java.lang.Object");
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use anyhow::{Context, Result};
+use std::io::{Cursor, ErrorKind, Read};
+
+use constant_pool::Index;
 
 pub mod attribute;
+mod check;
 pub mod class;
+pub mod class_ref;
 pub mod constant_pool;
 pub mod descriptor;
+pub mod error;
 pub mod field;
+pub mod instruction;
 pub mod method;
+pub mod mutf8;
+pub mod signature;
 
-fn parse_u8(c: &mut Cursor<&Vec<u8>>) -> Result<u8> {
+fn parse_u8(c: &mut Cursor<&[u8]>) -> Result<u8> {
     let mut tag = [0u8; 1];
     c.read_exact(&mut tag)?;
     Ok(tag[0])
 }
 
-fn parse_u16(c: &mut Cursor<&Vec<u8>>) -> Result<u16> {
+fn parse_u16(c: &mut Cursor<&[u8]>) -> Result<u16> {
     let mut val = [0u8; 2];
     c.read_exact(&mut val)?;
     Ok(u16::from_be_bytes(val))
 }
 
-fn parse_u32(c: &mut Cursor<&Vec<u8>>) -> Result<u32> {
+fn parse_u32(c: &mut Cursor<&[u8]>) -> Result<u32> {
     let mut val = [0u8; 4];
     c.read_exact(&mut val)?;
     Ok(u32::from_be_bytes(val))
 }
 
-fn parse_i32(c: &mut Cursor<&Vec<u8>>) -> Result<i32> {
+fn parse_i32(c: &mut Cursor<&[u8]>) -> Result<i32> {
     let mut val = [0u8; 4];
     c.read_exact(&mut val)?;
     Ok(i32::from_be_bytes(val))
 }
 
-fn parse_vec(c: &mut Cursor<&Vec<u8>>, n: usize) -> Result<Vec<u8>> {
+/// Reads `n` bytes, checking that they are there before allocating since `n` comes from the
+/// class file.
+fn parse_vec(c: &mut Cursor<&[u8]>, n: usize) -> Result<Vec<u8>> {
+    let remaining = c.get_ref().len().saturating_sub(c.position() as usize);
+    if n > remaining {
+        return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into());
+    }
+
     let mut val = vec![0u8; n];
     c.read_exact(&mut val)?;
     Ok(val)
 }
 
-fn parse_f32(c: &mut Cursor<&Vec<u8>>) -> Result<f32> {
+fn parse_f32(c: &mut Cursor<&[u8]>) -> Result<f32> {
     let mut val = [0u8; 4];
     c.read_exact(&mut val)?;
     Ok(f32::from_be_bytes(val))
 }
+
+fn parse_f64(c: &mut Cursor<&[u8]>) -> Result<f64> {
+    let mut val = [0u8; 8];
+    c.read_exact(&mut val)?;
+    Ok(f64::from_be_bytes(val))
+}
+
+fn write_u8(w: &mut Vec<u8>, val: u8) {
+    w.push(val);
+}
+
+fn write_u16(w: &mut Vec<u8>, val: u16) {
+    w.extend(val.to_be_bytes());
+}
+
+fn write_u32(w: &mut Vec<u8>, val: u32) {
+    w.extend(val.to_be_bytes());
+}
+
+fn write_index(w: &mut Vec<u8>, index: &Index) -> Result<()> {
+    write_u16(
+        w,
+        u16::try_from(index.value()).context(format!("{index:?} does not fit into u16"))?,
+    );
+    Ok(())
+}
+
+fn write_count(w: &mut Vec<u8>, count: usize) -> Result<()> {
+    write_u16(
+        w,
+        u16::try_from(count).context(format!("count {count} does not fit into u16"))?,
+    );
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use anyhow::{bail, Result};
+use std::fmt::Display;
+
+/// Decodes the modified UTF-8 of CONSTANT_Utf8 entries (JVMS 4.4.7) into UTF-16 code units.
+///
+/// Unlike standard UTF-8, U+0000 is encoded in two bytes, and characters outside the Basic
+/// Multilingual Plane are encoded as their two surrogates of three bytes each.
+pub fn decode(bytes: &[u8]) -> Result<Vec<u16>> {
+    let mut units = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let continuation = |offset: usize| -> Result<u16> {
+            match bytes.get(i + offset) {
+                Some(b) if b & 0xc0 == 0x80 => Ok((b & 0x3f) as u16),
+                Some(b) => bail!(
+                    "invalid modified UTF-8 continuation byte {b:#04x} at {}",
+                    i + offset
+                ),
+                None => bail!("truncated modified UTF-8 sequence at {i}"),
+            }
+        };
+
+        let b = bytes[i];
+        match b {
+            0x01..=0x7f => {
+                units.push(b as u16);
+                i += 1;
+            }
+            0xc0..=0xdf => {
+                units.push(((b & 0x1f) as u16) << 6 | continuation(1)?);
+                i += 2;
+            }
+            0xe0..=0xef => {
+                units.push(((b & 0x0f) as u16) << 12 | continuation(1)? << 6 | continuation(2)?);
+                i += 3;
+            }
+            _ => bail!("invalid modified UTF-8 byte {b:#04x} at {i}"),
+        }
+    }
+
+    Ok(units)
+}
+
+/// Encodes UTF-16 code units as modified UTF-8, the inverse of [`decode`].
+pub fn encode(units: &[u16]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(units.len());
+
+    for unit in units {
+        match unit {
+            0x0001..=0x007f => bytes.push(*unit as u8),
+            0x0000 | 0x0080..=0x07ff => {
+                bytes.push(0xc0 | (unit >> 6) as u8);
+                bytes.push(0x80 | (unit & 0x3f) as u8);
+            }
+            _ => {
+                bytes.push(0xe0 | (unit >> 12) as u8);
+                bytes.push(0x80 | (unit >> 6 & 0x3f) as u8);
+                bytes.push(0x80 | (unit & 0x3f) as u8);
+            }
+        }
+    }
+
+    bytes
+}
+
+/// A string as the JVM sees it, a sequence of UTF-16 code units that unlike a Rust `String`
+/// may contain unpaired surrogates.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct JavaString {
+    units: Vec<u16>,
+}
+
+impl JavaString {
+    pub fn new(units: Vec<u16>) -> Self {
+        Self { units }
+    }
+
+    /// Decodes the bytes of a CONSTANT_Utf8 entry.
+    pub fn from_modified_utf8(bytes: &[u8]) -> Result<Self> {
+        Ok(Self::new(decode(bytes)?))
+    }
+
+    pub fn to_modified_utf8(&self) -> Vec<u8> {
+        encode(&self.units)
+    }
+
+    pub fn units(&self) -> &[u16] {
+        &self.units
+    }
+
+    /// The string as Rust text, `None` if it contains unpaired surrogates.
+    pub fn as_string(&self) -> Option<String> {
+        String::from_utf16(&self.units).ok()
+    }
+}
+
+impl From<&str> for JavaString {
+    fn from(text: &str) -> Self {
+        Self::new(text.encode_utf16().collect())
+    }
+}
+
+/// Unpaired surrogates are shown as `\uXXXX` escapes.
+impl Display for JavaString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for c in char::decode_utf16(self.units.iter().copied()) {
+            match c {
+                Ok(c) => write!(f, "{c}")?,
+                Err(err) => write!(f, "\\u{:04x}", err.unpaired_surrogate())?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, JavaString};
+
+    #[test]
+    fn test_nul() {
+        let bytes = [b'a', 0xc0, 0x80, b'b'];
+        assert_eq!(decode(&bytes).unwrap(), vec![0x61, 0x0000, 0x62]);
+        assert_eq!(encode(&[0x61, 0x0000, 0x62]), bytes);
+        assert!(decode(&[b'a', 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_surrogate_pair() {
+        // U+1F600 as the surrogates D83D DE00, three bytes each
+        let bytes = [0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80];
+        let text = JavaString::from_modified_utf8(&bytes).unwrap();
+
+        assert_eq!(text.units(), [0xd83d, 0xde00]);
+        assert_eq!(text.as_string().unwrap(), "\u{1f600}");
+        assert_eq!(JavaString::from("\u{1f600}").to_modified_utf8(), bytes);
+        // standard UTF-8 four byte sequences are not modified UTF-8
+        assert!(decode("\u{1f600}".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_unpaired_surrogate() {
+        let bytes = [b'x', 0xed, 0xa0, 0x80];
+        let text = JavaString::from_modified_utf8(&bytes).unwrap();
+
+        assert_eq!(text.as_string(), None);
+        assert_eq!(text.to_string(), "x\\ud800");
+        assert_eq!(text.to_modified_utf8(), bytes);
+    }
+
+    #[test]
+    fn test_two_and_three_bytes() {
+        let text = JavaString::from("é€");
+        assert_eq!(text.to_modified_utf8(), "é€".as_bytes());
+        assert_eq!(
+            JavaString::from_modified_utf8("é€".as_bytes()).unwrap(),
+            text
+        );
+        assert!(decode(&[0xc3]).is_err());
+        assert!(decode(&[0xe2, 0x82, 0x41]).is_err());
+    }
+}
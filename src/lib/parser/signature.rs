@@ -0,0 +1,652 @@
+use anyhow::{bail, Context, Result};
+use std::fmt::Display;
+
+use super::{attribute::Attribute, constant_pool::ConstantPool};
+
+/// Generic type information of a class from its `Signature` attribute (JVMS 4.7.9.1).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClassSignature {
+    pub type_parameters: Vec<TypeParameter>,
+    pub super_class: ClassTypeSignature,
+    pub interfaces: Vec<ClassTypeSignature>,
+}
+
+/// Generic type information of a method, `result` is `None` for void methods.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MethodSignature {
+    pub type_parameters: Vec<TypeParameter>,
+    pub parameters: Vec<JavaTypeSignature>,
+    pub result: Option<JavaTypeSignature>,
+    pub throws: Vec<ThrowsSignature>,
+}
+
+/// Generic type of a field, record component or local variable.
+pub type FieldSignature = ReferenceTypeSignature;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeParameter {
+    pub name: String,
+    /// Absent if the first bound is an interface, as in `<T::Ljava/lang/Runnable;>`.
+    pub class_bound: Option<ReferenceTypeSignature>,
+    pub interface_bounds: Vec<ReferenceTypeSignature>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JavaTypeSignature {
+    Base(BaseType),
+    Reference(ReferenceTypeSignature),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BaseType {
+    Byte,
+    Char,
+    Double,
+    Float,
+    Int,
+    Long,
+    Short,
+    Boolean,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReferenceTypeSignature {
+    Class(ClassTypeSignature),
+    TypeVariable(String),
+    Array(Box<JavaTypeSignature>),
+}
+
+/// A possibly parameterized class type such as `Ljava/util/Map<TK;TV;>.Entry<TK;TV;>;`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClassTypeSignature {
+    /// Package in internal form, empty for the unnamed package.
+    pub package: String,
+    pub class: SimpleClassTypeSignature,
+    /// Inner classes, each with its own type arguments.
+    pub inner_classes: Vec<SimpleClassTypeSignature>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimpleClassTypeSignature {
+    pub name: String,
+    pub type_arguments: Vec<TypeArgument>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeArgument {
+    /// The unbounded wildcard `?`.
+    Any,
+    Exact(ReferenceTypeSignature),
+    /// `? extends` the bound.
+    Extends(ReferenceTypeSignature),
+    /// `? super` the bound.
+    Super(ReferenceTypeSignature),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ThrowsSignature {
+    Class(ClassTypeSignature),
+    TypeVariable(String),
+}
+
+impl ClassSignature {
+    pub fn new(text: &str) -> Result<Self> {
+        let mut parser = Parser::new(text);
+        let type_parameters = parser.type_parameters()?;
+        let super_class = parser.class_type()?;
+        let mut interfaces = Vec::new();
+        while !parser.at_end() {
+            interfaces.push(parser.class_type()?);
+        }
+
+        Ok(Self {
+            type_parameters,
+            super_class,
+            interfaces,
+        })
+    }
+}
+
+impl MethodSignature {
+    pub fn new(text: &str) -> Result<Self> {
+        let mut parser = Parser::new(text);
+        let type_parameters = parser.type_parameters()?;
+
+        parser.expect('(')?;
+        let mut parameters = Vec::new();
+        while !parser.eat(')') {
+            parameters.push(parser.java_type()?);
+        }
+
+        let result = if parser.eat('V') {
+            None
+        } else {
+            Some(parser.java_type()?)
+        };
+
+        let mut throws = Vec::new();
+        while parser.eat('^') {
+            throws.push(match parser.peek()? {
+                'T' => ThrowsSignature::TypeVariable(parser.type_variable()?),
+                _ => ThrowsSignature::Class(parser.class_type()?),
+            });
+        }
+        parser.end()?;
+
+        Ok(Self {
+            type_parameters,
+            parameters,
+            result,
+            throws,
+        })
+    }
+}
+
+impl ReferenceTypeSignature {
+    pub fn new(text: &str) -> Result<Self> {
+        let mut parser = Parser::new(text);
+        let signature = parser.reference_type()?;
+        parser.end()?;
+        Ok(signature)
+    }
+}
+
+impl ClassTypeSignature {
+    /// The binary name of the class in internal form, as in `java/util/Map$Entry`.
+    pub fn name(&self) -> String {
+        let mut name = if self.package.is_empty() {
+            self.class.name.clone()
+        } else {
+            format!("{}/{}", self.package, self.class.name)
+        };
+        for inner_class in &self.inner_classes {
+            name.push('$');
+            name.push_str(&inner_class.name);
+        }
+
+        name
+    }
+}
+
+/// The `Signature` attribute among `attributes`, if any.
+pub(crate) fn signature(
+    attributes: &[Attribute],
+    constant_pool: &ConstantPool,
+) -> Result<Option<String>> {
+    for attribute in attributes {
+        if let Attribute::Signature { signature_index } = attribute {
+            return Ok(Some(constant_pool.utf8(signature_index).context(
+                format!("no utf8 entry at {signature_index:?} in constant pool"),
+            )?));
+        }
+    }
+
+    Ok(None)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, position: 0 }
+    }
+
+    fn at_end(&self) -> bool {
+        self.position == self.text.len()
+    }
+
+    fn end(&self) -> Result<()> {
+        if !self.at_end() {
+            bail!(
+                "invalid signature {}: trailing {}",
+                self.text,
+                &self.text[self.position..]
+            );
+        }
+
+        Ok(())
+    }
+
+    fn peek(&self) -> Result<char> {
+        self.text[self.position..]
+            .chars()
+            .next()
+            .context(format!("invalid signature {}: unexpected end", self.text))
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.text[self.position..].starts_with(c) {
+            self.position += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        if !self.eat(c) {
+            bail!(
+                "invalid signature {}: expected {c} at {}",
+                self.text,
+                self.position
+            );
+        }
+
+        Ok(())
+    }
+
+    fn identifier(&mut self) -> Result<&'a str> {
+        let rest = &self.text[self.position..];
+        let length = rest
+            .find(['.', ';', '[', '/', '<', '>', ':'])
+            .unwrap_or(rest.len());
+        if length == 0 {
+            bail!(
+                "invalid signature {}: expected identifier at {}",
+                self.text,
+                self.position
+            );
+        }
+
+        self.position += length;
+        Ok(&rest[..length])
+    }
+
+    fn type_parameters(&mut self) -> Result<Vec<TypeParameter>> {
+        let mut type_parameters = Vec::new();
+        if !self.eat('<') {
+            return Ok(type_parameters);
+        }
+
+        loop {
+            let name = self.identifier()?.to_string();
+            self.expect(':')?;
+            let class_bound = match self.peek()? {
+                'L' | 'T' | '[' => Some(self.reference_type()?),
+                _ => None,
+            };
+            let mut interface_bounds = Vec::new();
+            while self.eat(':') {
+                interface_bounds.push(self.reference_type()?);
+            }
+
+            type_parameters.push(TypeParameter {
+                name,
+                class_bound,
+                interface_bounds,
+            });
+            if self.eat('>') {
+                return Ok(type_parameters);
+            }
+        }
+    }
+
+    fn java_type(&mut self) -> Result<JavaTypeSignature> {
+        let base = match self.peek()? {
+            'B' => BaseType::Byte,
+            'C' => BaseType::Char,
+            'D' => BaseType::Double,
+            'F' => BaseType::Float,
+            'I' => BaseType::Int,
+            'J' => BaseType::Long,
+            'S' => BaseType::Short,
+            'Z' => BaseType::Boolean,
+            _ => return Ok(JavaTypeSignature::Reference(self.reference_type()?)),
+        };
+
+        self.position += 1;
+        Ok(JavaTypeSignature::Base(base))
+    }
+
+    fn reference_type(&mut self) -> Result<ReferenceTypeSignature> {
+        Ok(match self.peek()? {
+            'L' => ReferenceTypeSignature::Class(self.class_type()?),
+            'T' => ReferenceTypeSignature::TypeVariable(self.type_variable()?),
+            '[' => {
+                self.position += 1;
+                ReferenceTypeSignature::Array(Box::new(self.java_type()?))
+            }
+            c => bail!(
+                "invalid signature {}: unexpected {c} at {}",
+                self.text,
+                self.position
+            ),
+        })
+    }
+
+    fn type_variable(&mut self) -> Result<String> {
+        self.expect('T')?;
+        let name = self.identifier()?.to_string();
+        self.expect(';')?;
+        Ok(name)
+    }
+
+    fn class_type(&mut self) -> Result<ClassTypeSignature> {
+        self.expect('L')?;
+
+        let mut segments = vec![self.identifier()?];
+        while self.eat('/') {
+            segments.push(self.identifier()?);
+        }
+        let name = segments.pop().unwrap_or_default().to_string();
+        let class = self.simple_class_type(name)?;
+
+        let mut inner_classes = Vec::new();
+        while self.eat('.') {
+            let name = self.identifier()?.to_string();
+            inner_classes.push(self.simple_class_type(name)?);
+        }
+        self.expect(';')?;
+
+        Ok(ClassTypeSignature {
+            package: segments.join("/"),
+            class,
+            inner_classes,
+        })
+    }
+
+    fn simple_class_type(&mut self, name: String) -> Result<SimpleClassTypeSignature> {
+        let mut type_arguments = Vec::new();
+        if self.eat('<') {
+            while !self.eat('>') {
+                type_arguments.push(match self.peek()? {
+                    '*' => {
+                        self.position += 1;
+                        TypeArgument::Any
+                    }
+                    '+' => {
+                        self.position += 1;
+                        TypeArgument::Extends(self.reference_type()?)
+                    }
+                    '-' => {
+                        self.position += 1;
+                        TypeArgument::Super(self.reference_type()?)
+                    }
+                    _ => TypeArgument::Exact(self.reference_type()?),
+                });
+            }
+            if type_arguments.is_empty() {
+                bail!("invalid signature {}: empty type arguments", self.text);
+            }
+        }
+
+        Ok(SimpleClassTypeSignature {
+            name,
+            type_arguments,
+        })
+    }
+}
+
+fn write_type_parameters(
+    f: &mut std::fmt::Formatter<'_>,
+    type_parameters: &[TypeParameter],
+) -> std::fmt::Result {
+    if type_parameters.is_empty() {
+        return Ok(());
+    }
+
+    write!(f, "<")?;
+    for type_parameter in type_parameters {
+        write!(f, "{type_parameter}")?;
+    }
+    write!(f, ">")
+}
+
+impl Display for ClassSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_type_parameters(f, &self.type_parameters)?;
+        write!(f, "{}", self.super_class)?;
+        for interface in &self.interfaces {
+            write!(f, "{interface}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for MethodSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_type_parameters(f, &self.type_parameters)?;
+        write!(f, "(")?;
+        for parameter in &self.parameters {
+            write!(f, "{parameter}")?;
+        }
+        write!(f, ")")?;
+        match &self.result {
+            Some(result) => write!(f, "{result}")?,
+            None => write!(f, "V")?,
+        }
+        for throws in &self.throws {
+            write!(f, "^{throws}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for TypeParameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:", self.name)?;
+        if let Some(class_bound) = &self.class_bound {
+            write!(f, "{class_bound}")?;
+        }
+        for interface_bound in &self.interface_bounds {
+            write!(f, ":{interface_bound}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for JavaTypeSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JavaTypeSignature::Base(base) => write!(f, "{base}"),
+            JavaTypeSignature::Reference(reference) => write!(f, "{reference}"),
+        }
+    }
+}
+
+impl Display for BaseType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BaseType::Byte => write!(f, "B"),
+            BaseType::Char => write!(f, "C"),
+            BaseType::Double => write!(f, "D"),
+            BaseType::Float => write!(f, "F"),
+            BaseType::Int => write!(f, "I"),
+            BaseType::Long => write!(f, "J"),
+            BaseType::Short => write!(f, "S"),
+            BaseType::Boolean => write!(f, "Z"),
+        }
+    }
+}
+
+impl Display for ReferenceTypeSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReferenceTypeSignature::Class(class) => write!(f, "{class}"),
+            ReferenceTypeSignature::TypeVariable(name) => write!(f, "T{name};"),
+            ReferenceTypeSignature::Array(component) => write!(f, "[{component}"),
+        }
+    }
+}
+
+impl Display for ClassTypeSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "L")?;
+        if !self.package.is_empty() {
+            write!(f, "{}/", self.package)?;
+        }
+        write!(f, "{}", self.class)?;
+        for inner_class in &self.inner_classes {
+            write!(f, ".{inner_class}")?;
+        }
+        write!(f, ";")
+    }
+}
+
+impl Display for SimpleClassTypeSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.type_arguments.is_empty() {
+            write!(f, "<")?;
+            for type_argument in &self.type_arguments {
+                write!(f, "{type_argument}")?;
+            }
+            write!(f, ">")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for TypeArgument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeArgument::Any => write!(f, "*"),
+            TypeArgument::Exact(bound) => write!(f, "{bound}"),
+            TypeArgument::Extends(bound) => write!(f, "+{bound}"),
+            TypeArgument::Super(bound) => write!(f, "-{bound}"),
+        }
+    }
+}
+
+impl Display for ThrowsSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThrowsSignature::Class(class) => write!(f, "{class}"),
+            ThrowsSignature::TypeVariable(name) => write!(f, "T{name};"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BaseType, ClassSignature, ClassTypeSignature, JavaTypeSignature, MethodSignature,
+        ReferenceTypeSignature, SimpleClassTypeSignature, ThrowsSignature, TypeArgument,
+        TypeParameter,
+    };
+
+    fn class(package: &str, name: &str, type_arguments: Vec<TypeArgument>) -> ClassTypeSignature {
+        ClassTypeSignature {
+            package: package.to_string(),
+            class: SimpleClassTypeSignature {
+                name: name.to_string(),
+                type_arguments,
+            },
+            inner_classes: Vec::new(),
+        }
+    }
+
+    fn variable(name: &str) -> ReferenceTypeSignature {
+        ReferenceTypeSignature::TypeVariable(name.to_string())
+    }
+
+    #[test]
+    fn test_class_signature() {
+        let text = "<K:Ljava/lang/Object;V::Ljava/lang/Comparable<-TV;>;>Ljava/util/AbstractMap<TK;TV;>;Ljava/io/Serializable;";
+        let signature = ClassSignature::new(text).unwrap();
+
+        assert_eq!(
+            signature.type_parameters,
+            vec![
+                TypeParameter {
+                    name: "K".to_string(),
+                    class_bound: Some(ReferenceTypeSignature::Class(class(
+                        "java/lang",
+                        "Object",
+                        vec![]
+                    ))),
+                    interface_bounds: vec![],
+                },
+                TypeParameter {
+                    name: "V".to_string(),
+                    class_bound: None,
+                    interface_bounds: vec![ReferenceTypeSignature::Class(class(
+                        "java/lang",
+                        "Comparable",
+                        vec![TypeArgument::Super(variable("V"))]
+                    ))],
+                },
+            ]
+        );
+        assert_eq!(
+            signature.super_class,
+            class(
+                "java/util",
+                "AbstractMap",
+                vec![
+                    TypeArgument::Exact(variable("K")),
+                    TypeArgument::Exact(variable("V"))
+                ]
+            )
+        );
+        assert_eq!(
+            signature.interfaces,
+            vec![class("java/io", "Serializable", vec![])]
+        );
+        assert_eq!(signature.to_string(), text);
+    }
+
+    #[test]
+    fn test_method_signature() {
+        let text = "<X:Ljava/lang/Throwable;>([ILjava/util/List<*>;Ljava/util/function/Supplier<+TX;>;)V^TX;^Ljava/io/IOException;";
+        let signature = MethodSignature::new(text).unwrap();
+
+        assert_eq!(
+            signature.parameters,
+            vec![
+                JavaTypeSignature::Reference(ReferenceTypeSignature::Array(Box::new(
+                    JavaTypeSignature::Base(BaseType::Int)
+                ))),
+                JavaTypeSignature::Reference(ReferenceTypeSignature::Class(class(
+                    "java/util",
+                    "List",
+                    vec![TypeArgument::Any]
+                ))),
+                JavaTypeSignature::Reference(ReferenceTypeSignature::Class(class(
+                    "java/util/function",
+                    "Supplier",
+                    vec![TypeArgument::Extends(variable("X"))]
+                ))),
+            ]
+        );
+        assert_eq!(signature.result, None);
+        assert_eq!(
+            signature.throws,
+            vec![
+                ThrowsSignature::TypeVariable("X".to_string()),
+                ThrowsSignature::Class(class("java/io", "IOException", vec![]))
+            ]
+        );
+        assert_eq!(signature.to_string(), text);
+    }
+
+    #[test]
+    fn test_inner_class_type_arguments() {
+        let text = "LOuter<TT;>.Inner<Ljava/lang/String;>.Deepest;";
+        let signature = ReferenceTypeSignature::new(text).unwrap();
+
+        let ReferenceTypeSignature::Class(class) = &signature else {
+            panic!("{signature:?} is not a class type");
+        };
+        assert_eq!(class.package, "");
+        assert_eq!(class.name(), "Outer$Inner$Deepest");
+        assert_eq!(class.inner_classes[0].type_arguments.len(), 1);
+        assert_eq!(signature.to_string(), text);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(ReferenceTypeSignature::new("I").is_err());
+        assert!(ReferenceTypeSignature::new("Ljava/lang/Object").is_err());
+        assert!(ReferenceTypeSignature::new("Ljava/util/List<>;").is_err());
+        assert!(MethodSignature::new("()V^I").is_err());
+        assert!(ClassSignature::new("<T>Ljava/lang/Object;").is_err());
+    }
+}
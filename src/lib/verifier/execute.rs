@@ -0,0 +1,729 @@
+use anyhow::{bail, Context as _, Result};
+use std::collections::HashMap;
+
+use crate::parser::{
+    constant_pool::{ConstantPool, ConstantPoolInfo, Index},
+    descriptor::{FieldType, MethodDescriptor, ReturnDescriptor},
+    instruction::{ArrayType, Instruction},
+};
+
+use super::{frame::Frame, types::Type, ClassHierarchy};
+
+/// What the instructions of one method are checked against.
+pub struct Context<'a> {
+    pub class_name: &'a str,
+    pub super_class_name: Option<&'a str>,
+    pub constant_pool: &'a ConstantPool,
+    pub hierarchy: &'a dyn ClassHierarchy,
+    pub return_type: Option<Type>,
+    /// Classes created by the `new` instructions of the method, by offset.
+    pub new_classes: HashMap<u16, String>,
+}
+
+impl Context<'_> {
+    pub fn class(&self, index: &Index) -> Result<String> {
+        class_name(self.constant_pool, index)
+    }
+
+    fn utf8(&self, index: &Index) -> Result<String> {
+        self.constant_pool
+            .utf8(index)
+            .with_context(|| format!("constant pool index {} is not Utf8", index.value()))
+    }
+
+    fn name_and_type(&self, index: &Index) -> Result<(String, String)> {
+        match self.constant_pool.get(index) {
+            Some(ConstantPoolInfo::NameAndType {
+                name_index,
+                descriptor_index,
+            }) => Ok((self.utf8(name_index)?, self.utf8(descriptor_index)?)),
+            _ => bail!("constant pool index {} is not NameAndType", index.value()),
+        }
+    }
+
+    /// Class, name and descriptor of a field or method reference.
+    fn member(&self, index: &Index) -> Result<(String, String, String)> {
+        match self.constant_pool.get(index) {
+            Some(
+                ConstantPoolInfo::FieldRef {
+                    class_index,
+                    name_and_type_index,
+                }
+                | ConstantPoolInfo::MethodRef {
+                    class_index,
+                    name_and_type_index,
+                }
+                | ConstantPoolInfo::InterfaceMethodRef {
+                    class_index,
+                    name_and_type_index,
+                },
+            ) => {
+                let (name, descriptor) = self.name_and_type(name_and_type_index)?;
+                Ok((self.class(class_index)?, name, descriptor))
+            }
+            _ => bail!(
+                "constant pool index {} is not a member reference",
+                index.value()
+            ),
+        }
+    }
+
+    fn field_type(&self, index: &Index) -> Result<(String, Type)> {
+        let (class, _, descriptor) = self.member(index)?;
+        Ok((class, Type::new(&FieldType::new(&descriptor)?)))
+    }
+
+    /// The type pushed by `ldc`, `ldc_w` and `ldc2_w`.
+    fn constant(&self, index: &Index) -> Result<Type> {
+        Ok(match self.constant_pool.get(index) {
+            Some(ConstantPoolInfo::Integer(_)) => Type::Int,
+            Some(ConstantPoolInfo::Float(_)) => Type::Float,
+            Some(ConstantPoolInfo::Long(_)) => Type::Long,
+            Some(ConstantPoolInfo::Double(_)) => Type::Double,
+            Some(ConstantPoolInfo::String { .. }) => Type::class("java/lang/String"),
+            Some(ConstantPoolInfo::ClassInfo { .. }) => Type::class("java/lang/Class"),
+            Some(ConstantPoolInfo::MethodType { .. }) => Type::class("java/lang/invoke/MethodType"),
+            Some(ConstantPoolInfo::MethodHandle { .. }) => {
+                Type::class("java/lang/invoke/MethodHandle")
+            }
+            Some(ConstantPoolInfo::Dynamic {
+                name_and_type_index,
+                ..
+            }) => Type::new(&FieldType::new(
+                &self.name_and_type(name_and_type_index)?.1,
+            )?),
+            _ => bail!("constant pool index {} is not loadable", index.value()),
+        })
+    }
+
+    fn pop_arguments(&self, frame: &mut Frame, descriptor: &MethodDescriptor) -> Result<()> {
+        for parameter in descriptor.parameters.iter().rev() {
+            frame.pop(&Type::new(parameter), self.hierarchy)?;
+        }
+
+        Ok(())
+    }
+
+    fn push_return(&self, frame: &mut Frame, descriptor: &MethodDescriptor) {
+        if let ReturnDescriptor::Type(field_type) = &descriptor.return_descriptor {
+            frame.push(Type::new(field_type));
+        }
+    }
+
+    fn check_return(&self, expected: Option<&Type>) -> Result<()> {
+        if self.return_type.as_ref() != expected {
+            let declared = match &self.return_type {
+                Some(t) => t.to_string(),
+                None => "void".to_string(),
+            };
+            bail!("Method returns {declared}, not the type of this return instruction");
+        }
+
+        Ok(())
+    }
+}
+
+/// The name of a Class constant, in internal form or as an array descriptor.
+pub fn class_name(constant_pool: &ConstantPool, index: &Index) -> Result<String> {
+    constant_pool
+        .class_name(index)
+        .with_context(|| format!("constant pool index {} is not a Class", index.value()))
+}
+
+fn array_type(array_type: &ArrayType) -> Type {
+    Type::class(match array_type {
+        ArrayType::Boolean => "[Z",
+        ArrayType::Char => "[C",
+        ArrayType::Float => "[F",
+        ArrayType::Double => "[D",
+        ArrayType::Byte => "[B",
+        ArrayType::Short => "[S",
+        ArrayType::Int => "[I",
+        ArrayType::Long => "[J",
+    })
+}
+
+/// Pops the index and array of an array load and checks that the array holds `components`.
+fn pop_array_element(frame: &mut Frame, components: &[&str]) -> Result<Type> {
+    frame.pop(&Type::Int, &NoHierarchy)?;
+    let array = frame.pop_array()?;
+
+    match &array {
+        Type::Reference(name) if !components.contains(&&name[1..]) => {
+            bail!("Bad type on operand stack: expected array of {components:?}, found {array}")
+        }
+        _ => Ok(array),
+    }
+}
+
+/// Primitive types are compared without consulting any class.
+struct NoHierarchy;
+
+impl ClassHierarchy for NoHierarchy {
+    fn super_class(&self, class: &str) -> Result<Option<String>> {
+        bail!("unexpected class hierarchy lookup of {class}")
+    }
+
+    fn is_interface(&self, class: &str) -> Result<bool> {
+        bail!("unexpected class hierarchy lookup of {class}")
+    }
+}
+
+/// Applies the effect of one instruction at `offset` to `frame` (JVMS 4.10.1.9).
+///
+/// Returns whether execution can continue with the next instruction; the frame after the
+/// instruction is also the one passed to its branch targets.
+pub fn execute(
+    context: &Context,
+    frame: &mut Frame,
+    offset: usize,
+    instruction: &Instruction,
+) -> Result<bool> {
+    let h = context.hierarchy;
+
+    match instruction {
+        Instruction::Nop => {}
+        Instruction::AconstNull => frame.push(Type::Null),
+        Instruction::IconstM1
+        | Instruction::Iconst0
+        | Instruction::Iconst1
+        | Instruction::Iconst2
+        | Instruction::Iconst3
+        | Instruction::Iconst4
+        | Instruction::Iconst5
+        | Instruction::Bipush(_)
+        | Instruction::Sipush(_) => frame.push(Type::Int),
+        Instruction::Lconst0 | Instruction::Lconst1 => frame.push(Type::Long),
+        Instruction::Fconst0 | Instruction::Fconst1 | Instruction::Fconst2 => {
+            frame.push(Type::Float)
+        }
+        Instruction::Dconst0 | Instruction::Dconst1 => frame.push(Type::Double),
+        Instruction::Ldc(index) | Instruction::LdcW(index) => {
+            let t = context.constant(index)?;
+            if t.size() != 1 {
+                bail!("ldc of a category 2 constant");
+            }
+            frame.push(t);
+        }
+        Instruction::Ldc2W(index) => {
+            let t = context.constant(index)?;
+            if t.size() != 2 {
+                bail!("ldc2_w of a category 1 constant");
+            }
+            frame.push(t);
+        }
+
+        Instruction::Iload(index) => load(frame, *index, Type::Int)?,
+        Instruction::Iload0 => load(frame, 0, Type::Int)?,
+        Instruction::Iload1 => load(frame, 1, Type::Int)?,
+        Instruction::Iload2 => load(frame, 2, Type::Int)?,
+        Instruction::Iload3 => load(frame, 3, Type::Int)?,
+        Instruction::Lload(index) => load(frame, *index, Type::Long)?,
+        Instruction::Lload0 => load(frame, 0, Type::Long)?,
+        Instruction::Lload1 => load(frame, 1, Type::Long)?,
+        Instruction::Lload2 => load(frame, 2, Type::Long)?,
+        Instruction::Lload3 => load(frame, 3, Type::Long)?,
+        Instruction::Fload(index) => load(frame, *index, Type::Float)?,
+        Instruction::Fload0 => load(frame, 0, Type::Float)?,
+        Instruction::Fload1 => load(frame, 1, Type::Float)?,
+        Instruction::Fload2 => load(frame, 2, Type::Float)?,
+        Instruction::Fload3 => load(frame, 3, Type::Float)?,
+        Instruction::Dload(index) => load(frame, *index, Type::Double)?,
+        Instruction::Dload0 => load(frame, 0, Type::Double)?,
+        Instruction::Dload1 => load(frame, 1, Type::Double)?,
+        Instruction::Dload2 => load(frame, 2, Type::Double)?,
+        Instruction::Dload3 => load(frame, 3, Type::Double)?,
+        Instruction::Aload(index) => aload(frame, *index)?,
+        Instruction::Aload0 => aload(frame, 0)?,
+        Instruction::Aload1 => aload(frame, 1)?,
+        Instruction::Aload2 => aload(frame, 2)?,
+        Instruction::Aload3 => aload(frame, 3)?,
+
+        Instruction::Iaload => {
+            pop_array_element(frame, &["I"])?;
+            frame.push(Type::Int);
+        }
+        Instruction::Baload => {
+            pop_array_element(frame, &["B", "Z"])?;
+            frame.push(Type::Int);
+        }
+        Instruction::Caload => {
+            pop_array_element(frame, &["C"])?;
+            frame.push(Type::Int);
+        }
+        Instruction::Saload => {
+            pop_array_element(frame, &["S"])?;
+            frame.push(Type::Int);
+        }
+        Instruction::Laload => {
+            pop_array_element(frame, &["J"])?;
+            frame.push(Type::Long);
+        }
+        Instruction::Faload => {
+            pop_array_element(frame, &["F"])?;
+            frame.push(Type::Float);
+        }
+        Instruction::Daload => {
+            pop_array_element(frame, &["D"])?;
+            frame.push(Type::Double);
+        }
+        Instruction::Aaload => {
+            frame.pop(&Type::Int, h)?;
+            let array = frame.pop_array()?;
+            match array.component() {
+                Some(component) if component.is_reference() => frame.push(component),
+                None => frame.push(Type::Null),
+                Some(_) => bail!("Bad type on operand stack: aaload of {array}"),
+            }
+        }
+
+        Instruction::Istore(index) => store(frame, *index, Type::Int, h)?,
+        Instruction::Istore0 => store(frame, 0, Type::Int, h)?,
+        Instruction::Istore1 => store(frame, 1, Type::Int, h)?,
+        Instruction::Istore2 => store(frame, 2, Type::Int, h)?,
+        Instruction::Istore3 => store(frame, 3, Type::Int, h)?,
+        Instruction::Lstore(index) => store(frame, *index, Type::Long, h)?,
+        Instruction::Lstore0 => store(frame, 0, Type::Long, h)?,
+        Instruction::Lstore1 => store(frame, 1, Type::Long, h)?,
+        Instruction::Lstore2 => store(frame, 2, Type::Long, h)?,
+        Instruction::Lstore3 => store(frame, 3, Type::Long, h)?,
+        Instruction::Fstore(index) => store(frame, *index, Type::Float, h)?,
+        Instruction::Fstore0 => store(frame, 0, Type::Float, h)?,
+        Instruction::Fstore1 => store(frame, 1, Type::Float, h)?,
+        Instruction::Fstore2 => store(frame, 2, Type::Float, h)?,
+        Instruction::Fstore3 => store(frame, 3, Type::Float, h)?,
+        Instruction::Dstore(index) => store(frame, *index, Type::Double, h)?,
+        Instruction::Dstore0 => store(frame, 0, Type::Double, h)?,
+        Instruction::Dstore1 => store(frame, 1, Type::Double, h)?,
+        Instruction::Dstore2 => store(frame, 2, Type::Double, h)?,
+        Instruction::Dstore3 => store(frame, 3, Type::Double, h)?,
+        Instruction::Astore(index) => astore(frame, *index)?,
+        Instruction::Astore0 => astore(frame, 0)?,
+        Instruction::Astore1 => astore(frame, 1)?,
+        Instruction::Astore2 => astore(frame, 2)?,
+        Instruction::Astore3 => astore(frame, 3)?,
+
+        Instruction::Iastore => array_store(frame, Type::Int, &["I"])?,
+        Instruction::Bastore => array_store(frame, Type::Int, &["B", "Z"])?,
+        Instruction::Castore => array_store(frame, Type::Int, &["C"])?,
+        Instruction::Sastore => array_store(frame, Type::Int, &["S"])?,
+        Instruction::Lastore => array_store(frame, Type::Long, &["J"])?,
+        Instruction::Fastore => array_store(frame, Type::Float, &["F"])?,
+        Instruction::Dastore => array_store(frame, Type::Double, &["D"])?,
+        Instruction::Aastore => {
+            // the component type is checked at run time, see JVMS 6.5 aastore
+            frame.pop_reference()?;
+            frame.pop(&Type::Int, h)?;
+            let array = frame.pop_array()?;
+            if array.component().is_some_and(|c| !c.is_reference()) {
+                bail!("Bad type on operand stack: aastore into {array}");
+            }
+        }
+
+        Instruction::Pop => {
+            frame.pop_category1()?;
+        }
+        Instruction::Pop2 => {
+            if frame.pop_value()?.size() == 1 {
+                frame.pop_category1()?;
+            }
+        }
+        Instruction::Dup => {
+            let value = frame.pop_category1()?;
+            frame.push(value.clone());
+            frame.push(value);
+        }
+        Instruction::DupX1 => {
+            let value1 = frame.pop_category1()?;
+            let value2 = frame.pop_category1()?;
+            frame.stack.extend([value1.clone(), value2, value1]);
+        }
+        Instruction::DupX2 => {
+            let value1 = frame.pop_category1()?;
+            let value2 = frame.pop_value()?;
+            if value2.size() == 2 {
+                frame.stack.extend([value1.clone(), value2, value1]);
+            } else {
+                let value3 = frame.pop_category1()?;
+                frame.stack.extend([value1.clone(), value3, value2, value1]);
+            }
+        }
+        Instruction::Dup2 => {
+            let value1 = frame.pop_value()?;
+            if value1.size() == 2 {
+                frame.stack.extend([value1.clone(), value1]);
+            } else {
+                let value2 = frame.pop_category1()?;
+                frame
+                    .stack
+                    .extend([value2.clone(), value1.clone(), value2, value1]);
+            }
+        }
+        Instruction::Dup2X1 => {
+            let value1 = frame.pop_value()?;
+            if value1.size() == 2 {
+                let value2 = frame.pop_category1()?;
+                frame.stack.extend([value1.clone(), value2, value1]);
+            } else {
+                let value2 = frame.pop_category1()?;
+                let value3 = frame.pop_category1()?;
+                frame
+                    .stack
+                    .extend([value2.clone(), value1.clone(), value3, value2, value1]);
+            }
+        }
+        Instruction::Dup2X2 => {
+            let value1 = frame.pop_value()?;
+            if value1.size() == 2 {
+                let value2 = frame.pop_value()?;
+                if value2.size() == 2 {
+                    frame.stack.extend([value1.clone(), value2, value1]);
+                } else {
+                    let value3 = frame.pop_category1()?;
+                    frame.stack.extend([value1.clone(), value3, value2, value1]);
+                }
+            } else {
+                let value2 = frame.pop_category1()?;
+                let value3 = frame.pop_value()?;
+                if value3.size() == 2 {
+                    frame
+                        .stack
+                        .extend([value2.clone(), value1.clone(), value3, value2, value1]);
+                } else {
+                    let value4 = frame.pop_category1()?;
+                    frame.stack.extend([
+                        value2.clone(),
+                        value1.clone(),
+                        value4,
+                        value3,
+                        value2,
+                        value1,
+                    ]);
+                }
+            }
+        }
+        Instruction::Swap => {
+            let value1 = frame.pop_category1()?;
+            let value2 = frame.pop_category1()?;
+            frame.stack.extend([value1, value2]);
+        }
+
+        Instruction::Iadd
+        | Instruction::Isub
+        | Instruction::Imul
+        | Instruction::Idiv
+        | Instruction::Irem
+        | Instruction::Ishl
+        | Instruction::Ishr
+        | Instruction::Iushr
+        | Instruction::Iand
+        | Instruction::Ior
+        | Instruction::Ixor => binary(frame, Type::Int, Type::Int)?,
+        Instruction::Ladd
+        | Instruction::Lsub
+        | Instruction::Lmul
+        | Instruction::Ldiv
+        | Instruction::Lrem
+        | Instruction::Land
+        | Instruction::Lor
+        | Instruction::Lxor => binary(frame, Type::Long, Type::Long)?,
+        Instruction::Lshl | Instruction::Lshr | Instruction::Lushr => {
+            frame.pop(&Type::Int, h)?;
+            frame.pop(&Type::Long, h)?;
+            frame.push(Type::Long);
+        }
+        Instruction::Fadd
+        | Instruction::Fsub
+        | Instruction::Fmul
+        | Instruction::Fdiv
+        | Instruction::Frem => binary(frame, Type::Float, Type::Float)?,
+        Instruction::Dadd
+        | Instruction::Dsub
+        | Instruction::Dmul
+        | Instruction::Ddiv
+        | Instruction::Drem => binary(frame, Type::Double, Type::Double)?,
+        Instruction::Ineg | Instruction::I2b | Instruction::I2c | Instruction::I2s => {
+            unary(frame, Type::Int, Type::Int)?
+        }
+        Instruction::Lneg => unary(frame, Type::Long, Type::Long)?,
+        Instruction::Fneg => unary(frame, Type::Float, Type::Float)?,
+        Instruction::Dneg => unary(frame, Type::Double, Type::Double)?,
+        Instruction::Iinc { index, .. } => {
+            frame.load(*index as usize, &Type::Int, h)?;
+        }
+        Instruction::I2l => unary(frame, Type::Int, Type::Long)?,
+        Instruction::I2f => unary(frame, Type::Int, Type::Float)?,
+        Instruction::I2d => unary(frame, Type::Int, Type::Double)?,
+        Instruction::L2i => unary(frame, Type::Long, Type::Int)?,
+        Instruction::L2f => unary(frame, Type::Long, Type::Float)?,
+        Instruction::L2d => unary(frame, Type::Long, Type::Double)?,
+        Instruction::F2i => unary(frame, Type::Float, Type::Int)?,
+        Instruction::F2l => unary(frame, Type::Float, Type::Long)?,
+        Instruction::F2d => unary(frame, Type::Float, Type::Double)?,
+        Instruction::D2i => unary(frame, Type::Double, Type::Int)?,
+        Instruction::D2l => unary(frame, Type::Double, Type::Long)?,
+        Instruction::D2f => unary(frame, Type::Double, Type::Float)?,
+        Instruction::Lcmp => binary(frame, Type::Long, Type::Int)?,
+        Instruction::Fcmpl | Instruction::Fcmpg => binary(frame, Type::Float, Type::Int)?,
+        Instruction::Dcmpl | Instruction::Dcmpg => binary(frame, Type::Double, Type::Int)?,
+
+        Instruction::Ifeq(_)
+        | Instruction::Ifne(_)
+        | Instruction::Iflt(_)
+        | Instruction::Ifge(_)
+        | Instruction::Ifgt(_)
+        | Instruction::Ifle(_) => {
+            frame.pop(&Type::Int, h)?;
+        }
+        Instruction::IfIcmpeq(_)
+        | Instruction::IfIcmpne(_)
+        | Instruction::IfIcmplt(_)
+        | Instruction::IfIcmpge(_)
+        | Instruction::IfIcmpgt(_)
+        | Instruction::IfIcmple(_) => {
+            frame.pop(&Type::Int, h)?;
+            frame.pop(&Type::Int, h)?;
+        }
+        Instruction::IfAcmpeq(_) | Instruction::IfAcmpne(_) => {
+            frame.pop_reference()?;
+            frame.pop_reference()?;
+        }
+        Instruction::IfNull(_) | Instruction::IfNonNull(_) => {
+            frame.pop_reference()?;
+        }
+        Instruction::Goto(_) | Instruction::GotoW(_) => return Ok(false),
+        Instruction::Jsr(_) | Instruction::JsrW(_) | Instruction::Ret(_) => {
+            bail!("jsr and ret are not allowed in class files of version 50 and above")
+        }
+        Instruction::TableSwitch { .. } | Instruction::LookupSwitch { .. } => {
+            frame.pop(&Type::Int, h)?;
+            return Ok(false);
+        }
+
+        Instruction::Ireturn => {
+            context.check_return(Some(&Type::Int))?;
+            frame.pop(&Type::Int, h)?;
+            return Ok(false);
+        }
+        Instruction::Lreturn => {
+            context.check_return(Some(&Type::Long))?;
+            frame.pop(&Type::Long, h)?;
+            return Ok(false);
+        }
+        Instruction::Freturn => {
+            context.check_return(Some(&Type::Float))?;
+            frame.pop(&Type::Float, h)?;
+            return Ok(false);
+        }
+        Instruction::Dreturn => {
+            context.check_return(Some(&Type::Double))?;
+            frame.pop(&Type::Double, h)?;
+            return Ok(false);
+        }
+        Instruction::Areturn => {
+            match &context.return_type {
+                Some(t @ Type::Reference(_)) => frame.pop(t, h)?,
+                _ => bail!("Method does not return a reference"),
+            };
+            return Ok(false);
+        }
+        Instruction::Return => {
+            context.check_return(None)?;
+            if frame.flag_this_uninit {
+                bail!("Constructor must call super() or this() before return");
+            }
+            return Ok(false);
+        }
+
+        Instruction::GetStatic(index) => {
+            let (_, t) = context.field_type(index)?;
+            frame.push(t);
+        }
+        Instruction::PutStatic(index) => {
+            let (_, t) = context.field_type(index)?;
+            frame.pop(&t, h)?;
+        }
+        Instruction::GetField(index) => {
+            let (class, t) = context.field_type(index)?;
+            frame.pop(&Type::class(&class), h)?;
+            frame.push(t);
+        }
+        Instruction::PutField(index) => {
+            let (class, t) = context.field_type(index)?;
+            frame.pop(&t, h)?;
+            // a constructor may assign its own fields before calling super()
+            let receiver = frame.pop_value()?;
+            let own_field = receiver == Type::UninitializedThis && class == context.class_name;
+            if !own_field && !receiver.is_assignable(&Type::class(&class), h)? {
+                bail!("Bad type on operand stack: expected {class}, found {receiver}");
+            }
+        }
+
+        Instruction::InvokeVirtual(index)
+        | Instruction::InvokeSpecial(index)
+        | Instruction::InvokeStatic(index)
+        | Instruction::InvokeInterface { index, .. } => {
+            let (class, name, descriptor) = context.member(index)?;
+            let descriptor = MethodDescriptor::new(&descriptor)?;
+            let special = matches!(instruction, Instruction::InvokeSpecial(_));
+            if name == "<clinit>" || (name == "<init>" && !special) {
+                bail!("Illegal call to internal method {name}");
+            }
+
+            context.pop_arguments(frame, &descriptor)?;
+            if name == "<init>" {
+                invoke_init(context, frame, &class)?;
+            } else if special {
+                frame.pop(&Type::class(context.class_name), h)?;
+            } else if !matches!(instruction, Instruction::InvokeStatic(_)) {
+                frame.pop(&Type::class(&class), h)?;
+            }
+            context.push_return(frame, &descriptor);
+        }
+        Instruction::InvokeDynamic(index) => {
+            let name_and_type_index = match context.constant_pool.get(index) {
+                Some(ConstantPoolInfo::InvokeDynamic {
+                    name_and_type_index,
+                    ..
+                }) => name_and_type_index,
+                _ => bail!("constant pool index {} is not InvokeDynamic", index.value()),
+            };
+            let (_, descriptor) = context.name_and_type(name_and_type_index)?;
+            let descriptor = MethodDescriptor::new(&descriptor)?;
+            context.pop_arguments(frame, &descriptor)?;
+            context.push_return(frame, &descriptor);
+        }
+
+        Instruction::New(index) => {
+            let class = context.class(index)?;
+            if class.starts_with('[') {
+                bail!("new of array class {class}");
+            }
+
+            let uninitialized = Type::Uninitialized(offset as u16);
+            if frame.locals.contains(&uninitialized) || frame.stack.contains(&uninitialized) {
+                bail!("Uninitialized object from this new instruction is still in use");
+            }
+            frame.push(uninitialized);
+        }
+        Instruction::NewArray(t) => {
+            frame.pop(&Type::Int, h)?;
+            frame.push(array_type(t));
+        }
+        Instruction::ANewArray(index) => {
+            frame.pop(&Type::Int, h)?;
+            frame.push(Type::array_of(&context.class(index)?));
+        }
+        Instruction::MultiANewArray { index, dimensions } => {
+            let class = context.class(index)?;
+            if *dimensions == 0
+                || class.chars().take_while(|c| *c == '[').count() < *dimensions as usize
+            {
+                bail!("multianewarray of {dimensions} dimensions of {class}");
+            }
+            for _ in 0..*dimensions {
+                frame.pop(&Type::Int, h)?;
+            }
+            frame.push(Type::class(&class));
+        }
+        Instruction::ArrayLength => {
+            frame.pop_array()?;
+            frame.push(Type::Int);
+        }
+        Instruction::Athrow => {
+            frame.pop(&Type::class("java/lang/Throwable"), h)?;
+            return Ok(false);
+        }
+        Instruction::CheckCast(index) => {
+            frame.pop(&Type::object(), h)?;
+            frame.push(Type::class(&context.class(index)?));
+        }
+        Instruction::InstanceOf(index) => {
+            context.class(index)?;
+            frame.pop(&Type::object(), h)?;
+            frame.push(Type::Int);
+        }
+        Instruction::MonitorEnter | Instruction::MonitorExit => {
+            frame.pop(&Type::object(), h)?;
+        }
+        Instruction::Wide(instruction) => return execute(context, frame, offset, instruction),
+    }
+
+    Ok(true)
+}
+
+fn load(frame: &mut Frame, index: u16, t: Type) -> Result<()> {
+    frame.load(index as usize, &t, &NoHierarchy)?;
+    if t.size() == 2 {
+        frame.load(index as usize + 1, &Type::Top, &NoHierarchy)?;
+    }
+    frame.push(t);
+    Ok(())
+}
+
+fn aload(frame: &mut Frame, index: u16) -> Result<()> {
+    let t = frame.load(index as usize, &Type::Top, &NoHierarchy)?;
+    if !t.is_reference() {
+        bail!("Bad local variable type: expected reference at {index}, found {t}");
+    }
+    frame.push(t);
+    Ok(())
+}
+
+fn store(frame: &mut Frame, index: u16, t: Type, hierarchy: &dyn ClassHierarchy) -> Result<()> {
+    frame.pop(&t, hierarchy)?;
+    frame.store(index as usize, t)
+}
+
+fn astore(frame: &mut Frame, index: u16) -> Result<()> {
+    let t = frame.pop_value()?;
+    if !(t.is_reference() || matches!(t, Type::ReturnAddress(_))) {
+        bail!("Bad type on operand stack: expected reference, found {t}");
+    }
+    frame.store(index as usize, t)
+}
+
+fn array_store(frame: &mut Frame, value: Type, components: &[&str]) -> Result<()> {
+    frame.pop(&value, &NoHierarchy)?;
+    pop_array_element(frame, components)?;
+    Ok(())
+}
+
+fn unary(frame: &mut Frame, operand: Type, result: Type) -> Result<()> {
+    frame.pop(&operand, &NoHierarchy)?;
+    frame.push(result);
+    Ok(())
+}
+
+fn binary(frame: &mut Frame, operand: Type, result: Type) -> Result<()> {
+    frame.pop(&operand, &NoHierarchy)?;
+    frame.pop(&operand, &NoHierarchy)?;
+    frame.push(result);
+    Ok(())
+}
+
+/// Pops the receiver of a constructor call and marks it initialized (JVMS 4.10.1.9 invokespecial).
+fn invoke_init(context: &Context, frame: &mut Frame, class: &str) -> Result<()> {
+    let receiver = frame.pop_value()?;
+    match &receiver {
+        Type::UninitializedThis => {
+            if class != context.class_name && Some(class) != context.super_class_name {
+                bail!("Bad <init> method call: {class} is neither this class nor its super class");
+            }
+            frame.initialize(&receiver, &Type::class(context.class_name));
+        }
+        Type::Uninitialized(offset) => {
+            let created = context
+                .new_classes
+                .get(offset)
+                .with_context(|| format!("No new instruction at offset {offset}"))?;
+            if created != class {
+                bail!("Bad <init> method call: object of {created} initialized as {class}");
+            }
+            frame.initialize(&receiver, &Type::class(class));
+        }
+        _ => bail!("Bad type on operand stack: expected uninitialized object, found {receiver}"),
+    }
+
+    Ok(())
+}
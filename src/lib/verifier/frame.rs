@@ -0,0 +1,248 @@
+use anyhow::{bail, Result};
+use std::fmt::Display;
+
+use super::{types::Type, ClassHierarchy};
+
+/// The types of the local variables and operand stack at one instruction.
+///
+/// Locals hold one entry per slot, so a long or double is followed by `Top`, while the stack
+/// holds one entry per value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub locals: Vec<Type>,
+    pub stack: Vec<Type>,
+    /// Set in a constructor until `this` has been initialized by another constructor.
+    pub flag_this_uninit: bool,
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let join = |types: &[Type]| {
+            types
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+
+        write!(
+            f,
+            "locals: [{}], stack: [{}]",
+            join(&self.locals),
+            join(&self.stack)
+        )?;
+        if self.flag_this_uninit {
+            write!(f, ", flags: [flagThisUninit]")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Frame {
+    /// Expands locals listed as in a stack map frame, one entry per value, to `max_locals` slots.
+    pub fn new(
+        locals: &[Type],
+        stack: Vec<Type>,
+        max_locals: usize,
+        max_stack: usize,
+    ) -> Result<Self> {
+        let mut slots = Vec::new();
+        for local in locals {
+            slots.push(local.clone());
+            if local.size() == 2 {
+                slots.push(Type::Top);
+            }
+        }
+
+        if slots.len() > max_locals {
+            bail!(
+                "{} local variable slots exceed max_locals {max_locals}",
+                slots.len()
+            );
+        }
+        slots.resize(max_locals, Type::Top);
+
+        let frame = Self {
+            flag_this_uninit: slots.contains(&Type::UninitializedThis),
+            locals: slots,
+            stack,
+        };
+        if frame.stack_size() > max_stack {
+            bail!(
+                "Operand stack of size {} exceeds max_stack {max_stack}",
+                frame.stack_size()
+            );
+        }
+
+        Ok(frame)
+    }
+
+    /// Number of words on the operand stack, counting longs and doubles twice.
+    pub fn stack_size(&self) -> usize {
+        self.stack.iter().map(|t| t.size()).sum()
+    }
+
+    pub fn push(&mut self, t: Type) {
+        self.stack.push(t);
+    }
+
+    /// Pops any value off the operand stack.
+    pub fn pop_value(&mut self) -> Result<Type> {
+        match self.stack.pop() {
+            Some(t) => Ok(t),
+            None => bail!("Operand stack underflow"),
+        }
+    }
+
+    /// Pops a category 1 value, which excludes longs and doubles.
+    pub fn pop_category1(&mut self) -> Result<Type> {
+        let t = self.pop_value()?;
+        if t.size() != 1 {
+            bail!("Bad type on operand stack: expected category 1 value, found {t}");
+        }
+
+        Ok(t)
+    }
+
+    /// Pops a value that is assignable to `expected`.
+    pub fn pop(&mut self, expected: &Type, hierarchy: &dyn ClassHierarchy) -> Result<Type> {
+        let t = self.pop_value()?;
+        if !t.is_assignable(expected, hierarchy)? {
+            bail!("Bad type on operand stack: expected {expected}, found {t}");
+        }
+
+        Ok(t)
+    }
+
+    /// Pops a reference of any type, including uninitialized ones.
+    pub fn pop_reference(&mut self) -> Result<Type> {
+        let t = self.pop_value()?;
+        if !t.is_reference() {
+            bail!("Bad type on operand stack: expected reference, found {t}");
+        }
+
+        Ok(t)
+    }
+
+    /// Pops an array reference or null.
+    pub fn pop_array(&mut self) -> Result<Type> {
+        let t = self.pop_value()?;
+        if !(t.is_array() || t == Type::Null) {
+            bail!("Bad type on operand stack: expected array, found {t}");
+        }
+
+        Ok(t)
+    }
+
+    /// The type of a local variable, which has to be assignable to `expected`.
+    pub fn load(
+        &self,
+        index: usize,
+        expected: &Type,
+        hierarchy: &dyn ClassHierarchy,
+    ) -> Result<Type> {
+        let Some(t) = self.locals.get(index) else {
+            bail!("Local variable index {index} out of bounds");
+        };
+        if !t.is_assignable(expected, hierarchy)? {
+            bail!("Bad local variable type: expected {expected} at {index}, found {t}");
+        }
+
+        Ok(t.clone())
+    }
+
+    /// Stores a value in a local variable, invalidating a long or double it partially overwrites.
+    pub fn store(&mut self, index: usize, t: Type) -> Result<()> {
+        if index + t.size() > self.locals.len() {
+            bail!("Local variable index {index} out of bounds");
+        }
+
+        if index > 0 && self.locals[index - 1].size() == 2 {
+            self.locals[index - 1] = Type::Top;
+        }
+        if t.size() == 2 {
+            self.locals[index + 1] = Type::Top;
+        }
+        self.locals[index] = t;
+
+        Ok(())
+    }
+
+    /// Replaces every occurrence of an uninitialized type once its constructor has been invoked.
+    pub fn initialize(&mut self, uninitialized: &Type, initialized: &Type) {
+        for t in self.locals.iter_mut().chain(self.stack.iter_mut()) {
+            if t == uninitialized {
+                *t = initialized.clone();
+            }
+        }
+
+        if *uninitialized == Type::UninitializedThis {
+            self.flag_this_uninit = false;
+        }
+    }
+
+    /// The frame at an exception handler, which sees the locals of this frame and the exception.
+    pub fn handler(&self, exception: Type) -> Frame {
+        Frame {
+            locals: self.locals.clone(),
+            stack: vec![exception],
+            flag_this_uninit: self.flag_this_uninit,
+        }
+    }
+
+    /// Whether this frame may flow into an instruction whose frame is `to` (JVMS 4.10.1.4).
+    pub fn is_assignable(&self, to: &Frame, hierarchy: &dyn ClassHierarchy) -> Result<bool> {
+        if self.locals.len() != to.locals.len()
+            || self.stack.len() != to.stack.len()
+            || (self.flag_this_uninit && !to.flag_this_uninit)
+        {
+            return Ok(false);
+        }
+
+        for (from, to) in self
+            .locals
+            .iter()
+            .zip(&to.locals)
+            .chain(self.stack.iter().zip(&to.stack))
+        {
+            if !from.is_assignable(to, hierarchy)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// The frame of an instruction reached from both frames, used by type inference
+    /// (JVMS 4.10.2.2). Locals that disagree become unusable, the stacks have to agree.
+    pub fn merge(&self, other: &Frame, hierarchy: &dyn ClassHierarchy) -> Result<Frame> {
+        if self.stack.len() != other.stack.len() {
+            bail!(
+                "Inconsistent stack height {} != {}",
+                self.stack.len(),
+                other.stack.len()
+            );
+        }
+
+        let mut stack = Vec::new();
+        for (a, b) in self.stack.iter().zip(&other.stack) {
+            let merged = a.merge(b, hierarchy)?;
+            if merged == Type::Top {
+                bail!("Mismatched stack types {a} and {b}");
+            }
+            stack.push(merged);
+        }
+
+        let mut locals = Vec::new();
+        for (a, b) in self.locals.iter().zip(&other.locals) {
+            locals.push(a.merge(b, hierarchy)?);
+        }
+
+        Ok(Frame {
+            locals,
+            stack,
+            flag_this_uninit: self.flag_this_uninit || other.flag_this_uninit,
+        })
+    }
+}
@@ -0,0 +1,319 @@
+use anyhow::{bail, Context as _, Result};
+use std::{cmp::Ordering, collections::HashMap};
+
+use crate::parser::{
+    attribute::Attribute,
+    class::ClassFile,
+    descriptor::{MethodDescriptor, ReturnDescriptor},
+    instruction::Instruction,
+    method::{Method, MethodFlag},
+};
+
+use execute::{class_name, Context};
+use frame::Frame;
+use type_checker::TypeChecker;
+use type_inference::TypeInference;
+use types::Type;
+
+mod execute;
+mod frame;
+mod type_checker;
+mod type_inference;
+pub mod types;
+
+/// First class file major version whose methods are verified by type checking. Methods of
+/// this version that fail type checking are verified again by type inference (JVMS 4.10).
+const TYPE_CHECKING_MAJOR_VERSION: u16 = 50;
+
+/// The classes the verifier has to know about to decide whether one reference type is
+/// assignable to another.
+pub trait ClassHierarchy {
+    /// The direct super class of a class in internal form, `None` for `java/lang/Object`.
+    fn super_class(&self, class: &str) -> Result<Option<String>>;
+    fn is_interface(&self, class: &str) -> Result<bool>;
+}
+
+/// Verifies the bytecode of every method of a class file (JVMS 4.10), by type checking
+/// against the StackMapTable or by type inference for class files before version 50.
+pub fn verify(class_file: &ClassFile, hierarchy: &dyn ClassHierarchy) -> Result<()> {
+    let constant_pool = &class_file.constant_pool;
+    let class = class_name(constant_pool, &class_file.this_class_index)?;
+    let super_class = if class_file.super_class_index.is_zero() {
+        None
+    } else {
+        Some(class_name(constant_pool, &class_file.super_class_index)?)
+    };
+
+    for method in &class_file.methods {
+        let name = constant_pool
+            .utf8(&method.name_index)
+            .context("method name is not Utf8")?;
+        let descriptor = constant_pool
+            .utf8(&method.descriptor_index)
+            .context("method descriptor is not Utf8")?;
+
+        verify_method(
+            &class,
+            super_class.as_deref(),
+            class_file,
+            method,
+            &name,
+            &descriptor,
+            hierarchy,
+        )
+        .with_context(|| {
+            format!(
+                "VerifyError: {}.{name}{descriptor}",
+                class.replace('/', ".")
+            )
+        })?;
+    }
+
+    Ok(())
+}
+
+fn verify_method(
+    class: &str,
+    super_class: Option<&str>,
+    class_file: &ClassFile,
+    method: &Method,
+    name: &str,
+    descriptor: &str,
+    hierarchy: &dyn ClassHierarchy,
+) -> Result<()> {
+    let Some((max_stack, max_locals, code, exceptions, attributes)) = method
+        .attributes
+        .iter()
+        .find_map(|attribute| match attribute {
+            Attribute::Code {
+                max_stacks,
+                max_locals,
+                code,
+                exceptions,
+                attributes,
+            } => Some((*max_stacks, *max_locals, code, exceptions, attributes)),
+            _ => None,
+        })
+    else {
+        return Ok(());
+    };
+
+    let descriptor = MethodDescriptor::new(descriptor)?;
+    let instructions = Instruction::decode(code)?;
+
+    let mut new_classes = HashMap::new();
+    for (offset, instruction) in &instructions {
+        if let Instruction::New(index) = instruction {
+            new_classes.insert(
+                *offset as u16,
+                class_name(&class_file.constant_pool, index)?,
+            );
+        }
+    }
+
+    let context = Context {
+        class_name: class,
+        super_class_name: super_class,
+        constant_pool: &class_file.constant_pool,
+        hierarchy,
+        return_type: match &descriptor.return_descriptor {
+            ReturnDescriptor::Type(field_type) => Some(Type::new(field_type)),
+            ReturnDescriptor::Void => None,
+        },
+        new_classes,
+    };
+
+    // the implicit initial frame of JVMS 4.10.1.6, one entry per argument
+    let mut locals = Vec::new();
+    if !method.access_flags.contains(&MethodFlag::Static) {
+        if name == "<init>" && super_class.is_some() {
+            locals.push(Type::UninitializedThis);
+        } else {
+            locals.push(Type::class(class));
+        }
+    }
+    locals.extend(descriptor.parameters.iter().map(Type::new));
+    let initial = Frame::new(&locals, Vec::new(), max_locals as usize, max_stack as usize)?;
+
+    let type_check = || {
+        TypeChecker::new(&context, &instructions, exceptions, max_stack as usize)
+            .check(&initial, locals, attributes)
+    };
+    let infer = || {
+        TypeInference::new(&context, &instructions, exceptions, max_stack as usize).check(&initial)
+    };
+    let result = match class_file.version.major.cmp(&TYPE_CHECKING_MAJOR_VERSION) {
+        Ordering::Less => infer(),
+        Ordering::Equal => type_check().or_else(|_| infer()),
+        Ordering::Greater => type_check(),
+    };
+
+    result.or_else(|(offset, frame, err)| bail!("at offset {offset}: {err}\n  frame: {frame}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{bail, Result};
+    use std::fs;
+
+    use super::{verify, ClassHierarchy};
+    use crate::{
+        parser::{
+            attribute::Attribute,
+            class::{ClassFile, Version},
+        },
+        ClassIdentifier,
+    };
+
+    struct Hierarchy;
+
+    impl ClassHierarchy for Hierarchy {
+        fn super_class(&self, class: &str) -> Result<Option<String>> {
+            Ok(match class {
+                "java/lang/Object" => None,
+                "Main" | "java/lang/String" | "java/io/PrintStream" => {
+                    Some("java/lang/Object".to_string())
+                }
+                _ => bail!("unknown class {class}"),
+            })
+        }
+
+        fn is_interface(&self, _: &str) -> Result<bool> {
+            Ok(false)
+        }
+    }
+
+    fn main_class() -> ClassFile {
+        let data = fs::read("testdata/Main.class").unwrap();
+        ClassFile::new(
+            &data,
+            ClassIdentifier::from_utf8("Main".to_string()).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn code<'a>(class_file: &'a mut ClassFile, method: &str) -> &'a mut Vec<u8> {
+        let name_index = class_file.constant_pool.utf8_index(method).unwrap();
+        let method = class_file
+            .methods
+            .iter_mut()
+            .find(|m| m.name_index == name_index)
+            .unwrap();
+        method
+            .attributes
+            .iter_mut()
+            .find_map(|attribute| match attribute {
+                Attribute::Code { code, .. } => Some(code),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    /// Main as a legacy class file whose main method calls a subroutine from two places, with
+    /// an int and then a String in local 1.
+    fn subroutine_class(subroutine: &[u8]) -> ClassFile {
+        let mut class_file = main_class();
+        class_file.version = Version::new(49, 0);
+
+        let main = class_file.constant_pool.utf8_index("main").unwrap();
+        let method = class_file
+            .methods
+            .iter_mut()
+            .find(|m| m.name_index == main)
+            .unwrap();
+        for attribute in &mut method.attributes {
+            if let Attribute::Code {
+                max_stacks,
+                max_locals,
+                code,
+                ..
+            } = attribute
+            {
+                *max_stacks = 1;
+                *max_locals = 3;
+                *code = vec![
+                    0x04, // iconst_1
+                    0x3c, // istore_1
+                    0xa8, 0x00, 0x0d, // jsr 15
+                    0x1b, // iload_1
+                    0x57, // pop
+                    0x2a, // aload_0
+                    0x4c, // astore_1
+                    0xa8, 0x00, 0x06, // jsr 15
+                    0x2b, // aload_1
+                    0x57, // pop
+                    0xb1, // return
+                    0x4d, // astore_2
+                ];
+                code.extend(subroutine);
+                code.extend([0xa9, 0x02]); // ret 2
+            }
+        }
+
+        class_file
+    }
+
+    #[test]
+    fn test_verify() {
+        verify(&main_class(), &Hierarchy).unwrap();
+    }
+
+    #[test]
+    fn test_bad_operand() {
+        let mut class_file = main_class();
+        // aload_0; invokespecial Object.<init> becomes fconst_0; invokespecial
+        code(&mut class_file, "<init>")[0] = 0x0b;
+
+        let err = verify(&class_file, &Hierarchy).unwrap_err();
+        let message = format!("{err:#}");
+        assert!(message.starts_with("VerifyError: Main.<init>()V: at offset 1:"));
+        assert!(message.contains("expected uninitialized object, found float"));
+        assert!(message.contains("frame: locals: [uninitializedThis], stack: [float]"));
+    }
+
+    #[test]
+    fn test_uninitialized_return() {
+        let mut class_file = main_class();
+        // aload_0; invokespecial; return becomes nop x4; return
+        code(&mut class_file, "<init>")[..4].fill(0x00);
+
+        let err = verify(&class_file, &Hierarchy).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("Constructor must call super() or this() before return")
+        );
+    }
+
+    #[test]
+    fn test_infer_bad_operand() {
+        let mut class_file = main_class();
+        class_file.version = Version::new(49, 0);
+        code(&mut class_file, "<init>")[0] = 0x0b;
+
+        let err = verify(&class_file, &Hierarchy).unwrap_err();
+        assert!(format!("{err:#}").contains("expected uninitialized object, found float"));
+    }
+
+    #[test]
+    fn test_subroutine() {
+        verify(&subroutine_class(&[]), &Hierarchy).unwrap();
+    }
+
+    #[test]
+    fn test_subroutine_stores_local() {
+        // aconst_null; astore_1 in the subroutine replaces the int of the first caller
+        let err = verify(&subroutine_class(&[0x01, 0x4c]), &Hierarchy).unwrap_err();
+        let message = format!("{err:#}");
+        assert!(
+            message.contains("at offset 5: Bad local variable type: expected int at 1, found null")
+        );
+    }
+
+    #[test]
+    fn test_jsr_with_stack_map() {
+        let mut class_file = subroutine_class(&[]);
+        class_file.version = Version::new(51, 0);
+
+        let err = verify(&class_file, &Hierarchy).unwrap_err();
+        assert!(format!("{err:#}").contains("jsr and ret are not allowed"));
+    }
+}
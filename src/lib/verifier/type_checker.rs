@@ -0,0 +1,263 @@
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+
+use crate::parser::{
+    attribute::{
+        exception::Exception,
+        stack_map_frame::{StackMapFrame, VerificationType},
+        Attribute,
+    },
+    constant_pool::Index,
+    instruction::Instruction,
+};
+
+use super::{
+    execute::{class_name, execute, Context},
+    frame::Frame,
+    types::Type,
+};
+
+/// The JVMS 4.10.1 type checker, which verifies a method against the frames of its
+/// StackMapTable instead of inferring them.
+pub struct TypeChecker<'a> {
+    context: &'a Context<'a>,
+    instructions: &'a [(usize, Instruction)],
+    exceptions: &'a [Exception],
+    max_stack: usize,
+}
+
+impl<'a> TypeChecker<'a> {
+    pub fn new(
+        context: &'a Context<'a>,
+        instructions: &'a [(usize, Instruction)],
+        exceptions: &'a [Exception],
+        max_stack: usize,
+    ) -> Self {
+        Self {
+            context,
+            instructions,
+            exceptions,
+            max_stack,
+        }
+    }
+
+    /// Checks every instruction, returning the offset and frame where verification failed.
+    pub fn check(
+        &self,
+        initial: &Frame,
+        locals: Vec<Type>,
+        attributes: &[Attribute],
+    ) -> std::result::Result<(), (usize, Frame, anyhow::Error)> {
+        let frames = self
+            .stack_map(locals, initial.locals.len(), attributes)
+            .map_err(|err| (0, initial.clone(), err))?;
+
+        let mut current = Some(initial.clone());
+        for (offset, instruction) in self.instructions {
+            let frame = match (frames.get(offset), current) {
+                (Some(stack_map_frame), Some(frame)) => {
+                    self.check_target(&frame, stack_map_frame)
+                        .map_err(|err| (*offset, frame, err))?;
+                    stack_map_frame.clone()
+                }
+                (Some(stack_map_frame), None) => stack_map_frame.clone(),
+                (None, Some(frame)) => frame,
+                (None, None) => {
+                    return Err((
+                        *offset,
+                        initial.clone(),
+                        anyhow::anyhow!("Expecting a stackmap frame at branch target or after an unconditional branch"),
+                    ))
+                }
+            };
+
+            current = self
+                .instruction(&frames, &frame, *offset, instruction)
+                .map_err(|err| (*offset, frame, err))?;
+        }
+
+        if let (Some(frame), Some((offset, _))) = (current, self.instructions.last()) {
+            return Err((
+                *offset,
+                frame,
+                anyhow::anyhow!("Control flow falls through code end"),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Checks one instruction and the frames it flows into, returning the frame of the next
+    /// instruction or `None` if execution cannot continue there.
+    fn instruction(
+        &self,
+        frames: &BTreeMap<usize, Frame>,
+        frame: &Frame,
+        offset: usize,
+        instruction: &Instruction,
+    ) -> Result<Option<Frame>> {
+        for exception in self.exceptions {
+            if (exception.start_pc as usize..exception.end_pc as usize).contains(&offset) {
+                let catch_type = if exception.catch_type == 0 {
+                    Type::class("java/lang/Throwable")
+                } else {
+                    let catch_type =
+                        Type::class(&self.context.class(&Index::new(exception.catch_type))?);
+                    if !catch_type.is_assignable(
+                        &Type::class("java/lang/Throwable"),
+                        self.context.hierarchy,
+                    )? {
+                        bail!("Catch type {catch_type} is not a subclass of Throwable");
+                    }
+                    catch_type
+                };
+
+                let handler = exception.handler_pc as usize;
+                let Some(target) = frames.get(&handler) else {
+                    bail!("Expecting a stackmap frame at exception handler {handler}");
+                };
+                let handler_frame = frame.handler(catch_type);
+                if !handler_frame.is_assignable(target, self.context.hierarchy)? {
+                    bail!("Type is not assignable to the stack map frame of exception handler {handler}\n  expected: {target}\n  actual: {handler_frame}");
+                }
+            }
+        }
+
+        let mut next = frame.clone();
+        let falls_through = execute(self.context, &mut next, offset, instruction)?;
+        if next.stack_size() > self.max_stack {
+            bail!("Operand stack overflow");
+        }
+
+        for target in instruction.branch_targets(offset) {
+            match frames.get(&target) {
+                Some(stack_map_frame) => self.check_target(&next, stack_map_frame)?,
+                None => bail!("Expecting a stackmap frame at branch target {target}"),
+            }
+        }
+
+        Ok(falls_through.then_some(next))
+    }
+
+    fn check_target(&self, frame: &Frame, target: &Frame) -> Result<()> {
+        if !frame.is_assignable(target, self.context.hierarchy)? {
+            bail!("Type is not assignable to stack map frame\n  expected: {target}\n  actual: {frame}");
+        }
+
+        Ok(())
+    }
+
+    /// Expands the StackMapTable into full frames by offset (JVMS 4.7.4).
+    fn stack_map(
+        &self,
+        mut locals: Vec<Type>,
+        max_locals: usize,
+        attributes: &[Attribute],
+    ) -> Result<BTreeMap<usize, Frame>> {
+        let mut frames = BTreeMap::new();
+        let Some(entries) = attributes.iter().find_map(|attribute| match attribute {
+            Attribute::StackMapTable { entries } => Some(entries),
+            _ => None,
+        }) else {
+            return Ok(frames);
+        };
+
+        let mut offset: Option<usize> = None;
+        for entry in entries {
+            let (delta, stack) = match entry {
+                StackMapFrame::SameFrame { offset_delta } => (*offset_delta as usize, Vec::new()),
+                StackMapFrame::SameLocals {
+                    offset_delta,
+                    verification_type,
+                } => (
+                    *offset_delta as usize,
+                    vec![self.verification_type(verification_type)?],
+                ),
+                StackMapFrame::SameLocalsExtended {
+                    offset_delta,
+                    verification_type,
+                } => (
+                    *offset_delta as usize,
+                    vec![self.verification_type(verification_type)?],
+                ),
+                StackMapFrame::Chop { k, offset_delta } => {
+                    if *k as usize > locals.len() {
+                        bail!("StackMapTable chops {k} of {} locals", locals.len());
+                    }
+                    locals.truncate(locals.len() - *k as usize);
+                    (*offset_delta as usize, Vec::new())
+                }
+                StackMapFrame::SameExtended { offset_delta } => {
+                    (*offset_delta as usize, Vec::new())
+                }
+                StackMapFrame::Append {
+                    offset_delta,
+                    locals: appended,
+                } => {
+                    for local in appended {
+                        locals.push(self.verification_type(local)?);
+                    }
+                    (*offset_delta as usize, Vec::new())
+                }
+                StackMapFrame::Full {
+                    offset_delta,
+                    locals: full,
+                    stack_items,
+                } => {
+                    locals = full
+                        .iter()
+                        .map(|local| self.verification_type(local))
+                        .collect::<Result<Vec<Type>>>()?;
+                    (
+                        *offset_delta as usize,
+                        stack_items
+                            .iter()
+                            .map(|item| self.verification_type(item))
+                            .collect::<Result<Vec<Type>>>()?,
+                    )
+                }
+            };
+
+            let frame_offset = match offset {
+                Some(offset) => offset + delta + 1,
+                None => delta,
+            };
+            if !self
+                .instructions
+                .iter()
+                .any(|(offset, _)| *offset == frame_offset)
+            {
+                bail!("StackMapTable frame at offset {frame_offset} is not at an instruction");
+            }
+
+            frames.insert(
+                frame_offset,
+                Frame::new(&locals, stack, max_locals, self.max_stack)?,
+            );
+            offset = Some(frame_offset);
+        }
+
+        Ok(frames)
+    }
+
+    fn verification_type(&self, verification_type: &VerificationType) -> Result<Type> {
+        Ok(match verification_type {
+            VerificationType::Top => Type::Top,
+            VerificationType::Integer => Type::Int,
+            VerificationType::Float => Type::Float,
+            VerificationType::Double => Type::Double,
+            VerificationType::Long => Type::Long,
+            VerificationType::Null => Type::Null,
+            VerificationType::UninitializedThis => Type::UninitializedThis,
+            VerificationType::Object { cpool_index } => {
+                Type::class(&class_name(self.context.constant_pool, cpool_index)?)
+            }
+            VerificationType::Uninitialized { offset } => {
+                if !self.context.new_classes.contains_key(offset) {
+                    bail!("StackMapTable refers to uninitialized object at {offset} without a new instruction");
+                }
+                Type::Uninitialized(*offset)
+            }
+        })
+    }
+}
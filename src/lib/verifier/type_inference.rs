@@ -0,0 +1,316 @@
+use anyhow::{anyhow, bail, Result};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::parser::{
+    attribute::exception::Exception, constant_pool::Index, instruction::Instruction,
+};
+
+use super::{
+    execute::{execute, Context},
+    frame::Frame,
+    types::Type,
+};
+
+/// The JVMS 4.10.2 type inference verifier for class files without a StackMapTable, which
+/// computes the frame of every instruction by data-flow analysis.
+///
+/// Subroutines called by `jsr` are verified once for all callers. At a `ret`, locals the
+/// subroutine does not store to keep the types they had at each calling `jsr`.
+pub struct TypeInference<'a> {
+    context: &'a Context<'a>,
+    instructions: &'a [(usize, Instruction)],
+    exceptions: &'a [Exception],
+    max_stack: usize,
+    /// Position of each instruction in `instructions` by offset.
+    positions: HashMap<usize, usize>,
+    frames: HashMap<usize, Frame>,
+    changed: BTreeSet<usize>,
+    /// The merged frames at the `ret` instructions of each subroutine, by subroutine entry.
+    returns: HashMap<usize, Frame>,
+    /// Locals stored to by each subroutine, including the subroutines it calls.
+    stored: HashMap<usize, HashSet<usize>>,
+}
+
+impl<'a> TypeInference<'a> {
+    pub fn new(
+        context: &'a Context<'a>,
+        instructions: &'a [(usize, Instruction)],
+        exceptions: &'a [Exception],
+        max_stack: usize,
+    ) -> Self {
+        Self {
+            context,
+            instructions,
+            exceptions,
+            max_stack,
+            positions: instructions
+                .iter()
+                .enumerate()
+                .map(|(position, (offset, _))| (*offset, position))
+                .collect(),
+            frames: HashMap::new(),
+            changed: BTreeSet::new(),
+            returns: HashMap::new(),
+            stored: HashMap::new(),
+        }
+    }
+
+    /// Infers the frame of every instruction, returning the offset and frame where
+    /// verification failed.
+    pub fn check(
+        mut self,
+        initial: &Frame,
+    ) -> std::result::Result<(), (usize, Frame, anyhow::Error)> {
+        self.frames.insert(0, initial.clone());
+        self.changed.insert(0);
+
+        while let Some(offset) = self.changed.pop_first() {
+            let frame = self.frames[&offset].clone();
+            let (_, instruction) = &self.instructions[self.positions[&offset]];
+            self.instruction(&frame, offset, instruction)
+                .map_err(|err| (offset, frame, err))?;
+        }
+
+        Ok(())
+    }
+
+    fn instruction(
+        &mut self,
+        frame: &Frame,
+        offset: usize,
+        instruction: &Instruction,
+    ) -> Result<()> {
+        self.handlers(frame, offset)?;
+
+        match instruction {
+            Instruction::Jsr(_) | Instruction::JsrW(_) => {
+                let entry = instruction.branch_targets(offset)[0];
+                let mut called = frame.clone();
+                called.push(Type::ReturnAddress(entry));
+                self.check_stack(&called)?;
+                self.merge(entry, &called)?;
+
+                if let Some(returned) = self.returns.get(&entry).cloned() {
+                    let next = self.next(offset)?;
+                    let frame = self.returned(frame, &returned, entry)?;
+                    self.merge(next, &frame)?;
+                }
+            }
+            Instruction::Ret(index) => self.ret(frame, *index as usize)?,
+            Instruction::Wide(wide) if matches!(**wide, Instruction::Ret(_)) => {
+                self.instruction(frame, offset, wide)?
+            }
+            _ => {
+                let mut next = frame.clone();
+                let falls_through = execute(self.context, &mut next, offset, instruction)?;
+                self.check_stack(&next)?;
+                // stores inside a try block are seen by its handlers as well
+                self.handlers(&next, offset)?;
+
+                for target in instruction.branch_targets(offset) {
+                    self.merge(target, &next)?;
+                }
+                if falls_through {
+                    let next_offset = self.next(offset)?;
+                    self.merge(next_offset, &next)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns from the subroutine whose return address is in local `index` to every caller.
+    fn ret(&mut self, frame: &Frame, index: usize) -> Result<()> {
+        let entry = match frame.locals.get(index) {
+            Some(Type::ReturnAddress(entry)) => *entry,
+            Some(t) => {
+                bail!("Bad local variable type: expected returnAddress at {index}, found {t}")
+            }
+            None => bail!("Local variable index {index} out of bounds"),
+        };
+
+        let returned = match self.returns.get(&entry) {
+            Some(returned) => returned.merge(frame, self.context.hierarchy)?,
+            None => frame.clone(),
+        };
+        self.returns.insert(entry, returned.clone());
+
+        let callers: Vec<usize> = self
+            .instructions
+            .iter()
+            .filter(|(offset, instruction)| {
+                matches!(instruction, Instruction::Jsr(_) | Instruction::JsrW(_))
+                    && instruction.branch_targets(*offset) == [entry]
+                    && self.frames.contains_key(offset)
+            })
+            .map(|(offset, _)| *offset)
+            .collect();
+        for caller in callers {
+            let next = self.next(caller)?;
+            let frame = self.returned(&self.frames[&caller].clone(), &returned, entry)?;
+            self.merge(next, &frame)?;
+        }
+
+        Ok(())
+    }
+
+    /// The frame after a `jsr` returns: the locals the subroutine stores to come from its
+    /// `ret`, all others from the caller.
+    fn returned(&mut self, caller: &Frame, returned: &Frame, entry: usize) -> Result<Frame> {
+        let stored = self.stored(entry, &mut Vec::new())?;
+        let mut frame = returned.clone();
+        for (i, local) in frame.locals.iter_mut().enumerate() {
+            if !stored.contains(&i) {
+                *local = caller.locals[i].clone();
+            }
+        }
+        // a long or double of the caller is lost if the subroutine stored to its second half
+        for i in 1..frame.locals.len() {
+            if frame.locals[i - 1].size() == 2 && frame.locals[i] != Type::Top {
+                frame.locals[i - 1] = Type::Top;
+            }
+        }
+        frame.flag_this_uninit |= caller.flag_this_uninit;
+
+        Ok(frame)
+    }
+
+    /// Locals written by the subroutine at `entry`, found by following its control flow up to
+    /// its `ret` instructions.
+    fn stored(&mut self, entry: usize, calling: &mut Vec<usize>) -> Result<HashSet<usize>> {
+        if let Some(stored) = self.stored.get(&entry) {
+            return Ok(stored.clone());
+        }
+        if calling.contains(&entry) {
+            bail!("Recursive call to subroutine at {entry}");
+        }
+        calling.push(entry);
+
+        let mut stored = HashSet::new();
+        let mut visited = HashSet::new();
+        let mut pending = vec![entry];
+        while let Some(offset) = pending.pop() {
+            if !visited.insert(offset) {
+                continue;
+            }
+            let Some(position) = self.positions.get(&offset) else {
+                bail!("Branch target {offset} is not an instruction");
+            };
+            let (_, instruction) = &self.instructions[*position];
+
+            let instruction = match instruction {
+                Instruction::Wide(wide) => wide,
+                instruction => instruction,
+            };
+            if let Some((index, size)) = stored_local(instruction) {
+                stored.extend(index..index + size);
+            }
+
+            match instruction {
+                Instruction::Jsr(_) | Instruction::JsrW(_) => {
+                    let called = instruction.branch_targets(offset)[0];
+                    stored.extend(self.stored(called, calling)?);
+                    pending.push(self.next(offset)?);
+                }
+                Instruction::Ret(_)
+                | Instruction::Goto(_)
+                | Instruction::GotoW(_)
+                | Instruction::TableSwitch { .. }
+                | Instruction::LookupSwitch { .. }
+                | Instruction::Ireturn
+                | Instruction::Lreturn
+                | Instruction::Freturn
+                | Instruction::Dreturn
+                | Instruction::Areturn
+                | Instruction::Return
+                | Instruction::Athrow => pending.extend(instruction.branch_targets(offset)),
+                _ => {
+                    pending.extend(instruction.branch_targets(offset));
+                    pending.push(self.next(offset)?);
+                }
+            }
+        }
+
+        calling.pop();
+        self.stored.insert(entry, stored.clone());
+        Ok(stored)
+    }
+
+    /// Merges the handler frames of the exception handlers covering an instruction.
+    fn handlers(&mut self, frame: &Frame, offset: usize) -> Result<()> {
+        for exception in self.exceptions {
+            if !(exception.start_pc as usize..exception.end_pc as usize).contains(&offset) {
+                continue;
+            }
+
+            let throwable = Type::class("java/lang/Throwable");
+            let catch_type = if exception.catch_type == 0 {
+                throwable
+            } else {
+                let catch_type =
+                    Type::class(&self.context.class(&Index::new(exception.catch_type))?);
+                if !catch_type.is_assignable(&throwable, self.context.hierarchy)? {
+                    bail!("Catch type {catch_type} is not a subclass of Throwable");
+                }
+                catch_type
+            };
+
+            self.merge(exception.handler_pc as usize, &frame.handler(catch_type))?;
+        }
+
+        Ok(())
+    }
+
+    fn merge(&mut self, offset: usize, frame: &Frame) -> Result<()> {
+        if !self.positions.contains_key(&offset) {
+            bail!("Branch target {offset} is not an instruction");
+        }
+
+        let merged = match self.frames.get(&offset) {
+            Some(current) => current.merge(frame, self.context.hierarchy)?,
+            None => frame.clone(),
+        };
+        if self.frames.get(&offset) != Some(&merged) {
+            self.frames.insert(offset, merged);
+            self.changed.insert(offset);
+        }
+
+        Ok(())
+    }
+
+    fn check_stack(&self, frame: &Frame) -> Result<()> {
+        if frame.stack_size() > self.max_stack {
+            bail!("Operand stack overflow");
+        }
+
+        Ok(())
+    }
+
+    /// The offset of the instruction following the one at `offset`.
+    fn next(&self, offset: usize) -> Result<usize> {
+        self.instructions
+            .get(self.positions[&offset] + 1)
+            .map(|(offset, _)| *offset)
+            .ok_or_else(|| anyhow!("Control flow falls through code end"))
+    }
+}
+
+/// The local variable and number of slots written by a store instruction.
+fn stored_local(instruction: &Instruction) -> Option<(usize, usize)> {
+    Some(match instruction {
+        Instruction::Istore(index) | Instruction::Fstore(index) | Instruction::Astore(index) => {
+            (*index as usize, 1)
+        }
+        Instruction::Lstore(index) | Instruction::Dstore(index) => (*index as usize, 2),
+        Instruction::Istore0 | Instruction::Fstore0 | Instruction::Astore0 => (0, 1),
+        Instruction::Istore1 | Instruction::Fstore1 | Instruction::Astore1 => (1, 1),
+        Instruction::Istore2 | Instruction::Fstore2 | Instruction::Astore2 => (2, 1),
+        Instruction::Istore3 | Instruction::Fstore3 | Instruction::Astore3 => (3, 1),
+        Instruction::Lstore0 | Instruction::Dstore0 => (0, 2),
+        Instruction::Lstore1 | Instruction::Dstore1 => (1, 2),
+        Instruction::Lstore2 | Instruction::Dstore2 => (2, 2),
+        Instruction::Lstore3 | Instruction::Dstore3 => (3, 2),
+        _ => return None,
+    })
+}
@@ -0,0 +1,299 @@
+use anyhow::Result;
+use std::fmt::Display;
+
+use crate::parser::descriptor::FieldType;
+
+use super::ClassHierarchy;
+
+const OBJECT: &str = "java/lang/Object";
+
+/// The verification type lattice of JVMS 4.10.1.2. References are named by their class in
+/// internal form, arrays by their descriptor, as in `java/lang/String` and `[I`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Type {
+    Top,
+    Int,
+    Float,
+    Long,
+    Double,
+    Null,
+    Reference(String),
+    UninitializedThis,
+    /// An object created by the `new` instruction at this offset, not yet initialized.
+    Uninitialized(u16),
+    /// The address pushed by `jsr`, only found in class files verified by type inference.
+    ReturnAddress(usize),
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Top => write!(f, "top"),
+            Type::Int => write!(f, "int"),
+            Type::Float => write!(f, "float"),
+            Type::Long => write!(f, "long"),
+            Type::Double => write!(f, "double"),
+            Type::Null => write!(f, "null"),
+            Type::Reference(name) => write!(f, "{name}"),
+            Type::UninitializedThis => write!(f, "uninitializedThis"),
+            Type::Uninitialized(offset) => write!(f, "uninitialized({offset})"),
+            Type::ReturnAddress(target) => write!(f, "returnAddress({target})"),
+        }
+    }
+}
+
+impl Type {
+    pub fn object() -> Self {
+        Type::Reference(OBJECT.to_string())
+    }
+
+    pub fn new(field_type: &FieldType) -> Self {
+        match field_type {
+            FieldType::Byte
+            | FieldType::Char
+            | FieldType::Int
+            | FieldType::Short
+            | FieldType::Boolean => Type::Int,
+            FieldType::Float => Type::Float,
+            FieldType::Long => Type::Long,
+            FieldType::Double => Type::Double,
+            FieldType::Class(name) => Type::Reference(name.clone()),
+            FieldType::Array(_) => Type::Reference(field_type.to_string()),
+        }
+    }
+
+    /// Type of a Class constant, whose name is an array descriptor for array classes.
+    pub fn class(name: &str) -> Self {
+        Type::Reference(name.to_string())
+    }
+
+    /// Number of local variable slots or operand stack words the type takes up.
+    pub fn size(&self) -> usize {
+        match self {
+            Type::Long | Type::Double => 2,
+            _ => 1,
+        }
+    }
+
+    pub fn is_reference(&self) -> bool {
+        matches!(
+            self,
+            Type::Null | Type::Reference(_) | Type::UninitializedThis | Type::Uninitialized(_)
+        )
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self, Type::Reference(name) if name.starts_with('['))
+    }
+
+    /// The component type of an array type, `None` for anything else.
+    pub fn component(&self) -> Option<Type> {
+        match self {
+            Type::Reference(name) => name
+                .strip_prefix('[')
+                .and_then(|component| FieldType::new(component).ok())
+                .map(|component| Type::new(&component)),
+            _ => None,
+        }
+    }
+
+    /// The array type with this component type, named by the `anewarray` operand.
+    pub fn array_of(name: &str) -> Type {
+        if name.starts_with('[') {
+            Type::Reference(format!("[{name}"))
+        } else {
+            Type::Reference(format!("[L{name};"))
+        }
+    }
+
+    /// Whether a value of this type may be used where `to` is expected (JVMS 4.10.1.2).
+    pub fn is_assignable(&self, to: &Type, hierarchy: &dyn ClassHierarchy) -> Result<bool> {
+        if self == to || *to == Type::Top {
+            return Ok(true);
+        }
+
+        Ok(match (self, to) {
+            (Type::Null, Type::Reference(_)) => true,
+            (Type::Reference(from), Type::Reference(to)) => {
+                is_java_assignable(from, to, hierarchy)?
+            }
+            _ => false,
+        })
+    }
+
+    /// The least upper bound of two types, used when type inference merges frames.
+    pub fn merge(&self, other: &Type, hierarchy: &dyn ClassHierarchy) -> Result<Type> {
+        if self == other {
+            return Ok(self.clone());
+        }
+
+        Ok(match (self, other) {
+            (Type::Null, Type::Reference(_)) => other.clone(),
+            (Type::Reference(_), Type::Null) => self.clone(),
+            (Type::Reference(a), Type::Reference(b)) => {
+                Type::Reference(common_super_class(a, b, hierarchy)?)
+            }
+            _ => Type::Top,
+        })
+    }
+}
+
+fn is_java_assignable(from: &str, to: &str, hierarchy: &dyn ClassHierarchy) -> Result<bool> {
+    if from == to || to == OBJECT {
+        return Ok(true);
+    }
+
+    if let Some(to_component) = to.strip_prefix('[') {
+        let Some(from_component) = from.strip_prefix('[') else {
+            return Ok(false);
+        };
+        return match (reference_name(from_component), reference_name(to_component)) {
+            (Some(from), Some(to)) => is_java_assignable(&from, &to, hierarchy),
+            _ => Ok(from_component == to_component),
+        };
+    }
+
+    if from.starts_with('[') {
+        return Ok(matches!(to, "java/lang/Cloneable" | "java/io/Serializable"));
+    }
+
+    // interfaces are treated like java/lang/Object, the check is deferred to run time
+    if hierarchy.is_interface(to)? {
+        return Ok(true);
+    }
+
+    let mut class = from.to_string();
+    while let Some(super_class) = hierarchy.super_class(&class)? {
+        if super_class == to {
+            return Ok(true);
+        }
+        class = super_class;
+    }
+
+    Ok(false)
+}
+
+/// The class name of a reference component descriptor, `None` for primitive components.
+fn reference_name(descriptor: &str) -> Option<String> {
+    if descriptor.starts_with('[') {
+        Some(descriptor.to_string())
+    } else {
+        descriptor
+            .strip_prefix('L')
+            .and_then(|name| name.strip_suffix(';'))
+            .map(|name| name.to_string())
+    }
+}
+
+fn common_super_class(a: &str, b: &str, hierarchy: &dyn ClassHierarchy) -> Result<String> {
+    if a.starts_with('[') || b.starts_with('[') {
+        if let (Some(a), Some(b)) = (a.strip_prefix('['), b.strip_prefix('[')) {
+            if let (Some(a), Some(b)) = (reference_name(a), reference_name(b)) {
+                let component = common_super_class(&a, &b, hierarchy)?;
+                return Ok(match Type::array_of(&component) {
+                    Type::Reference(name) => name,
+                    _ => unreachable!(),
+                });
+            }
+        }
+        return Ok(OBJECT.to_string());
+    }
+
+    if hierarchy.is_interface(a)? || hierarchy.is_interface(b)? {
+        return Ok(OBJECT.to_string());
+    }
+
+    let mut ancestors = vec![a.to_string()];
+    while let Some(super_class) = hierarchy.super_class(ancestors.last().unwrap())? {
+        ancestors.push(super_class);
+    }
+
+    let mut class = b.to_string();
+    loop {
+        if ancestors.contains(&class) {
+            return Ok(class);
+        }
+        match hierarchy.super_class(&class)? {
+            Some(super_class) => class = super_class,
+            None => return Ok(OBJECT.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{bail, Result};
+
+    use super::Type;
+    use crate::verifier::ClassHierarchy;
+
+    struct Hierarchy;
+
+    impl ClassHierarchy for Hierarchy {
+        fn super_class(&self, class: &str) -> Result<Option<String>> {
+            Ok(match class {
+                "java/lang/Object" => None,
+                "java/lang/Integer" => Some("java/lang/Number".to_string()),
+                "java/lang/Number" | "java/lang/String" => Some("java/lang/Object".to_string()),
+                _ => bail!("unknown class {class}"),
+            })
+        }
+
+        fn is_interface(&self, class: &str) -> Result<bool> {
+            Ok(class == "java/lang/Comparable")
+        }
+    }
+
+    fn reference(name: &str) -> Type {
+        Type::Reference(name.to_string())
+    }
+
+    #[test]
+    fn test_is_assignable() {
+        let integer = reference("java/lang/Integer");
+        let number = reference("java/lang/Number");
+
+        assert!(integer.is_assignable(&number, &Hierarchy).unwrap());
+        assert!(!number.is_assignable(&integer, &Hierarchy).unwrap());
+        assert!(integer
+            .is_assignable(&reference("java/lang/Comparable"), &Hierarchy)
+            .unwrap());
+        assert!(Type::Null.is_assignable(&integer, &Hierarchy).unwrap());
+        assert!(!Type::Int.is_assignable(&Type::Float, &Hierarchy).unwrap());
+        assert!(Type::Long.is_assignable(&Type::Top, &Hierarchy).unwrap());
+        assert!(reference("[Ljava/lang/Integer;")
+            .is_assignable(&reference("[Ljava/lang/Number;"), &Hierarchy)
+            .unwrap());
+        assert!(!reference("[I")
+            .is_assignable(&reference("[J"), &Hierarchy)
+            .unwrap());
+        assert!(reference("[I")
+            .is_assignable(&reference("java/io/Serializable"), &Hierarchy)
+            .unwrap());
+        assert!(!Type::Uninitialized(3)
+            .is_assignable(&Type::object(), &Hierarchy)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_merge() {
+        let integer = reference("java/lang/Integer");
+        let string = reference("java/lang/String");
+
+        assert_eq!(integer.merge(&string, &Hierarchy).unwrap(), Type::object());
+        assert_eq!(
+            integer
+                .merge(&reference("java/lang/Number"), &Hierarchy)
+                .unwrap(),
+            reference("java/lang/Number")
+        );
+        assert_eq!(Type::Null.merge(&string, &Hierarchy).unwrap(), string);
+        assert_eq!(Type::Int.merge(&string, &Hierarchy).unwrap(), Type::Top);
+        assert_eq!(
+            reference("[Ljava/lang/Integer;")
+                .merge(&reference("[Ljava/lang/String;"), &Hierarchy)
+                .unwrap(),
+            reference("[Ljava/lang/Object;")
+        );
+    }
+}
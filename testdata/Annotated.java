@@ -0,0 +1,36 @@
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.lang.annotation.Target;
+import java.util.List;
+
+@Deprecated
+@Annotated.Info(b = 1, c = 'c', d = 1.5, f = 2.5f, i = 3, j = 4L, s = 5, z = true, str = "text",
+		e = ElementType.FIELD, cls = String.class, nested = @Retention(RetentionPolicy.CLASS), arr = { 1, 2 })
+public class Annotated {
+	@Retention(RetentionPolicy.RUNTIME)
+	@Target({ ElementType.TYPE, ElementType.PARAMETER, ElementType.TYPE_USE })
+	@interface Info {
+		byte b() default 0;
+		char c() default 'a';
+		double d() default 0.0;
+		float f() default 0.0f;
+		int i() default 0;
+		long j() default 0L;
+		short s() default 0;
+		boolean z() default false;
+		String str() default "default";
+		ElementType e() default ElementType.TYPE;
+		Class<?> cls() default Object.class;
+		Retention nested() default @Retention(RetentionPolicy.RUNTIME);
+		int[] arr() default {};
+	}
+
+	@Retention(RetentionPolicy.CLASS)
+	@interface Invisible {
+	}
+
+	@Invisible
+	public void method(@Info int param, List<@Info String> list) {
+	}
+}
@@ -0,0 +1,19 @@
+public sealed interface Shape permits Shape.Circle, Shape.Square {
+	double area();
+
+	record Circle(double radius, String label) implements Shape {
+		public double area() {
+			return Math.PI * radius * radius;
+		}
+
+		public Circle scale(final double factor) {
+			return new Circle(radius * factor, label);
+		}
+	}
+
+	final class Square implements Shape {
+		public double area() {
+			return 1;
+		}
+	}
+}
@@ -0,0 +1,9 @@
+package com.example;
+
+public class Main implements Runnable {
+	public static void main(String[] args) {
+	}
+
+	public void run() {
+	}
+}
@@ -0,0 +1,4 @@
+package com.example.internal;
+
+public class Helper {
+}
@@ -0,0 +1,11 @@
+module com.example {
+	requires transitive java.logging;
+	requires static java.sql;
+
+	exports com.example;
+	exports com.example.internal to java.base;
+	opens com.example.internal;
+
+	uses java.lang.Runnable;
+	provides java.lang.Runnable with com.example.Main;
+}
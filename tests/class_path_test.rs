@@ -0,0 +1,124 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use jvm::{
+    assembler::{assemble, write_classes},
+    ClassIdentifier, ClassPath,
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+/// Writes a jar at `path` holding the assembled `sources` and an optional manifest.
+fn jar(path: &Path, manifest: Option<&str>, sources: &[&str]) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let mut writer = ZipWriter::new(File::create(path).unwrap());
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+    if let Some(manifest) = manifest {
+        writer.start_file("META-INF/MANIFEST.MF", options).unwrap();
+        writer.write_all(manifest.as_bytes()).unwrap();
+    }
+    for source in sources {
+        let class_file = assemble(source).unwrap();
+        let name = format!(
+            "{}.class",
+            class_file.this_class.to_string().replace('.', "/")
+        );
+        writer.start_file(name, options).unwrap();
+        writer.write_all(&class_file.to_bytes().unwrap()).unwrap();
+    }
+    writer.finish().unwrap();
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("jvm-class-path-test-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+const MAIN: &str = "
+    .class public app/Main
+    .method public static main([Ljava/lang/String;)V
+        .limit stack 1
+        ldc dep/Dep
+        return
+    .end method
+    ";
+
+const DEP: &str = ".class public dep/Dep";
+
+#[test]
+fn test_find_in_jar() {
+    let dir = temp_dir("find");
+    let app = dir.join("app.jar");
+    jar(&app, None, &[MAIN, DEP]);
+
+    let class_path = ClassPath::load(vec![app]).unwrap();
+    let find = |name: &str| {
+        class_path
+            .find(&ClassIdentifier::from_utf8(name.to_string()).unwrap())
+            .unwrap()
+    };
+    assert_eq!(&find("app.Main").unwrap()[..4], &[0xCA, 0xFE, 0xBA, 0xBE]);
+
+    // classes not in the jar fall through to the JDK
+    assert!(find("java.lang.Object").is_some());
+
+    assert_eq!(find("app.Missing"), None);
+    assert_eq!(find("java.lang.Missing"), None);
+    assert_eq!(find("Missing"), None);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_manifest_class_path() {
+    let dir = temp_dir("manifest");
+    let app = dir.join("app.jar");
+    let manifest = "Manifest-Version: 1.0\r\n\
+                    Class-Path: missing.jar lib/de\r\n p.jar app.jar\r\n\
+                    \r\n";
+    jar(&app, Some(manifest), &[MAIN]);
+    jar(&dir.join("lib/dep.jar"), None, &[DEP]);
+
+    jvm::run(
+        vec![app],
+        ClassIdentifier::from_utf8("app.Main".to_string()).unwrap(),
+    )
+    .unwrap();
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_find_in_directory_by_package() {
+    let dir = temp_dir("packages");
+    write_classes(&dir, &[MAIN, DEP, ".class public other/Main"]).unwrap();
+
+    let class_path = ClassPath::load(vec![dir.clone()]).unwrap();
+    let find = |name: &str| {
+        class_path
+            .find(&ClassIdentifier::from_utf8(name.to_string()).unwrap())
+            .unwrap()
+    };
+    assert_eq!(
+        find("app.Main"),
+        Some(fs::read(dir.join("app/Main.class")).unwrap())
+    );
+    assert_eq!(
+        find("other.Main"),
+        Some(fs::read(dir.join("other/Main.class")).unwrap())
+    );
+    assert_ne!(find("app.Main"), find("other.Main"));
+
+    jvm::run(
+        vec![dir.clone()],
+        ClassIdentifier::from_utf8("app.Main".to_string()).unwrap(),
+    )
+    .unwrap();
+
+    fs::remove_dir_all(&dir).unwrap();
+}
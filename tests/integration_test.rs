@@ -24,3 +24,15 @@ fn test_main_no_main() {
     )
     .unwrap();
 }
+
+#[test]
+fn test_main_missing() {
+    let package = Package::default();
+    let name = ClassName::new("Missing".to_string());
+    let error = jvm::run(
+        vec![PathBuf::from("testdata/")],
+        ClassIdentifier::new(package, name),
+    )
+    .unwrap_err();
+    assert_eq!(error.to_string(), "NoClassDefFoundError: <default>.Missing");
+}
@@ -0,0 +1,34 @@
+use std::process::Command;
+
+fn javap(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_jvm-javap"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_class_file() {
+    let output = javap(&["testdata/Main.class"]);
+
+    assert!(output.contains("major version: 61"));
+    assert!(output.contains("flags: (0x0021) ACC_PUBLIC, ACC_SUPER"));
+    assert!(output.contains("#13 = String             #14          // Hello world."));
+    assert!(output.contains(
+        "5: invokevirtual #15                // Method java/io/PrintStream.println:(Ljava/lang/String;)V"
+    ));
+    assert!(output.contains("line 4: 8"));
+    assert!(output.contains("SourceFile: \"Main.java\""));
+}
+
+#[test]
+fn test_class_in_jmod() {
+    let output = javap(&["java.util.Formatter"]);
+
+    assert!(output.contains("Classfile java/util/Formatter"));
+    assert!(output.contains("tableswitch   { // -2 to 0"));
+    assert!(output.contains("StackMapTable: number_of_entries"));
+    assert!(output.contains("Exception table:"));
+}
@@ -0,0 +1,35 @@
+//! Kept in its own test binary since it points `JAVA_HOME` at a JDK without a runtime image for
+//! the whole process.
+
+use std::{env, fs, os::unix::fs::symlink, path::PathBuf};
+
+use jvm::{ClassIdentifier, ClassPath};
+
+#[test]
+fn test_jmods_fallback() {
+    let java_home = PathBuf::from(env::var("JAVA_HOME").unwrap());
+    let jdk = env::temp_dir().join(format!("jvm-jmods-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&jdk);
+    fs::create_dir_all(&jdk).unwrap();
+    symlink(java_home.join("jmods"), jdk.join("jmods")).unwrap();
+    env::set_var("JAVA_HOME", &jdk);
+
+    let class_path = ClassPath::load(vec![]).unwrap();
+    let find = |name: &str| {
+        class_path
+            .find(&ClassIdentifier::from_utf8(name.to_string()).unwrap())
+            .unwrap()
+    };
+    let object = find("java.lang.Object").unwrap();
+    assert_eq!(&object[..4], &[0xca, 0xfe, 0xba, 0xbe]);
+    assert!(find("java.sql.Connection").is_some());
+    assert!(find("java.lang.Missing").is_none());
+    assert!(find("no.such.Package").is_none());
+
+    fs::remove_dir_all(&jdk).unwrap();
+    let error = ClassPath::load(vec![]).err().unwrap();
+    assert_eq!(
+        error.to_string(),
+        "neither lib/modules nor jmods/ found in $JAVA_HOME"
+    );
+}
@@ -0,0 +1,293 @@
+use std::fs;
+
+use anyhow::Result;
+use jvm::{assembler::write_classes, ClassIdentifier};
+
+/// Assembles `source` into its own class path directory and runs the class's main method.
+fn run(name: &str, source: &str) -> Result<()> {
+    run_with(name, &[source])
+}
+
+/// Assembles `sources` into one class path directory and runs the main method of `name`.
+fn run_with(name: &str, sources: &[&str]) -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("jvm-opcode-test-{}-{name}", std::process::id()));
+    write_classes(&dir, sources)?;
+
+    let result = jvm::run(
+        vec![dir.clone()],
+        ClassIdentifier::from_utf8(name.to_string()).unwrap(),
+    );
+    fs::remove_dir_all(&dir)?;
+    result
+}
+
+/// A class whose main method consists of `code`.
+fn main_class(name: &str, code: &str) -> String {
+    format!(
+        "
+        .class public {name}
+        .method public static main([Ljava/lang/String;)V
+            .limit stack 4
+            {code}
+        .end method
+        "
+    )
+}
+
+#[test]
+fn test_return() {
+    run("Return", &main_class("Return", "return")).unwrap();
+}
+
+#[test]
+fn test_iconst_0() {
+    run("Iconst0", &main_class("Iconst0", "iconst_0\nreturn")).unwrap();
+}
+
+#[test]
+fn test_ldc_class() {
+    run("LdcClass", &main_class("LdcClass", "ldc LdcClass\nreturn")).unwrap();
+}
+
+#[test]
+fn test_anewarray() {
+    let code = "
+        iconst_0
+        anewarray java/lang/Object
+        return
+    ";
+    run("ANewArray", &main_class("ANewArray", code)).unwrap();
+}
+
+#[test]
+fn test_new() {
+    run(
+        "New",
+        "
+        .class public New
+        .method public static main([Ljava/lang/String;)V
+            .limit stack 2
+            new New
+            dup
+            invokespecial New/<init>()V
+            return
+        .end method
+        .method <init>()V
+            .limit stack 1
+            aload_0
+            invokespecial java/lang/Object/<init>()V
+            return
+        .end method
+        ",
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_invokestatic() {
+    // the callee runs, but returning into the caller is not implemented yet
+    let err = run(
+        "InvokeStatic",
+        "
+        .class public InvokeStatic
+        .method public static main([Ljava/lang/String;)V
+            invokestatic InvokeStatic/callee()V
+            return
+        .end method
+        .method static callee()V
+            return
+        .end method
+        ",
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "after invoke_static has executed its code"
+    );
+}
+
+#[test]
+fn test_invokevirtual() {
+    // only native methods can be invoked on an object so far
+    let err = run(
+        "InvokeVirtual",
+        "
+        .class public InvokeVirtual
+        .method public static main([Ljava/lang/String;)V
+            .limit stack 2
+            new InvokeVirtual
+            dup
+            invokespecial InvokeVirtual/<init>()V
+            invokevirtual InvokeVirtual/run()V
+            return
+        .end method
+        .method <init>()V
+            .limit stack 1
+            aload_0
+            invokespecial java/lang/Object/<init>()V
+            return
+        .end method
+        .method run()V
+            return
+        .end method
+        ",
+    )
+    .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "invokevirtual objectref??");
+}
+
+#[test]
+fn test_getstatic() {
+    // getstatic resolves the field, but does not push its value yet
+    let err = run(
+        "GetStatic",
+        "
+        .class public GetStatic
+        .field static value I = 1
+        .method public static main([Ljava/lang/String;)V
+            .limit stack 1
+            getstatic GetStatic/value I
+            return
+        .end method
+        ",
+    )
+    .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "execute_getstatic");
+}
+
+#[test]
+fn test_ifne() {
+    // ifne pops its operand, but does not branch yet
+    let code = "
+        iconst_0
+        ifne Done
+    Done:
+        return
+    ";
+    let err = run("Ifne", &main_class("Ifne", code)).unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "ifne");
+}
+
+#[test]
+fn test_putstatic() {
+    run(
+        "PutStatic",
+        "
+        .class public PutStatic
+        .field static value I
+        .method public static main([Ljava/lang/String;)V
+            return
+        .end method
+        .method static <clinit>()V
+            .limit stack 1
+            iconst_0
+            putstatic PutStatic/value I
+            return
+        .end method
+        ",
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_putstatic_final_outside_clinit() {
+    let err = run(
+        "PutStaticFinal",
+        "
+        .class public PutStaticFinal
+        .field static final value I = 1
+        .method public static main([Ljava/lang/String;)V
+            .limit stack 1
+            iconst_0
+            putstatic PutStaticFinal/value I
+            return
+        .end method
+        ",
+    )
+    .unwrap_err();
+    assert!(err
+        .root_cause()
+        .to_string()
+        .starts_with("IllegalAccessError"));
+}
+
+#[test]
+fn test_putstatic_final_other_clinit() {
+    let err = run_with(
+        "PutStaticOther",
+        &[
+            "
+            .class public PutStaticOther
+            .method public static main([Ljava/lang/String;)V
+                return
+            .end method
+            .method static <clinit>()V
+                .limit stack 1
+                iconst_0
+                putstatic PutStaticOwner/value I
+                return
+            .end method
+            ",
+            "
+            .class public PutStaticOwner
+            .field public static final value I = 1
+            ",
+        ],
+    )
+    .unwrap_err();
+    assert!(err
+        .root_cause()
+        .to_string()
+        .starts_with("IllegalAccessError"));
+}
+
+/// A nest host with a private static field, and a nestmate and a stranger reading it.
+const NEST: [&str; 3] = [
+    "
+    .class public Host
+    .nestmember Host$Member
+    .field private static secret I
+    ",
+    "
+    .class public Host$Member
+    .nesthost Host
+    .method public static main([Ljava/lang/String;)V
+        .limit stack 1
+        getstatic Host/secret I
+        return
+    .end method
+    ",
+    "
+    .class public Stranger
+    .method public static main([Ljava/lang/String;)V
+        .limit stack 1
+        getstatic Host/secret I
+        return
+    .end method
+    ",
+];
+
+#[test]
+fn test_private_access_in_nest() {
+    // getstatic itself is not implemented yet, it has to fail after the access check
+    let err = run_with("Host$Member", &NEST).unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "execute_getstatic");
+
+    let err = run_with("Stranger", &NEST).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "IllegalAccessError: <default>.Stranger cannot access private member secret I of <default>.Host"
+    );
+}
+
+#[test]
+fn test_unknown_op() {
+    let err = run("UnknownOp", &main_class("UnknownOp", "iconst_1\nreturn")).unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "unknown op iconst_1");
+}
+
+#[test]
+fn test_verify_error() {
+    let err = run("VerifyError", &main_class("VerifyError", "pop\nreturn")).unwrap_err();
+    assert!(format!("{err:#}").contains("VerifyError"), "{err:#}");
+}
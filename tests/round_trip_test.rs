@@ -0,0 +1,155 @@
+use std::{env, fs::File, io::Read, path::PathBuf};
+
+use jvm::{
+    parser::{
+        attribute::Attribute, class::ClassFile, class_ref::ClassFileRef, instruction::Instruction,
+    },
+    ClassIdentifier,
+};
+use zip::ZipArchive;
+
+fn java_base_classes() -> Vec<(ClassIdentifier, Vec<u8>)> {
+    let jmod = PathBuf::from(env::var("JAVA_HOME").unwrap()).join("jmods/java.base.jmod");
+    let mut archive = ZipArchive::new(File::open(jmod).unwrap()).unwrap();
+
+    let mut classes = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).unwrap();
+        let name = entry.name().to_string();
+        if let Some(class_name) = name
+            .strip_prefix("classes/")
+            .and_then(|n| n.strip_suffix(".class"))
+        {
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data).unwrap();
+            classes.push((
+                ClassIdentifier::from_utf8(class_name.to_string()).unwrap(),
+                data,
+            ));
+        }
+    }
+
+    classes
+}
+
+#[test]
+fn test_round_trip_java_base() {
+    let mut round_tripped = 0;
+    for (identifier, data) in java_base_classes() {
+        let class_file = ClassFile::new(&data, identifier.clone()).unwrap();
+
+        class_file.check().unwrap();
+
+        let written = class_file.to_bytes().unwrap();
+        assert!(written == data, "{identifier} does not round trip");
+
+        let reparsed = ClassFile::new(&written, identifier).unwrap();
+        assert_eq!(reparsed.to_bytes().unwrap(), written);
+        round_tripped += 1;
+    }
+
+    assert!(round_tripped > 6000);
+}
+
+#[test]
+fn test_decode_java_base() {
+    let mut decoded = 0;
+    for (identifier, data) in java_base_classes() {
+        let class_file = ClassFile::new(&data, identifier.clone()).unwrap();
+
+        for method in &class_file.methods {
+            for attribute in &method.attributes {
+                if let Attribute::Code { code, .. } = attribute {
+                    let instructions = Instruction::decode(code).unwrap();
+                    let mut written = Vec::new();
+                    for (_, instruction) in &instructions {
+                        instruction.write(&mut written).unwrap();
+                    }
+                    assert!(written == *code, "{identifier} does not re-encode");
+                    decoded += 1;
+                }
+            }
+        }
+    }
+
+    assert!(decoded > 50000);
+}
+
+#[test]
+fn test_signatures_java_base() {
+    let mut parsed = 0;
+    for (identifier, data) in java_base_classes() {
+        let class_file = ClassFile::new(&data, identifier.clone()).unwrap();
+        let cp = &class_file.constant_pool;
+
+        let mut signatures = Vec::new();
+        if let Some(signature) = class_file.signature().unwrap() {
+            signatures.push((signature.to_string(), &class_file.attributes));
+        }
+        for method in &class_file.methods {
+            if let Some(signature) = method.signature(cp).unwrap() {
+                signatures.push((signature.to_string(), &method.attributes));
+            }
+        }
+        for field in &class_file.fields {
+            if let Some(signature) = field.signature(cp).unwrap() {
+                signatures.push((signature.to_string(), &field.attributes));
+            }
+        }
+
+        for (signature, attributes) in signatures {
+            let original = attributes
+                .iter()
+                .find_map(|attribute| match attribute {
+                    Attribute::Signature { signature_index } => cp.utf8(signature_index),
+                    _ => None,
+                })
+                .unwrap();
+            assert_eq!(signature, original, "{identifier}");
+            parsed += 1;
+        }
+    }
+
+    assert!(parsed > 10000);
+}
+
+#[test]
+fn test_class_ref_java_base() {
+    for (identifier, data) in java_base_classes() {
+        let class_ref = ClassFileRef::new(&data).unwrap();
+        let class_file = ClassFile::new(&data, identifier.clone()).unwrap();
+        let cp = &class_file.constant_pool;
+
+        let methods: Vec<(String, String)> = class_ref
+            .methods()
+            .unwrap()
+            .map(|method| {
+                (
+                    class_ref.utf8(method.name_index()).unwrap().into_owned(),
+                    class_ref
+                        .utf8(method.descriptor_index())
+                        .unwrap()
+                        .into_owned(),
+                )
+            })
+            .collect();
+        let expected: Vec<(String, String)> = class_file
+            .methods
+            .iter()
+            .map(|method| {
+                (
+                    method.name(cp).unwrap(),
+                    cp.utf8(&method.descriptor_index).unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(methods, expected, "{identifier}");
+
+        assert_eq!(class_ref.fields().unwrap().count(), class_file.fields.len());
+        for (index, info) in cp.iter() {
+            // compared as text, NaN constants are not equal to themselves
+            let constant = class_ref.constant(index.value() as u16).unwrap();
+            assert_eq!(format!("{constant:?}"), format!("{info:?}"));
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::{collections::HashMap, env, fs::File, io::Read, path::PathBuf};
+
+use anyhow::{Context, Result};
+use jvm::{
+    parser::class::{AccessFlag, ClassFile, Version},
+    verifier::{verify, ClassHierarchy},
+    ClassIdentifier,
+};
+use zip::ZipArchive;
+
+/// Super class and whether it is an interface, by class name in internal form.
+struct Hierarchy {
+    classes: HashMap<String, (Option<String>, bool)>,
+}
+
+impl ClassHierarchy for Hierarchy {
+    fn super_class(&self, class: &str) -> Result<Option<String>> {
+        Ok(self
+            .classes
+            .get(class)
+            .context(format!("{class} not found"))?
+            .0
+            .clone())
+    }
+
+    fn is_interface(&self, class: &str) -> Result<bool> {
+        Ok(self
+            .classes
+            .get(class)
+            .context(format!("{class} not found"))?
+            .1)
+    }
+}
+
+fn internal_name(identifier: &ClassIdentifier) -> String {
+    format!(
+        "{}/{}",
+        identifier.package.name.replace('.', "/"),
+        identifier.name
+    )
+}
+
+fn java_base() -> (Vec<ClassFile>, Hierarchy) {
+    let jmod = PathBuf::from(env::var("JAVA_HOME").unwrap()).join("jmods/java.base.jmod");
+    let mut archive = ZipArchive::new(File::open(jmod).unwrap()).unwrap();
+
+    let mut class_files = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).unwrap();
+        let Some(class_name) = entry
+            .name()
+            .strip_prefix("classes/")
+            .and_then(|n| n.strip_suffix(".class"))
+            .map(|n| n.to_string())
+        else {
+            continue;
+        };
+        if class_name.ends_with("module-info") {
+            continue;
+        }
+
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data).unwrap();
+        class_files
+            .push(ClassFile::new(&data, ClassIdentifier::from_utf8(class_name).unwrap()).unwrap());
+    }
+
+    let hierarchy = Hierarchy {
+        classes: class_files
+            .iter()
+            .map(|class_file| {
+                (
+                    internal_name(&class_file.this_class),
+                    (
+                        class_file.super_class.as_ref().map(internal_name),
+                        class_file.access_flags.contains(&AccessFlag::Interface),
+                    ),
+                )
+            })
+            .collect(),
+    };
+
+    (class_files, hierarchy)
+}
+
+#[test]
+fn test_verify_java_base() {
+    let (class_files, hierarchy) = java_base();
+
+    for class_file in &class_files {
+        verify(class_file, &hierarchy).unwrap();
+    }
+
+    assert!(class_files.len() > 6000);
+}
+
+#[test]
+fn test_infer_java_base() {
+    let (mut class_files, hierarchy) = java_base();
+
+    // verified as legacy class files, the StackMapTable is ignored
+    for class_file in &mut class_files {
+        class_file.version = Version::new(49, 0);
+        verify(class_file, &hierarchy).unwrap();
+    }
+}